@@ -0,0 +1,167 @@
+//! Security advisories from the [RustSec advisory database](https://rustsec.org)
+//!
+//! Advisories are fetched from [OSV](https://osv.dev), which mirrors the RustSec database along
+//! with the version ranges each advisory affects, so only advisories for the queried version
+//! are returned
+
+use crate::{error::Error, Release, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// The OSV API URL
+pub const OSV_API_URL: &str = "https://api.osv.dev/v1";
+
+/// What an advisory warns about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdvisoryKind {
+    /// A security vulnerability
+    Vulnerability,
+    /// The crate is no longer maintained
+    Unmaintained,
+    /// The crate exposes undefined behavior through a safe API
+    Unsound,
+    /// Any other informational advisory
+    Notice,
+}
+
+impl std::fmt::Display for AdvisoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdvisoryKind::Vulnerability => f.write_str("vulnerability"),
+            AdvisoryKind::Unmaintained => f.write_str("unmaintained"),
+            AdvisoryKind::Unsound => f.write_str("unsound"),
+            AdvisoryKind::Notice => f.write_str("notice"),
+        }
+    }
+}
+
+/// An advisory affecting a release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Advisory {
+    /// The advisory's id, e.g. `RUSTSEC-2021-0001`
+    pub id: String,
+    /// Other ids for the same advisory, e.g. CVE numbers
+    pub aliases: Vec<String>,
+    /// A one line summary of the advisory
+    pub summary: Option<String>,
+    /// What the advisory warns about
+    pub kind: AdvisoryKind,
+}
+
+impl std::fmt::Display for Advisory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { id, kind, .. } = self;
+        write!(f, "{id} ({kind})")?;
+
+        if let Some(summary) = &self.summary {
+            write!(f, ": {summary}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    vulns: Vec<OsvEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvEntry {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    database_specific: Option<DatabaseSpecific>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DatabaseSpecific {
+    #[serde(default)]
+    informational: Option<String>,
+}
+
+impl Advisory {
+    /// Parse the advisories from the json response of OSV's `/query` endpoint
+    ///
+    /// OSV also has advisories from other databases, but only RustSec advisories are returned
+    pub fn from_json<T>(content: T) -> Result<Vec<Self>>
+    where
+        T: AsRef<str>,
+    {
+        let response: QueryResponse =
+            serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)?;
+
+        Ok(response
+            .vulns
+            .into_iter()
+            .filter(|entry| entry.id.starts_with("RUSTSEC-"))
+            .map(|entry| {
+                let informational = entry
+                    .database_specific
+                    .and_then(|specific| specific.informational);
+                let kind = match informational.as_deref() {
+                    None => AdvisoryKind::Vulnerability,
+                    Some("unmaintained") => AdvisoryKind::Unmaintained,
+                    Some("unsound") => AdvisoryKind::Unsound,
+                    Some(_) => AdvisoryKind::Notice,
+                };
+
+                Advisory {
+                    id: entry.id,
+                    aliases: entry.aliases,
+                    summary: entry.summary,
+                    kind,
+                }
+            })
+            .collect())
+    }
+}
+
+/// A client for the OSV API, or a compatible mirror of it
+#[derive(Debug, Clone)]
+pub struct Client {
+    url: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(OSV_API_URL)
+    }
+}
+
+impl Client {
+    /// Create a client for an OSV API at a custom URL
+    pub fn new<T>(url: T) -> Self
+    where
+        String: From<T>,
+    {
+        let url = String::from(url).trim_end_matches('/').to_owned();
+
+        Self { url }
+    }
+
+    /// Fetch the advisories affecting a specific version of a crate
+    pub fn advisories(&self, name: &str, version: &Version) -> Result<Vec<Advisory>> {
+        let query = json!({
+            "package": { "name": name, "ecosystem": "crates.io" },
+            "version": version.to_string(),
+        });
+
+        Advisory::from_json(crate::post(&format!("{}/query", self.url), &query)?)
+    }
+}
+
+impl Release {
+    /// Fetch the advisories affecting this release
+    ///
+    /// See [`Client::advisories`] to use another OSV API
+    pub fn advisories(&self) -> Result<Vec<Advisory>> {
+        Client::default().advisories(&self.name, &self.vers)
+    }
+}
@@ -0,0 +1,648 @@
+//! A client for the crates.io web API, for information that isn't in the registry index
+//!
+//! The index only describes releases. Descriptions, links, download counts, and owners are only
+//! available from the API. crates.io asks that API users make at most one request per second, so
+//! avoid using this for large numbers of crates
+//!
+//! More info on the API can be found in the [crates.io docs](https://crates.io/data-access)
+
+use crate::{
+    download::RegistryConfig, error::Error, validate_crate_name, DependencyKind, Package, Result,
+    CRATES_IO_API_URL,
+};
+use semver::Version;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The largest page size crates.io allows for paginated endpoints
+const PER_PAGE: usize = 100;
+
+/// A client for a crates.io compatible web API
+#[derive(Debug, Clone)]
+pub struct Client {
+    api_url: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(CRATES_IO_API_URL)
+    }
+}
+
+impl Client {
+    /// Create a client for an API at a custom URL, e.g. `https://crates.io/api/v1`
+    pub fn new<T>(api_url: T) -> Self
+    where
+        String: From<T>,
+    {
+        let api_url = String::from(api_url).trim_end_matches('/').to_owned();
+
+        Self { api_url }
+    }
+
+    /// Create a client for the API of the registry with this index, found from it's `config.json`
+    ///
+    /// Fails with [`Error::NoApi`] if the registry doesn't advertise an API
+    pub fn from_index(index_url: &str) -> Result<Self> {
+        Self::from_config(&RegistryConfig::fetch(index_url)?)
+            .ok_or_else(|| Error::NoApi(index_url.to_owned()))
+    }
+
+    /// Create a client for the API in a registry's configuration, if it has one
+    pub fn from_config(config: &RegistryConfig) -> Option<Self> {
+        config
+            .api
+            .as_ref()
+            .map(|api| Self::new(format!("{}/api/v1", api.trim_end_matches('/'))))
+    }
+
+    /// Search the registry for crates matching `query`, returning up to `limit` results
+    ///
+    /// Results are ordered by relevance, as ranked by the registry
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let query = encode(query);
+        let mut results = Vec::new();
+        let mut page = 1;
+
+        // Pages are offset by their size, so every page is requested at the same size
+        let per_page = limit.min(PER_PAGE);
+        while results.len() < limit {
+            let url = format!(
+                "{}/crates?q={query}&page={page}&per_page={per_page}",
+                self.api_url
+            );
+            let found = SearchResult::from_json(crate::get(&url)?)?;
+
+            let last_page = found.len() < per_page;
+            results.extend(found);
+            if last_page {
+                break;
+            }
+
+            page += 1;
+        }
+
+        results.truncate(limit);
+
+        Ok(results)
+    }
+
+    /// Fetch the descriptive metadata of a crate
+    pub fn crate_info(&self, name: &str) -> Result<CrateInfo> {
+        CrateInfo::from_json(self.get_crate(name, "")?)
+    }
+
+    /// Fetch the metadata, download counts, and version details of a crate in one request
+    pub fn crate_details(&self, name: &str) -> Result<CrateDetails> {
+        CrateDetails::from_json(self.get_crate(name, "")?)
+    }
+
+    /// Fetch the users and teams that can publish new versions of a crate
+    pub fn owners(&self, name: &str) -> Result<Vec<Owner>> {
+        Owner::from_json(self.get_crate(name, "/owners")?)
+    }
+
+    /// Fetch the download counts of a crate and each of it's versions
+    pub fn downloads(&self, name: &str) -> Result<Downloads> {
+        Downloads::from_json(self.get_crate(name, "")?)
+    }
+
+    /// Fetch the number of crates that depend on any version of a crate
+    pub fn reverse_dependency_count(&self, name: &str) -> Result<u64> {
+        Ok(self.reverse_dependencies_page(name, 1, 1)?.total)
+    }
+
+    /// Fetch every crate that depends on a crate, fetching one page of results per request
+    ///
+    /// Popular crates can have tens of thousands of dependents, so prefer
+    /// [`Client::reverse_dependency_count`] when only the number is needed
+    pub fn reverse_dependencies(&self, name: &str) -> Result<ReverseDependencies> {
+        let mut all = self.reverse_dependencies_page(name, 1, PER_PAGE)?;
+        let mut last_page = all.dependents.len() < PER_PAGE;
+        let mut page = 1;
+
+        // A short page is the last one, even if the total says there are more
+        while !last_page && (all.dependents.len() as u64) < all.total {
+            page += 1;
+
+            let next = self.reverse_dependencies_page(name, page, PER_PAGE)?;
+            last_page = next.dependents.len() < PER_PAGE;
+            all.dependents.extend(next.dependents);
+        }
+
+        Ok(all)
+    }
+
+    fn reverse_dependencies_page(
+        &self,
+        name: &str,
+        page: usize,
+        per_page: usize,
+    ) -> Result<ReverseDependencies> {
+        let path = format!("/reverse_dependencies?page={page}&per_page={per_page}");
+
+        ReverseDependencies::from_json(self.get_crate(name, &path)?)
+    }
+
+    /// Fetch the details of each version of a crate that aren't in the index
+    pub fn versions(&self, name: &str) -> Result<BTreeMap<Version, VersionInfo>> {
+        VersionInfo::from_json(self.get_crate(name, "")?)
+    }
+
+    /// Fetch when each version of a crate was published, as RFC 3339 timestamps
+    pub fn publish_dates(&self, name: &str) -> Result<BTreeMap<Version, String>> {
+        Ok(self
+            .versions(name)?
+            .into_iter()
+            .map(|(version, info)| (version, info.published))
+            .collect())
+    }
+
+    /// Fetch the license of each version of a crate that has one
+    pub fn licenses(&self, name: &str) -> Result<BTreeMap<Version, String>> {
+        Ok(self
+            .versions(name)?
+            .into_iter()
+            .filter_map(|(version, info)| Some((version, info.license?)))
+            .collect())
+    }
+
+    /// Fetch a path under `/crates/{name}`, after checking that the name is valid
+    pub(crate) fn get_crate(&self, name: &str, path: &str) -> Result<String> {
+        validate_crate_name(name).map_err(Error::InvalidName)?;
+
+        crate::get(&format!("{}/crates/{name}{path}", self.api_url))
+    }
+}
+
+/// Descriptive metadata of a crate, from the manifest of it's latest release
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateInfo {
+    /// The canonical name of the crate
+    pub name: String,
+    /// A short description of the crate
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Keywords used to find the crate in searches
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Slugs of the categories the crate is listed in, e.g. `encoding`
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// The crate's homepage
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// The crate's source repository
+    #[serde(default)]
+    pub repository: Option<String>,
+    /// The crate's documentation
+    #[serde(default)]
+    pub documentation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateResponse<T> {
+    #[serde(rename = "crate")]
+    krate: T,
+}
+
+impl CrateInfo {
+    /// Parse crate metadata from the json response of the `/crates/{name}` endpoint
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        from_crate_response(content.as_ref())
+    }
+}
+
+/// Everything the `/crates/{name}` endpoint describes about a crate, see
+/// [`Client::crate_details`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateDetails {
+    /// The crate's descriptive metadata
+    pub info: CrateInfo,
+    /// The crate's download counts
+    pub downloads: Downloads,
+    /// The details of each version of the crate
+    pub versions: BTreeMap<Version, VersionInfo>,
+}
+
+impl CrateDetails {
+    /// Parse everything from the json response of the `/crates/{name}` endpoint
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let content = content.as_ref();
+
+        Ok(Self {
+            info: CrateInfo::from_json(content)?,
+            downloads: Downloads::from_json(content)?,
+            versions: VersionInfo::from_json(content)?,
+        })
+    }
+}
+
+/// A crate found by [`Client::search`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// The name of the crate
+    pub name: String,
+    /// A short description of the crate
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The latest stable version of the crate, or the latest pre-release if it has no stable ones
+    pub latest: Version,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    crates: Vec<SearchCrate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchCrate {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    max_version: Version,
+    #[serde(default)]
+    max_stable_version: Option<Version>,
+}
+
+impl SearchResult {
+    /// Parse results from the json response of the `/crates?q=` endpoint
+    pub fn from_json<T>(content: T) -> Result<Vec<Self>>
+    where
+        T: AsRef<str>,
+    {
+        let response: SearchResponse =
+            serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)?;
+
+        Ok(response
+            .crates
+            .into_iter()
+            .map(|krate| SearchResult {
+                name: krate.name,
+                description: krate.description,
+                latest: krate.max_stable_version.unwrap_or(krate.max_version),
+            })
+            .collect())
+    }
+}
+
+/// Percent-encode a string for use in a query parameter
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(byte).to_string()
+            }
+            b' ' => "+".to_owned(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Whether an owner is an individual user or a team
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerKind {
+    User,
+    Team,
+}
+
+impl std::fmt::Display for OwnerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OwnerKind::User => f.write_str("user"),
+            OwnerKind::Team => f.write_str("team"),
+        }
+    }
+}
+
+/// A user or team that owns a crate, see [`Client::owners`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Owner {
+    /// The registry's id for the owner
+    pub id: u64,
+    /// The owner's login, e.g. `dtolnay` or `github:serde-rs:publish`
+    pub login: String,
+    /// Whether the owner is a user or a team
+    pub kind: OwnerKind,
+    /// The owner's display name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// A link to the owner's profile
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OwnersResponse {
+    users: Vec<Owner>,
+}
+
+impl Owner {
+    /// Parse owners from the json response of the `/crates/{name}/owners` endpoint
+    pub fn from_json<T>(content: T) -> Result<Vec<Self>>
+    where
+        T: AsRef<str>,
+    {
+        serde_json::from_str::<OwnersResponse>(content.as_ref())
+            .map(|response| response.users)
+            .map_err(Error::Deserialize)
+    }
+}
+
+/// Crates that depend on a crate, see [`Client::reverse_dependencies`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReverseDependencies {
+    /// The number of dependents the registry knows about, which may be more than were fetched
+    pub total: u64,
+    /// The latest version of each dependent crate that depends on the crate
+    pub dependents: Vec<ReverseDependency>,
+}
+
+/// A crate version that depends on another crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReverseDependency {
+    /// The name of the dependent crate
+    pub name: String,
+    /// The version of the dependent crate with this dependency
+    pub vers: Version,
+    /// The requirement the dependent has on the crate
+    pub req: String,
+    /// The kind of dependency
+    pub kind: DependencyKind,
+    /// Whether the dependency is optional
+    pub optional: bool,
+    /// Downloads of the dependent crate, useful for ranking by impact
+    pub downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReverseDependenciesResponse {
+    dependencies: Vec<ApiDependency>,
+    versions: Vec<ApiDependentVersion>,
+    meta: Meta,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiDependency {
+    version_id: u64,
+    req: String,
+    #[serde(default)]
+    kind: DependencyKind,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiDependentVersion {
+    id: u64,
+    #[serde(rename = "crate")]
+    krate: String,
+    num: Version,
+}
+
+#[derive(Debug, Deserialize)]
+struct Meta {
+    total: u64,
+}
+
+impl ReverseDependencies {
+    /// Parse a page of the json response of the `/crates/{name}/reverse_dependencies` endpoint
+    ///
+    /// Each dependency refers to the dependent version by id, so dependencies without a matching
+    /// entry in the response's `versions` are skipped
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let response: ReverseDependenciesResponse =
+            serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)?;
+
+        let versions = response
+            .versions
+            .into_iter()
+            .map(|version| (version.id, (version.krate, version.num)))
+            .collect::<BTreeMap<_, _>>();
+
+        let dependents = response
+            .dependencies
+            .into_iter()
+            .filter_map(|dep| {
+                let (name, vers) = versions.get(&dep.version_id)?.clone();
+                Some(ReverseDependency {
+                    name,
+                    vers,
+                    req: dep.req,
+                    kind: dep.kind,
+                    optional: dep.optional,
+                    downloads: dep.downloads,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            total: response.meta.total,
+            dependents,
+        })
+    }
+}
+
+/// Download counts of a crate, see [`Client::downloads`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Downloads {
+    /// Downloads of all versions since the crate was published
+    pub total: u64,
+    /// Downloads of all versions in the last 90 days, if the registry tracks them
+    pub recent: Option<u64>,
+    /// Downloads of each version since it was published
+    pub versions: BTreeMap<Version, u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadsResponse {
+    #[serde(rename = "crate")]
+    krate: CrateDownloads,
+    #[serde(default)]
+    versions: Vec<ApiVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDownloads {
+    downloads: u64,
+    #[serde(default)]
+    recent_downloads: Option<u64>,
+}
+
+/// A version in the `versions` list of a `/crates/{name}` response
+#[derive(Debug, Deserialize)]
+struct ApiVersion {
+    num: Version,
+    downloads: u64,
+    created_at: String,
+    #[serde(default)]
+    license: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionsResponse {
+    #[serde(default)]
+    versions: Vec<ApiVersion>,
+}
+
+/// Details of a version that aren't in the index, see [`Client::versions`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// When the version was published, as an RFC 3339 timestamp
+    pub published: String,
+    /// The SPDX license expression from the version's manifest, e.g. `MIT OR Apache-2.0`
+    ///
+    /// Versions that only have a `license-file` don't have one
+    pub license: Option<String>,
+}
+
+impl VersionInfo {
+    /// Parse the details of each version from the json response of the `/crates/{name}` endpoint
+    pub fn from_json<T>(content: T) -> Result<BTreeMap<Version, Self>>
+    where
+        T: AsRef<str>,
+    {
+        let response: VersionsResponse =
+            serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)?;
+
+        Ok(response
+            .versions
+            .into_iter()
+            .map(|version| {
+                let info = VersionInfo {
+                    published: version.created_at,
+                    license: version.license,
+                };
+                (version.num, info)
+            })
+            .collect())
+    }
+}
+
+impl Downloads {
+    /// Parse download counts from the json response of the `/crates/{name}` endpoint
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let response: DownloadsResponse =
+            serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)?;
+
+        Ok(Self {
+            total: response.krate.downloads,
+            recent: response.krate.recent_downloads,
+            versions: response
+                .versions
+                .into_iter()
+                .map(|version| (version.num, version.downloads))
+                .collect(),
+        })
+    }
+
+    /// Return the downloads of a specific version, if the registry has it
+    pub fn version(&self, version: &Version) -> Option<u64> {
+        self.versions.get(version).copied()
+    }
+}
+
+/// Parse the `crate` object of a `/crates/{name}` response
+pub(crate) fn from_crate_response<T: DeserializeOwned>(content: &str) -> Result<T> {
+    serde_json::from_str::<CrateResponse<T>>(content)
+        .map(|response| response.krate)
+        .map_err(Error::Deserialize)
+}
+
+impl Package {
+    /// Fetch the descriptive metadata for this package from crates.io
+    ///
+    /// See [`Client::crate_info`] to use another registry's API
+    pub fn crate_info(&self) -> Result<CrateInfo> {
+        Client::default().crate_info(self.name())
+    }
+
+    /// Fetch the owners of this package from crates.io
+    ///
+    /// See [`Client::owners`] to use another registry's API
+    pub fn owners(&self) -> Result<Vec<Owner>> {
+        Client::default().owners(self.name())
+    }
+
+    /// Set the publish date and license of each release from the details fetched with
+    /// [`Client::versions`], so both come from a single request
+    ///
+    /// Releases without details are left without a date or license
+    pub fn with_version_info(mut self, versions: &BTreeMap<Version, VersionInfo>) -> Self {
+        for release in &mut self.releases {
+            let info = versions.get(&release.vers);
+            release.published = info.map(|info| info.published.clone());
+            release.license = info.and_then(|info| info.license.clone());
+        }
+
+        self
+    }
+
+    /// Fetch the publish date of each release from crates.io, setting [`Release::published`]
+    ///
+    /// Releases the API doesn't know about are left without a date. See
+    /// [`Package::with_version_info`] to use another registry's API
+    pub fn with_publish_dates(self) -> Result<Self> {
+        let dates = Client::default().publish_dates(self.name())?;
+
+        Ok(self.with_dates(&dates))
+    }
+
+    /// Set the publish date of each release that has one in `dates`
+    pub(crate) fn with_dates(mut self, dates: &BTreeMap<Version, String>) -> Self {
+        for release in &mut self.releases {
+            release.published = dates.get(&release.vers).cloned();
+        }
+
+        self
+    }
+
+    /// Fetch the license of each release from crates.io, setting [`Release::license`]
+    ///
+    /// Releases the API doesn't know about, or that only have a `license-file`, are left without
+    /// a license. See [`Package::with_version_info`] to use another registry's API
+    pub fn with_licenses(self) -> Result<Self> {
+        let licenses = Client::default().licenses(self.name())?;
+
+        Ok(self.with_license_map(&licenses))
+    }
+
+    /// Set the license of each release that has one in `licenses`
+    pub(crate) fn with_license_map(mut self, licenses: &BTreeMap<Version, String>) -> Self {
+        for release in &mut self.releases {
+            release.license = licenses.get(&release.vers).cloned();
+        }
+
+        self
+    }
+
+    /// Fetch the crates that depend on this package from crates.io
+    ///
+    /// See [`Client::reverse_dependencies`] to use another registry's API
+    pub fn reverse_dependencies(&self) -> Result<ReverseDependencies> {
+        Client::default().reverse_dependencies(self.name())
+    }
+
+    /// Fetch the download counts for this package from crates.io
+    ///
+    /// See [`Client::downloads`] to use another registry's API
+    pub fn downloads(&self) -> Result<Downloads> {
+        Client::default().downloads(self.name())
+    }
+}
@@ -0,0 +1,215 @@
+//! Checking whether a crate name can be published to a registry
+
+#[cfg(feature = "api")]
+use crate::{api, error::Error, CRATES_IO_INDEX_URL};
+use crate::{error::NameError, normalize_name, validate_crate_name, Query, Result};
+use serde::Serialize;
+
+/// Names crates.io reserves, either for the standard library and compiler, or because they
+/// can't be used as file names on Windows
+pub const RESERVED_NAMES: &[&str] = &[
+    "alloc",
+    "arena",
+    "ast",
+    "builtins",
+    "collections",
+    "compiler-builtins",
+    "compiler-rt",
+    "compiletest",
+    "core",
+    "coretest",
+    "debug",
+    "driver",
+    "flate",
+    "fmt_macros",
+    "grammar",
+    "graphviz",
+    "macro",
+    "macros",
+    "proc_macro",
+    "rbml",
+    "rust-installer",
+    "rustbook",
+    "rustc",
+    "rustc_back",
+    "rustc_borrowck",
+    "rustc_driver",
+    "rustc_llvm",
+    "rustc_resolve",
+    "rustc_trans",
+    "rustc_typeck",
+    "rustdoc",
+    "rustllvm",
+    "rustuv",
+    "serialize",
+    "std",
+    "syntax",
+    "test",
+    "unicode",
+    // Reserved file names on Windows
+    "aux",
+    "com1",
+    "com2",
+    "com3",
+    "com4",
+    "com5",
+    "com6",
+    "com7",
+    "com8",
+    "com9",
+    "con",
+    "lpt1",
+    "lpt2",
+    "lpt3",
+    "lpt4",
+    "lpt5",
+    "lpt6",
+    "lpt7",
+    "lpt8",
+    "lpt9",
+    "nul",
+    "prn",
+];
+
+/// Whether a name is reserved, see [`RESERVED_NAMES`]
+///
+/// Names are compared the way the registry compares them, see [`normalize_name`]
+pub fn is_reserved(name: &str) -> bool {
+    let name = normalize_name(name);
+
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| normalize_name(reserved) == name)
+}
+
+/// The most `-` and `_` separators a name can have for every spelling of it to be looked up in
+/// the index, since each one doubles the number of spellings
+pub const MAX_SEPARATORS: usize = 4;
+
+/// Return every spelling of a name with each separator as either `-` or `_`, starting with the
+/// name as given
+///
+/// Registries consider all of them the same name. Returns `None` if the name has more than
+/// [`MAX_SEPARATORS`] separators
+pub fn separator_spellings(name: &str) -> Option<Vec<String>> {
+    let separators = name
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '-' | '_'))
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+    if separators.len() > MAX_SEPARATORS {
+        return None;
+    }
+
+    let mut spellings = vec![name.to_owned()];
+    for combination in 0..1_usize << separators.len() {
+        let mut spelling = name.as_bytes().to_vec();
+        for (bit, index) in separators.iter().enumerate() {
+            spelling[*index] = if combination & (1 << bit) == 0 {
+                b'-'
+            } else {
+                b'_'
+            };
+        }
+
+        let spelling = String::from_utf8(spelling).expect("separators are ascii");
+        if !spellings.contains(&spelling) {
+            spellings.push(spelling);
+        }
+    }
+
+    Some(spellings)
+}
+
+/// Whether a crate name can be published, see [`Query::availability`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "detail", rename_all = "lowercase")]
+pub enum Availability {
+    /// No crate with a conflicting name exists
+    Available,
+    /// A crate with a conflicting name exists, holding it's canonical name
+    Taken(String),
+    /// The name isn't a valid crate name
+    Invalid(NameError),
+    /// The name is reserved
+    Reserved,
+    /// Whether a crate with a conflicting name exists couldn't be checked, because the registry
+    /// has no web API and the name has too many separators to look up every spelling of it
+    Unknown,
+}
+
+impl std::fmt::Display for Availability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Availability::Available => write!(f, "available"),
+            Availability::Taken(name) => write!(f, "taken by `{name}`"),
+            Availability::Invalid(error) => write!(f, "invalid, {error}"),
+            Availability::Reserved => write!(f, "reserved"),
+            Availability::Unknown => write!(f, "unknown, too many separators to check the index"),
+        }
+    }
+}
+
+impl Query {
+    /// Check whether the name of this query is free to publish on it's registry
+    ///
+    /// Registries consider names conflicting if they only differ by case or by `-` and `_`, so
+    /// `serde-json` is reported as taken by `serde_json`. Any version requirement is ignored
+    ///
+    /// With the `api` feature, the registry's web API is asked, since it matches names
+    /// regardless of separators. Otherwise, or if the registry has no API, every
+    /// [spelling](separator_spellings) of the name is looked up in the index, and
+    /// [`Availability::Unknown`] is returned if there are too many of them
+    pub fn availability(&self) -> Result<Availability> {
+        if let Err(error) = validate_crate_name(self.name()) {
+            return Ok(Availability::Invalid(error));
+        }
+
+        if is_reserved(self.name()) {
+            return Ok(Availability::Reserved);
+        }
+
+        #[cfg(feature = "api")]
+        if let Some(availability) = self.availability_from_api()? {
+            return Ok(availability);
+        }
+
+        let Some(spellings) = separator_spellings(self.name()) else {
+            return Ok(Availability::Unknown);
+        };
+        for spelling in spellings {
+            let mut query = self.clone().separator_fallback(false);
+            query.name = spelling;
+
+            match query.package() {
+                Ok(package) => return Ok(Availability::Taken(package.name().to_owned())),
+                Err(error) if error.is_not_found() => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(Availability::Available)
+    }
+
+    /// Check the name with the registry's web API, or return `None` if the registry has none
+    #[cfg(feature = "api")]
+    fn availability_from_api(&self) -> Result<Option<Availability>> {
+        let client = if self.index_url() == CRATES_IO_INDEX_URL {
+            api::Client::default()
+        } else {
+            match api::Client::from_index(self.index_url()) {
+                Ok(client) => client,
+                // Not every registry has a `config.json`, like some mirrors of an index
+                Err(Error::NoApi(_)) => return Ok(None),
+                Err(error) if error.is_not_found() => return Ok(None),
+                Err(error) => return Err(error),
+            }
+        };
+
+        match client.crate_details(self.name()) {
+            Ok(details) => Ok(Some(Availability::Taken(details.info.name))),
+            Err(error) if error.is_not_found() => Ok(Some(Availability::Available)),
+            Err(error) => Err(error),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use cargo_lookup::{normalize_name, Package, Query};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Packages fetched during one run, so a crate depended on by many others is only fetched once
+///
+/// Packages are kept by index and name, since registries can have crates with the same name
+#[derive(Debug, Default)]
+pub struct Packages {
+    fetched: Mutex<HashMap<(String, String), Arc<Package>>>,
+}
+
+impl Packages {
+    /// Fetch the package of a query, or return it from the packages fetched earlier
+    ///
+    /// Warnings about the package's index file are reported the first time it's fetched
+    pub fn fetch(&self, query: &Query) -> cargo_lookup::Result<Arc<Package>> {
+        let key = (query.index_url().to_owned(), normalize_name(query.name()));
+
+        if let Some(package) = self.fetched.lock().expect("cache lock").get(&key) {
+            return Ok(Arc::clone(package));
+        }
+
+        // Fetched without holding the lock, so other threads aren't held up. Two threads may
+        // fetch the same package at once, which is harmless
+        let package = Arc::new(query.package()?);
+        crate::report_warnings(&package);
+
+        let mut fetched = self.fetched.lock().expect("cache lock");
+        Ok(Arc::clone(fetched.entry(key).or_insert(package)))
+    }
+}
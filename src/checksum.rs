@@ -0,0 +1,77 @@
+//! Verifying downloaded `.crate` files against the checksums published in the index
+
+use crate::{error::Error, validate_crate_name, Release, Result};
+use semver::Version;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+/// Parse the name and version from a `.crate` file name like `serde_json-1.0.140.crate`
+///
+/// Both names and versions can contain `-`, so the name is everything before the first `-` that
+/// starts a valid version. Returns `None` if the file name isn't in this form
+///
+/// ## Examples
+///
+/// ```
+/// use cargo_lookup::checksum::parse_crate_file_name;
+///
+/// let (name, version) = parse_crate_file_name("wasm-bindgen-0.2.0-rc.1.crate").unwrap();
+/// assert_eq!(name, "wasm-bindgen");
+/// assert_eq!(version.to_string(), "0.2.0-rc.1");
+/// assert!(parse_crate_file_name("serde.tar.gz").is_none());
+/// ```
+pub fn parse_crate_file_name(file_name: &str) -> Option<(String, Version)> {
+    let stem = file_name.strip_suffix(".crate")?;
+
+    stem.match_indices('-').find_map(|(index, _)| {
+        let (name, version) = (&stem[..index], &stem[index + 1..]);
+        validate_crate_name(name).ok()?;
+
+        Some((name.to_owned(), version.parse().ok()?))
+    })
+}
+
+impl Release {
+    /// Check that the contents of this release's `.crate` file match its published checksum
+    ///
+    /// Fails with [`Error::ChecksumMismatch`] if the SHA256 checksum of `bytes` differs from
+    /// [`Release::cksum`]
+    pub fn verify_checksum(&self, bytes: &[u8]) -> Result<()> {
+        self.compare_checksum(Sha256::digest(bytes).as_slice())
+    }
+
+    /// Check that the contents read from `reader` match this release's published checksum
+    ///
+    /// The contents are hashed as they are read, so the `.crate` file doesn't have to fit in
+    /// memory. See [`Release::verify_checksum`]
+    pub fn verify_checksum_reader<R: Read>(&self, mut reader: R) -> Result<()> {
+        let mut hasher = Sha256::new();
+        let mut buffer = [0; 8192];
+
+        loop {
+            let read = reader.read(&mut buffer).map_err(Error::Io)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        self.compare_checksum(hasher.finalize().as_slice())
+    }
+
+    fn compare_checksum(&self, digest: &[u8]) -> Result<()> {
+        let actual = digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+
+        if actual.eq_ignore_ascii_case(&self.cksum) {
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch {
+                expected: self.cksum.clone(),
+                actual,
+            })
+        }
+    }
+}
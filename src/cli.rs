@@ -1,4 +1,11 @@
-use clap::{Parser, ValueEnum};
+use crate::{
+    table::{DEFAULT_COLUMNS, TABLE_COLUMNS},
+    template::{Field, Template},
+};
+use cargo_lookup::{graph, Dependency, DependencyKind, SelectionPolicy, YankPolicy};
+use clap::{Parser, Subcommand, ValueEnum};
+use semver::Version;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[clap(name = "cargo", bin_name = "cargo")]
@@ -9,30 +16,499 @@ pub enum Cli {
 
 #[derive(Debug, Parser)]
 pub struct Options {
+    #[clap(subcommand)]
+    pub(crate) command: Option<Command>,
     /// Packages to query
     pub(crate) packages: Vec<String>,
+    /// Also query the registry dependencies declared in this `Cargo.toml`, with their version
+    /// requirements and features. For a workspace root, every member's dependencies are queried
+    #[clap(long, value_name = "PATH", verbatim_doc_comment)]
+    pub(crate) manifest_path: Option<PathBuf>,
     /// Output type
-    #[clap(short = 't', long = "type", value_name = "TYPE")]
+    #[clap(short = 't', long = "type", value_name = "TYPE", global = true)]
     pub(crate) kind: Option<Type>,
     /// Output format
-    #[clap(short, long, default_value = "default")]
+    #[clap(short, long, default_value = "default", global = true)]
     pub(crate) format: Format,
+    /// Print each release by filling in a template, e.g. `"{name} {version} {msrv}"`.
+    /// Placeholders: name, version, yanked, msrv, features, deps, dep_count, checksum,
+    /// license, published, and size. Write `{{` and `}}` for literal braces.
+    /// Takes the place of `--type`, except with `--type=versions` where it's filled in for
+    /// each listed release
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with = "format",
+        verbatim_doc_comment
+    )]
+    pub(crate) format_str: Option<Template>,
+    /// Columns to show with `--format=csv`, `tsv`, or `table`, named like `--format-str`
+    /// placeholders. Defaults to name,version,yanked,msrv,dep_count,checksum, or
+    /// name,version,yanked,msrv,dep_count,feature_count for `table`
+    #[clap(
+        long,
+        value_name = "FIELD",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    pub(crate) columns: Vec<Field>,
     /// Use a custom crate index URL
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     pub(crate) index_url: Option<String>,
+    /// Use the index of a registry named in cargo's config, like `cargo add --registry`
+    #[clap(long, value_name = "NAME", global = true, conflicts_with = "index_url")]
+    pub(crate) registry: Option<String>,
     /// Careful, this may take a while!
     /// Display info on queried package dependencies that are recursively resolved
     #[clap(short, long, verbatim_doc_comment)]
     pub(crate) recursive: bool,
+    /// Resolve queried packages and their dependencies into one consistent set of releases,
+    /// unifying compatible requirements like cargo does
+    #[clap(long, conflicts_with_all = ["recursive", "max_depth"], verbatim_doc_comment)]
+    pub(crate) resolve: bool,
     /// Maximum depth when recursively querying dependencies
     #[clap(short, long)]
     pub(crate) max_depth: Option<usize>,
     /// Delimiter when printing features or dependencies
     #[clap(short, long, default_value = " ")]
     pub(crate) delim: String,
+    /// Features of the queried packages deciding which optional dependencies are shown and followed
+    #[clap(short = 'F', long, value_delimiter = ',')]
+    pub(crate) features: Vec<String>,
+    /// Don't enable the `default` feature when working out which dependencies are enabled
+    #[clap(long)]
+    pub(crate) no_default_features: bool,
+    /// Only include dependencies used when building for this target triple, e.g.
+    /// `x86_64-unknown-linux-gnu`. Dependencies for every target are included if omitted
+    #[clap(long, value_name = "TRIPLE", global = true, verbatim_doc_comment)]
+    pub(crate) target: Option<String>,
+    /// Only show and follow dependencies of these kinds. Includes all kinds if omitted
+    ///
+    /// Dev dependencies are only followed for the queried packages, since cargo never builds
+    /// the dev dependencies of a package's dependencies
+    #[clap(long, value_name = "KIND", value_delimiter = ',')]
+    pub(crate) dep_kind: Vec<DepKind>,
+    /// Leave out dev dependencies, equivalent to `--dep-kind=normal,build`
+    #[clap(long, conflicts_with = "dep_kind")]
+    pub(crate) no_dev_deps: bool,
     /// Ignore missing packages
     #[clap(short = 'g', long)]
     pub(crate) ignore_missing: bool,
+    /// Fetch each index file from this mirror too, and fail if the two disagree
+    #[clap(long, value_name = "MIRROR_URL", global = true)]
+    pub(crate) cross_check: Option<String>,
+    /// Annotate results with lifecycle data from an extensions JSON file at this URL
+    #[clap(long, value_name = "URL")]
+    pub(crate) extensions: Option<String>,
+    /// Flag results affected by RustSec advisories, including vulnerable and unmaintained crates
+    #[clap(long)]
+    pub(crate) check_advisories: bool,
+    /// Find the size of each release's `.crate` file and show it before each result
+    #[clap(long)]
+    pub(crate) size: bool,
+    /// Fetch the publish date of each release from crates.io and show it before each result
+    #[clap(long)]
+    pub(crate) dates: bool,
+    /// Maximum number of concurrent network requests.
+    /// Defaults to the number of CPUs, up to 8
+    #[clap(short, long, global = true, verbatim_doc_comment)]
+    pub(crate) jobs: Option<usize>,
+    /// Skip index entries that fail to parse instead of failing the query
+    #[clap(long, global = true)]
+    pub(crate) lenient: bool,
+    /// Allow yanked releases to be selected, which are skipped by default
+    #[clap(long, global = true, conflicts_with_all = ["exclude_yanked", "only_yanked"])]
+    pub(crate) include_yanked: bool,
+    /// Skip yanked releases. This is the default when selecting releases, and hides them from
+    /// `--type=versions` listings
+    #[clap(long, global = true, conflicts_with = "only_yanked")]
+    pub(crate) exclude_yanked: bool,
+    /// Only select and list yanked releases
+    #[clap(long, global = true)]
+    pub(crate) only_yanked: bool,
+    /// Allow pre-release versions to be selected, by bare names and version requirements
+    #[clap(long, global = true)]
+    pub(crate) include_prerelease: bool,
+    /// Only select releases supporting this rust version, e.g. `1.70`
+    #[clap(long, value_name = "VERSION", global = true, value_parser = parse_rust_version)]
+    pub(crate) msrv: Option<Version>,
+    /// Print a JSON description of the features this binary was built with, then exit
+    #[clap(long, exclusive = true)]
+    pub(crate) capabilities: bool,
+}
+
+impl Options {
+    /// The number of concurrent jobs to use for network activity
+    pub(crate) fn jobs(&self) -> usize {
+        self.jobs.unwrap_or_else(crate::jobs::default_jobs)
+    }
+
+    /// The policy used to select releases, built from the selection flags
+    pub(crate) fn policy(&self) -> SelectionPolicy {
+        SelectionPolicy::default()
+            .yank_policy(self.yank_policy().unwrap_or_default())
+            .include_prereleases(self.include_prerelease)
+            .rust_version(self.msrv.clone())
+    }
+
+    /// How yanked releases are treated, if one of the yank flags was given
+    pub(crate) fn yank_policy(&self) -> Option<YankPolicy> {
+        if self.include_yanked {
+            Some(YankPolicy::Include)
+        } else if self.exclude_yanked {
+            Some(YankPolicy::Exclude)
+        } else if self.only_yanked {
+            Some(YankPolicy::Only)
+        } else {
+            None
+        }
+    }
+
+    /// Whether publish dates are needed, for `--dates` or a `published` field
+    pub(crate) fn wants_dates(&self) -> bool {
+        self.dates || self.shows_field(Field::Published)
+    }
+
+    /// Whether licenses are needed, for `--type=license` or a `license` field
+    pub(crate) fn wants_licenses(&self) -> bool {
+        self.kind == Some(Type::License) || self.shows_field(Field::License)
+    }
+
+    /// Whether `.crate` sizes are needed, for `--size` or a `size` field
+    pub(crate) fn wants_sizes(&self) -> bool {
+        self.size || self.shows_field(Field::Size)
+    }
+
+    /// The columns shown by `--format=csv`, `tsv`, and `table`
+    pub(crate) fn columns(&self) -> &[Field] {
+        match self.format {
+            _ if !self.columns.is_empty() => &self.columns,
+            Format::Table => &TABLE_COLUMNS,
+            _ => &DEFAULT_COLUMNS,
+        }
+    }
+
+    /// Whether a field is shown by `--format-str` or as a column
+    fn shows_field(&self, field: Field) -> bool {
+        let in_template = self
+            .format_str
+            .as_ref()
+            .is_some_and(|template| template.uses(field));
+        let in_columns = self.format.is_table() && self.columns().contains(&field);
+
+        in_template || in_columns
+    }
+
+    /// Whether a feature selection was given, so dependency output should only include
+    /// dependencies enabled by it
+    pub(crate) fn feature_aware(&self) -> bool {
+        !self.features.is_empty() || self.no_default_features
+    }
+
+    /// Whether dependencies of a given kind were selected with `--dep-kind` or `--no-dev-deps`
+    pub(crate) fn includes_dep_kind(&self, kind: &DependencyKind) -> bool {
+        if self.no_dev_deps {
+            return *kind != DependencyKind::Dev;
+        }
+
+        self.dep_kind.is_empty()
+            || self
+                .dep_kind
+                .iter()
+                .any(|selected| DependencyKind::from(selected.clone()) == *kind)
+    }
+
+    /// Whether dependencies of a given kind are followed when recursing, where `root` is
+    /// whether the dependent is a queried package
+    pub(crate) fn follows_dep_kind(&self, kind: &DependencyKind, root: bool) -> bool {
+        self.includes_dep_kind(kind) && (root || *kind != DependencyKind::Dev)
+    }
+
+    /// Whether a dependency is used on the target selected with `--target`
+    pub(crate) fn includes_dep_target(&self, dep: &Dependency) -> bool {
+        self.target
+            .as_deref()
+            .map_or(true, |target| dep.applies_to(target))
+    }
+}
+
+/// Parse a rust version, allowing the minor and patch versions to be left out like `rust-version`
+fn parse_rust_version(version: &str) -> Result<Version, semver::Error> {
+    let parts = version.split('.').count();
+    let padded = match parts {
+        1 => format!("{version}.0.0"),
+        2 => format!("{version}.0"),
+        _ => version.to_owned(),
+    };
+
+    padded.parse()
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Resolve the latest release of each listed package and write a pin file
+    Pin(PinOptions),
+    /// Compare two releases of a package, listing dependency, feature, and MSRV changes
+    Diff(DiffOptions),
+    /// Check whether crate names are free to publish
+    Available(AvailableOptions),
+    /// Check whether a crate, or a release matching a requirement, exists, through the exit code
+    Exists(ExistsOptions),
+    /// Print the path of crates' files in the index, like `se/rd/serde`, without any requests
+    IndexPath(IndexPathOptions),
+    /// Print a crate's index file as it is, one JSON release per line
+    Raw(RawOptions),
+    /// Download the `.crate` files of a release, or of it's whole dependency tree, checking their
+    /// checksums
+    Download(DownloadOptions),
+    /// Check local `.crate` files against the checksums published in the index
+    Verify(VerifyOptions),
+    /// List the users and teams that can publish a crate on crates.io
+    Owners(OwnersOptions),
+    /// Show the total, recent, and per-version download counts of a crate
+    Downloads(DownloadsOptions),
+    /// List or count the crates on crates.io that depend on a crate
+    Rdeps(RdepsOptions),
+    /// Search the registry for crates, using the registry's web API
+    Search(SearchOptions),
+    /// Show a card of a crate's details from the index and the registry's web API
+    Info(InfoOptions),
+    /// Check the packages in a `Cargo.lock` for yanked releases and checksums that don't match the index
+    AuditLock(AuditLockOptions),
+    /// Compare a `Cargo.lock` with a fresh resolution, listing what `cargo update` would change
+    LockDiff(LockDiffOptions),
+    /// Find the minimum supported rust version of a package's whole dependency tree
+    Msrv(MsrvOptions),
+    /// Write a software bill of materials for a package's dependency tree or a `Cargo.lock`
+    Sbom(SbomOptions),
+    /// List the dependencies of a project with newer releases than the ones in use
+    Outdated(OutdatedOptions),
+    /// Write the resolved dependency graph of a package as DOT, JSON, or GraphML
+    Graph(GraphOptions),
+    /// Print the resolved dependency tree of a package, like `cargo tree`
+    Tree(TreeOptions),
+    /// Sum the `.crate` sizes of a package and it's dependency tree
+    TreeSize(TreeSizeOptions),
+    /// Show the dependency chains that pull a crate into a package's dependency tree
+    Why(WhyOptions),
+}
+
+#[derive(Debug, Parser)]
+pub struct PinOptions {
+    /// File listing one package query per line, e.g. `serde` or `tokio@^1`
+    #[clap(long, value_name = "PATH", required_unless_present = "verify")]
+    pub(crate) from_file: Option<PathBuf>,
+    /// Where to write the pin file. Printed to stdout if omitted
+    #[clap(short, long, value_name = "PATH")]
+    pub(crate) out: Option<PathBuf>,
+    /// Check an existing pin file for releases that have drifted from the pinned version
+    #[clap(long, value_name = "PATH", conflicts_with_all = ["from_file", "out"])]
+    pub(crate) verify: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct DiffOptions {
+    /// Package to compare releases of, or the release to compare from, like `serde@1.0.100`
+    pub(crate) package: String,
+    /// Version to compare from, or to if the package names a release
+    pub(crate) from: Option<String>,
+    /// Version to compare to. Defaults to the latest release
+    pub(crate) to: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct AvailableOptions {
+    /// Names to check
+    #[clap(required = true)]
+    pub(crate) names: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct ExistsOptions {
+    /// Crate to check for, with an optional version requirement like `serde@=1.0.219`
+    pub(crate) package: String,
+    /// Print `yes` or `no` as well as setting the exit code
+    #[clap(long)]
+    pub(crate) print: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct IndexPathOptions {
+    /// Names of the crates
+    #[clap(required = true)]
+    pub(crate) names: Vec<String>,
+    /// Print the full URL of each file in the index given with `--index-url`, or crates.io
+    #[clap(long)]
+    pub(crate) url: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct RawOptions {
+    /// Crate to print, with an optional version requirement like `serde@^1` to keep only the
+    /// lines of matching releases
+    pub(crate) package: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct DownloadOptions {
+    /// Package to download, with an optional version requirement like `serde@^1`
+    pub(crate) package: String,
+    /// Directory to write the `.crate` files to, created if it doesn't exist
+    #[clap(short, long, value_name = "DIR", default_value = ".")]
+    pub(crate) output: PathBuf,
+    /// Also download every normal and build dependency in the package's resolved tree
+    #[clap(short, long)]
+    pub(crate) recursive: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct VerifyOptions {
+    /// `.crate` files to check, named like `serde-1.0.219.crate`
+    #[clap(required = true)]
+    pub(crate) files: Vec<PathBuf>,
+    /// Name of the crate, for a single file that isn't named after it's release
+    #[clap(long, requires = "version")]
+    pub(crate) name: Option<String>,
+    /// Version of the crate, for a single file that isn't named after it's release
+    #[clap(long, requires = "name")]
+    pub(crate) version: Option<Version>,
+}
+
+#[derive(Debug, Parser)]
+pub struct OwnersOptions {
+    /// Crate to list the owners of
+    pub(crate) package: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct DownloadsOptions {
+    /// Crate to count the downloads of. A version requirement, like `serde@^1`, limits the
+    /// versions listed
+    pub(crate) package: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct RdepsOptions {
+    /// Crate to find the dependents of
+    pub(crate) package: String,
+    /// Only count the dependents, in a single request, instead of listing every one of them
+    /// 100 per request
+    #[clap(short, long)]
+    pub(crate) count_only: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct SearchOptions {
+    /// Terms to search for
+    #[clap(required = true)]
+    pub(crate) terms: Vec<String>,
+    /// Maximum number of results to show
+    #[clap(short, long, default_value = "10")]
+    pub(crate) limit: usize,
+}
+
+#[derive(Debug, Parser)]
+pub struct InfoOptions {
+    /// Package to show info for
+    pub(crate) package: String,
+    /// Check whether docs.rs built documentation for the latest release
+    #[clap(long)]
+    pub(crate) docs: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct SbomOptions {
+    /// Package whose dependency tree is described
+    #[clap(required_unless_present = "lockfile")]
+    pub(crate) package: Option<String>,
+    /// Describe the registry packages of a lockfile instead of a resolved package
+    #[clap(long, value_name = "PATH", conflicts_with = "package")]
+    pub(crate) lockfile: Option<PathBuf>,
+    /// Bill of materials standard to write
+    #[clap(long, value_name = "FORMAT", default_value = "cyclonedx")]
+    pub(crate) emit: SbomFormat,
+    /// Don't fetch licenses and suppliers from the registry's API
+    #[clap(long)]
+    pub(crate) no_api: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct GraphOptions {
+    /// Package whose dependency graph is written
+    pub(crate) package: String,
+    /// Format to write the graph in
+    #[clap(long, value_name = "FORMAT", default_value = "dot")]
+    pub(crate) emit: GraphFormat,
+}
+
+#[derive(Debug, Parser)]
+pub struct TreeOptions {
+    /// Package whose dependency tree is printed
+    pub(crate) package: String,
+    /// Maximum depth of the tree below the package
+    #[clap(long, value_name = "N")]
+    pub(crate) depth: Option<usize>,
+    /// Characters to draw the tree with
+    #[clap(long, default_value = "utf8")]
+    pub(crate) charset: Charset,
+}
+
+#[derive(Debug, Parser)]
+pub struct TreeSizeOptions {
+    /// Package to measure the dependency tree of
+    pub(crate) package: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct AuditLockOptions {
+    /// The lockfile to audit
+    #[clap(long, value_name = "PATH", default_value = "Cargo.lock")]
+    pub(crate) lockfile: PathBuf,
+    /// Also report locked releases affected by RustSec advisories
+    #[clap(long)]
+    pub(crate) check_advisories: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct LockDiffOptions {
+    /// The lockfile to compare
+    #[clap(long, value_name = "PATH", default_value = "Cargo.lock")]
+    pub(crate) lockfile: PathBuf,
+}
+
+#[derive(Debug, Parser)]
+pub struct OutdatedOptions {
+    /// The `Cargo.toml` of the project. For a workspace root, every member is checked
+    #[clap(long, value_name = "PATH", default_value = "Cargo.toml")]
+    pub(crate) manifest_path: PathBuf,
+    /// The lockfile recording the versions in use. Defaults to the project's `Cargo.lock`, if it
+    /// has one
+    #[clap(long, value_name = "PATH")]
+    pub(crate) lockfile: Option<PathBuf>,
+    /// Print each outdated dependency with a requirement on it's newest release, ready to paste
+    /// into a `Cargo.toml`
+    #[clap(long)]
+    pub(crate) suggest: bool,
+    /// Print a unified diff updating the requirements of outdated dependencies in the manifest
+    #[clap(long, conflicts_with = "suggest")]
+    pub(crate) diff: bool,
+    /// Exit with `1` if any dependency is outdated, like `git diff --exit-code`
+    #[clap(long)]
+    pub(crate) exit_code: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct MsrvOptions {
+    /// Package whose dependency tree is resolved
+    pub(crate) package: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct WhyOptions {
+    /// Package whose dependency tree is resolved
+    pub(crate) package: String,
+    /// Crate to explain the presence of
+    pub(crate) dependency: String,
 }
 
 #[derive(ValueEnum, Debug, Clone, PartialEq)]
@@ -43,6 +519,90 @@ pub enum Type {
     Features,
     /// Print output in JSON format
     Json,
+    /// Print output in YAML format, with the same structure as the JSON output
+    Yaml,
+    /// Show crates.io download counts for each package
+    Downloads,
+    /// Show the license of each package from crates.io
+    License,
+    /// List every release of each package, newest first, marking yanked releases
+    ///
+    /// Only releases matching the query's version requirement are listed if it has one. Yanked
+    /// releases are listed unless `--exclude-yanked` is given
+    Versions,
+    /// List the releases that added or removed features, oldest first, e.g. `wasm_js added in
+    /// 0.3.0`
+    ///
+    /// Releases are limited like `--type=versions`, so a version requirement narrows the range.
+    /// Pre-releases are skipped unless `--include-prerelease` is given
+    FeatureHistory,
+    /// Print `name version sha256` for each release, with the checksum of it's `.crate` file
+    Cksum,
+    /// Print the URL each release's `.crate` file is downloaded from, using the registry's
+    /// `config.json`
+    DlUrl,
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+pub enum DepKind {
+    /// Dependencies used to build and run a package
+    Normal,
+    /// Dependencies only used for tests, examples, and benchmarks
+    Dev,
+    /// Dependencies only used by build scripts
+    Build,
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+pub enum GraphFormat {
+    /// A Graphviz DOT graph, like `--format=dot`
+    Dot,
+    /// A JSON object with `nodes` and `edges` arrays, and the ids of the `roots`
+    Json,
+    /// A GraphML document, for tools like Gephi, yEd, and NetworkX
+    Graphml,
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+pub enum SbomFormat {
+    /// A CycloneDX 1.5 JSON document
+    Cyclonedx,
+    /// An SPDX 2.3 JSON document
+    Spdx,
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+pub enum Charset {
+    /// Box-drawing characters
+    Utf8,
+    /// Plain ASCII characters
+    Ascii,
+}
+
+impl From<Charset> for graph::Charset {
+    fn from(charset: Charset) -> Self {
+        match charset {
+            Charset::Utf8 => graph::Charset::Utf8,
+            Charset::Ascii => graph::Charset::Ascii,
+        }
+    }
+}
+
+impl Format {
+    /// Whether the format prints rows of `--columns`
+    pub(crate) fn is_table(&self) -> bool {
+        matches!(self, Format::Csv | Format::Tsv | Format::Table)
+    }
+}
+
+impl From<DepKind> for DependencyKind {
+    fn from(kind: DepKind) -> Self {
+        match kind {
+            DepKind::Normal => DependencyKind::Normal,
+            DepKind::Dev => DependencyKind::Dev,
+            DepKind::Build => DependencyKind::Build,
+        }
+    }
 }
 
 #[derive(ValueEnum, Debug, Clone, PartialEq)]
@@ -57,4 +617,37 @@ pub enum Format {
     ///
     /// Equivalent to passing `--type=features --format=no-prefix --delim=,`
     CargoAddAll,
+    /// A Graphviz DOT graph of the resolved packages and their dependencies
+    ///
+    /// Most useful with `--recursive` or `--resolve`, e.g. piped into `dot -Tsvg`
+    Dot,
+    /// A Mermaid flowchart of the resolved packages and their dependencies
+    ///
+    /// Can be pasted into a ```mermaid code block in GitHub issues, pull requests, and docs
+    Mermaid,
+    /// The resolved packages in an order they can be built in, dependencies first
+    ///
+    /// Dev dependencies are ignored. Fails if the packages depend on each other in a cycle
+    BuildOrder,
+    /// A `Cargo.lock` file listing the resolved packages, their checksums, and dependencies
+    Lockfile,
+    /// One JSON object per line for each release, printed as soon as it's resolved
+    ///
+    /// With `--recursive`, a release may be unified with a later requirement and replaced by
+    /// another semver-compatible release, which is printed too. With `--resolve`, releases are
+    /// printed once the resolution finishes
+    Ndjson,
+    /// Comma-separated values with a header row, with the fields chosen by `--columns`
+    Csv,
+    /// Tab-separated values with a header row, with the fields chosen by `--columns`
+    Tsv,
+    /// A table with padded, aligned columns for reading in a terminal, with the fields chosen
+    /// by `--columns`
+    Table,
+    /// `Cargo.toml` dependency lines for the queried packages, ready to paste, e.g.
+    /// `serde = { version = "1.0.219", features = ["derive"] }`
+    ///
+    /// Features come from the query string and `--features`, and `--no-default-features` is
+    /// respected
+    Toml,
 }
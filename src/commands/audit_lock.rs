@@ -0,0 +1,164 @@
+use crate::{
+    cache::Packages,
+    cli::{AuditLockOptions, Options},
+    jobs, project,
+};
+use anyhow::{anyhow, Context, Result};
+use cargo_lookup::{
+    advisories::{self, Advisory},
+    lockfile::{LockIssue, LockedPackage},
+    SelectionPolicy, CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use serde::Serialize;
+use std::process;
+
+/// A locked package with a problem
+#[derive(Debug, Serialize)]
+struct Finding {
+    name: String,
+    version: Version,
+    #[serde(flatten)]
+    problem: Problem,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Problem {
+    Lock(LockIssue),
+    /// Always `"advisory"`, tagged like a [`LockIssue`]
+    Advisory {
+        issue: &'static str,
+        #[serde(flatten)]
+        advisory: Advisory,
+    },
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Lock(issue) => write!(f, "{issue}"),
+            Problem::Advisory { advisory, .. } => write!(f, "advisory {advisory}"),
+        }
+    }
+}
+
+/// Check every registry package in a lockfile against the index it's locked from, and
+/// optionally the RustSec advisory database, for CI gating
+///
+/// Exits with `1` if any locked release is yanked, missing, has a different checksum, or is
+/// affected by an advisory, and with `2` if the audit couldn't be done, including when
+/// advisories were asked for but couldn't be fetched
+pub fn run(audit: &AuditLockOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let findings = match findings(audit, options, policy) {
+        Ok(findings) => findings,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            process::exit(2);
+        }
+    };
+
+    if !crate::print_structured(&findings, options)? {
+        for Finding {
+            name,
+            version,
+            problem,
+        } in &findings
+        {
+            println!("{name} {version}: {problem}");
+        }
+    }
+
+    if !findings.is_empty() {
+        eprintln!(
+            "error: found {} problem(s) in `{}`",
+            findings.len(),
+            audit.lockfile.display()
+        );
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn findings(
+    audit: &AuditLockOptions,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<Vec<Finding>> {
+    let lockfile = project::read_lockfile(&audit.lockfile)?;
+
+    // Each package is looked up in the registry it's locked from, so packages from other
+    // registries aren't mistaken for crates.io ones
+    let mut locked = Vec::new();
+    let mut unsupported = Vec::new();
+    for package in lockfile
+        .packages
+        .into_iter()
+        .filter(LockedPackage::is_registry)
+    {
+        match package.index_url() {
+            Some(index_url) => locked.push((index_url, package)),
+            None => unsupported.push(format!("`{} {}`", package.name, package.version)),
+        }
+    }
+    if !unsupported.is_empty() {
+        eprintln!(
+            "warning: skipping packages from git indexes, only sparse indexes are supported: {}",
+            unsupported.join(", ")
+        );
+    }
+
+    let packages = Packages::default();
+    let issues = jobs::parallel_map(&locked, options.jobs(), |(index_url, locked)| {
+        let query = crate::query(&locked.name, options, policy)?.with_index(index_url.as_str());
+
+        match packages.fetch(&query) {
+            Ok(package) => Ok(locked.audit(&package)),
+            Err(error) if error.is_not_found() => Ok(vec![LockIssue::Missing]),
+            Err(error) => Err(anyhow!(error)),
+        }
+    });
+
+    // The advisory database only covers crates.io
+    let advisories = if audit.check_advisories {
+        let client = advisories::Client::default();
+        jobs::parallel_map(&locked, options.jobs(), |(index_url, locked)| {
+            if index_url != CRATES_IO_INDEX_URL {
+                return Ok(Vec::new());
+            }
+
+            client
+                .advisories(&locked.name, &locked.version)
+                .with_context(|| {
+                    format!(
+                        "failed to fetch advisories for `{} {}`",
+                        locked.name, locked.version
+                    )
+                })
+        })
+    } else {
+        locked.iter().map(|_| Ok(Vec::new())).collect()
+    };
+
+    let mut findings = Vec::new();
+    for (((_, locked), issues), advisories) in locked.into_iter().zip(issues).zip(advisories) {
+        let problems = issues?
+            .into_iter()
+            .map(Problem::Lock)
+            .chain(advisories?.into_iter().map(|advisory| Problem::Advisory {
+                issue: "advisory",
+                advisory,
+            }));
+
+        for problem in problems {
+            findings.push(Finding {
+                name: locked.name.clone(),
+                version: locked.version.clone(),
+                problem,
+            });
+        }
+    }
+
+    Ok(findings)
+}
@@ -0,0 +1,40 @@
+use crate::cli::{AvailableOptions, Options};
+use anyhow::Result;
+use cargo_lookup::{availability::Availability, SelectionPolicy};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    #[serde(flatten)]
+    availability: Availability,
+}
+
+/// Report whether each name is available, taken, invalid, or reserved on the registry
+///
+/// Names are checked one at a time, since each check may be a request to the registry's API
+pub fn run(
+    available: &AvailableOptions,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<()> {
+    let entries = available
+        .names
+        .iter()
+        .map(|name| {
+            let availability = crate::query(name, options, policy)?.availability()?;
+            Ok(Entry {
+                name: name.clone(),
+                availability,
+            })
+        })
+        .collect::<Result<Vec<Entry>>>()?;
+
+    if !crate::print_structured(&entries, options)? {
+        for Entry { name, availability } in entries {
+            println!("{name}: {availability}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::cli::{Format, Options, Type};
+use anyhow::Result;
+use clap::{CommandFactory, ValueEnum};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Cargo features this binary may be compiled with, and whether each one is enabled
+const FEATURES: &[(&str, bool)] = &[
+    ("cli", cfg!(feature = "cli")),
+    ("api", cfg!(feature = "api")),
+    ("cargo_metadata", cfg!(feature = "cargo_metadata")),
+    ("crates-index", cfg!(feature = "crates-index")),
+];
+
+/// A machine-readable description of what this binary supports
+#[derive(Debug, Serialize)]
+struct Capabilities {
+    version: &'static str,
+    features: BTreeMap<&'static str, bool>,
+    commands: Vec<String>,
+    types: Vec<String>,
+    formats: Vec<String>,
+}
+
+/// Print the capabilities of this binary as JSON
+pub fn run() -> Result<()> {
+    let capabilities = Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features: FEATURES.iter().copied().collect(),
+        commands: Options::command()
+            .get_subcommands()
+            .map(|command| command.get_name().to_owned())
+            .collect(),
+        types: value_names::<Type>(),
+        formats: value_names::<Format>(),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&capabilities)?);
+
+    Ok(())
+}
+
+fn value_names<T: ValueEnum>() -> Vec<String> {
+    T::value_variants()
+        .iter()
+        .filter_map(ValueEnum::to_possible_value)
+        .map(|value| value.get_name().to_owned())
+        .collect()
+}
@@ -0,0 +1,77 @@
+use crate::cli::{DiffOptions, Options};
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_lookup::{normalize_name, SelectionPolicy};
+use semver::{Comparator, Op, Version, VersionReq};
+
+/// Compare two releases of a package, defaulting to the latest release if `to` is omitted
+///
+/// Releases are given either as a package and versions, like `serde 1.0.100 1.0.219`, or as
+/// `serde@1.0.100 serde@1.0.219`
+pub fn run(diff: &DiffOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let (name, from, to) = match diff.package.split_once('@') {
+        Some((name, from)) => {
+            if diff.to.is_some() {
+                bail!("expected at most two releases to compare, like `{name}@{from} {name}@<version>`");
+            }
+            (name, from, diff.from.as_deref())
+        }
+        None => {
+            let name = diff.package.as_str();
+            let from = diff.from.as_deref().ok_or_else(|| {
+                anyhow!("expected a version to compare from, like `{name} <version>` or `{name}@<version>`")
+            })?;
+            (name, from, diff.to.as_deref())
+        }
+    };
+    let from = version(name, from)?;
+    let to = to.map(|to| version(name, to)).transpose()?;
+
+    let package = crate::query(name, options, policy)?.package()?;
+    crate::report_warnings(&package);
+
+    let from = package
+        .version(&exact(&from))
+        .ok_or_else(|| anyhow!("failed to find release {from} of `{name}`"))?;
+    let to = match &to {
+        Some(to) => package.version(&exact(to)),
+        None => package.select(None, policy),
+    }
+    .ok_or_else(|| anyhow!("failed to find a release of `{name}` to compare to"))?;
+
+    let diff = from.diff(to);
+
+    if !crate::print_structured(&diff, options)? {
+        println!("{diff}");
+    }
+
+    Ok(())
+}
+
+/// Parse a version of a package, written either as `1.0.219` or `serde@1.0.219`
+fn version(name: &str, version: &str) -> Result<Version> {
+    let version = match version.split_once('@') {
+        Some((other, _)) if normalize_name(other) != normalize_name(name) => {
+            bail!("can't compare releases of different packages, `{name}` and `{other}`")
+        }
+        Some((_, version)) => version,
+        None => version,
+    };
+
+    version
+        .trim_start_matches('=')
+        .parse()
+        .with_context(|| format!("invalid version `{version}` of `{name}`"))
+}
+
+/// A requirement matching only the given version
+fn exact(version: &Version) -> VersionReq {
+    VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Exact,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+            pre: version.pre.clone(),
+        }],
+    }
+}
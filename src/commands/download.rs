@@ -0,0 +1,172 @@
+use crate::{
+    cli::{DownloadOptions, Options},
+    jobs,
+};
+use anyhow::{anyhow, Context, Result};
+use cargo_lookup::{
+    download::RegistryConfig, DependencyKind, Release, SelectionPolicy, CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use serde::Serialize;
+use std::{fs, path::Path};
+
+#[derive(Debug, Serialize)]
+struct Downloaded {
+    name: String,
+    version: Version,
+    path: String,
+    status: Status,
+    /// Why the download failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Downloaded,
+    /// The file was already there and matches it's checksum, so it wasn't downloaded again
+    Present,
+    Failed,
+}
+
+/// Download the `.crate` files of a release, or of every release in it's resolved tree, into a
+/// directory, like seeding an offline mirror
+///
+/// Files are named `<name>-<version>.crate` like cargo's cache, and each one is checked against
+/// it's checksum in the index before it's written. Files already in the directory with the right
+/// checksum are kept, so an interrupted download can be picked up again
+pub fn run(download: &DownloadOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let releases = releases(download, options, policy)?;
+
+    let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+    let config = RegistryConfig::fetch(index_url)?;
+    fs::create_dir_all(&download.output)
+        .with_context(|| format!("failed to create `{}`", download.output.display()))?;
+
+    let results = jobs::parallel_map(&releases, options.jobs(), |release| {
+        fetch(&config, release, &download.output)
+    });
+
+    let downloaded = releases
+        .into_iter()
+        .zip(results)
+        .map(|(release, result)| {
+            let path = download.output.join(file_name(&release));
+            let (status, error) = match result {
+                Ok(status) => (status, None),
+                Err(error) => (Status::Failed, Some(format!("{error:#}"))),
+            };
+
+            Downloaded {
+                name: release.name,
+                version: release.vers,
+                path: path.display().to_string(),
+                status,
+                error,
+            }
+        })
+        .collect::<Vec<Downloaded>>();
+
+    if !crate::print_structured(&downloaded, options)? {
+        print(&downloaded, &download.output);
+    }
+
+    let failed = downloaded
+        .iter()
+        .filter(|downloaded| downloaded.status == Status::Failed)
+        .count();
+    if failed > 0 {
+        return Err(anyhow!(
+            "failed to download {failed} of {} crates",
+            downloaded.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Select the release to download, along with it's normal and build dependencies if recursive
+fn releases(
+    download: &DownloadOptions,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<Vec<Release>> {
+    if download.recursive {
+        let packages = [download.package.clone()];
+        let dep_kinds = [DependencyKind::Normal, DependencyKind::Build];
+        let (_, graph) = crate::resolve_with_dep_kinds(&packages, dep_kinds, policy, options)?;
+
+        return Ok(graph.into_releases());
+    }
+
+    let query = crate::query(&download.package, options, policy)?;
+    let package = query.package()?;
+    crate::report_warnings(&package);
+
+    match package.select(query.version_req(), policy) {
+        Some(release) => Ok(vec![release.clone()]),
+        None => Err(crate::no_match(
+            &download.package,
+            &query,
+            &package,
+            policy,
+            options,
+        )),
+    }
+}
+
+/// Download a release into `dir`, unless a file with the right checksum is already there
+fn fetch(config: &RegistryConfig, release: &Release, dir: &Path) -> Result<Status> {
+    let path = dir.join(file_name(release));
+
+    if let Ok(file) = fs::File::open(&path) {
+        if release.verify_checksum_reader(file).is_ok() {
+            return Ok(Status::Present);
+        }
+    }
+
+    let bytes = config.download(release)?;
+    fs::write(&path, bytes).with_context(|| format!("failed to write `{}`", path.display()))?;
+
+    Ok(Status::Downloaded)
+}
+
+/// The name cargo gives a release's `.crate` file, like `serde-1.0.219.crate`
+fn file_name(release: &Release) -> String {
+    format!("{}-{}.crate", release.name, release.vers)
+}
+
+fn print(downloaded: &[Downloaded], output: &Path) {
+    for Downloaded {
+        name,
+        version,
+        status,
+        error,
+        ..
+    } in downloaded
+    {
+        match (status, error) {
+            (Status::Downloaded, _) => println!("downloaded {name} {version}"),
+            (Status::Present, _) => println!("present {name} {version}"),
+            (Status::Failed, error) => eprintln!(
+                "error: failed to download {name} {version}: {}",
+                error.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
+
+    let count = |status| {
+        downloaded
+            .iter()
+            .filter(|downloaded| downloaded.status == status)
+            .count()
+    };
+    println!(
+        "{} downloaded, {} already present, {} failed in `{}`",
+        count(Status::Downloaded),
+        count(Status::Present),
+        count(Status::Failed),
+        output.display()
+    );
+}
@@ -0,0 +1,99 @@
+use crate::{
+    cli::{DownloadsOptions, Options},
+    table::Table,
+};
+use anyhow::{Context, Result};
+use cargo_lookup::Query;
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Downloads {
+    name: String,
+    total: u64,
+    recent: Option<u64>,
+    /// Newest first
+    versions: Vec<VersionDownloads>,
+}
+
+#[derive(Debug, Serialize)]
+struct VersionDownloads {
+    version: Version,
+    published: Option<String>,
+    downloads: u64,
+}
+
+/// Show the download counts of a crate and each of it's versions, newest first
+///
+/// With `--format=csv`, `tsv`, or `table`, each version is a row with it's publish date, ready
+/// to chart
+pub fn run(downloads: &DownloadsOptions, options: &Options) -> Result<()> {
+    let query = downloads.package.parse::<Query>()?;
+    let name = query.name();
+
+    let client = crate::api_client(options)?;
+    let details = client
+        .crate_details(name)
+        .with_context(|| format!("failed to fetch downloads of `{name}`"))?;
+
+    let versions = details
+        .downloads
+        .versions
+        .iter()
+        .rev()
+        .filter(|(version, _)| query.version_req().map_or(true, |req| req.matches(version)))
+        .map(|(version, downloads)| VersionDownloads {
+            version: version.clone(),
+            published: details
+                .versions
+                .get(version)
+                .map(|info| info.published.clone()),
+            downloads: *downloads,
+        })
+        .collect();
+    let downloads = Downloads {
+        name: details.info.name,
+        total: details.downloads.total,
+        recent: details.downloads.recent,
+        versions,
+    };
+
+    if options.format.is_table() {
+        let mut table = Table::with_header(&["version", "published", "downloads"]);
+        for version in &downloads.versions {
+            table.push(vec![
+                version.version.to_string(),
+                version.published.clone().unwrap_or_default(),
+                version.downloads.to_string(),
+            ]);
+        }
+
+        crate::print_table(&table, options);
+        return Ok(());
+    }
+
+    if !crate::print_structured(&downloads, options)? {
+        match downloads.recent {
+            Some(recent) => println!(
+                "{}: {} total, {recent} recent",
+                downloads.name, downloads.total
+            ),
+            None => println!("{}: {} total", downloads.name, downloads.total),
+        }
+
+        let width = downloads
+            .versions
+            .iter()
+            .map(|version| version.version.to_string().len())
+            .max()
+            .unwrap_or_default();
+        for VersionDownloads {
+            version, downloads, ..
+        } in &downloads.versions
+        {
+            println!("  {:<width$}  {downloads}", version.to_string());
+        }
+    }
+
+    Ok(())
+}
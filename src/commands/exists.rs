@@ -0,0 +1,44 @@
+use crate::cli::{ExistsOptions, Options};
+use anyhow::{anyhow, Result};
+use cargo_lookup::{SelectionPolicy, YankPolicy};
+use std::process;
+
+/// Exit with `0` if the crate exists and has a release matching the requirement, or `1` if it
+/// doesn't, like `grep`. Failures exit with `2`, so they can't be mistaken for a missing crate
+///
+/// Yanked releases still exist, so they match unless `--exclude-yanked` is given
+pub fn run(exists: &ExistsOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let found = match find(exists, options, policy) {
+        Ok(found) => found,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            process::exit(2);
+        }
+    };
+
+    if exists.print {
+        println!("{}", if found { "yes" } else { "no" });
+    }
+    if !found {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn find(exists: &ExistsOptions, options: &Options, policy: &SelectionPolicy) -> Result<bool> {
+    let query = crate::query(&exists.package, options, policy)?;
+    let package = match query.package() {
+        Ok(package) => package,
+        Err(error) if error.is_not_found() => return Ok(false),
+        Err(error) => return Err(anyhow!(error)),
+    };
+
+    let yank_policy = options.yank_policy().unwrap_or(YankPolicy::Include);
+    Ok(package.releases().iter().any(|release| {
+        yank_policy.allows(release)
+            && query
+                .version_req()
+                .map_or(true, |version_req| version_req.matches(&release.vers))
+    }))
+}
@@ -0,0 +1,77 @@
+use crate::cli::{Format, GraphFormat, GraphOptions, Options};
+use anyhow::Result;
+use cargo_lookup::{graph::NodeId, SelectionPolicy};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    /// The ids of the queried package's nodes
+    roots: Vec<NodeId>,
+}
+
+#[derive(Debug, Serialize)]
+struct Node {
+    id: NodeId,
+    name: String,
+    version: Version,
+    yanked: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Edge {
+    from: NodeId,
+    to: NodeId,
+    kind: String,
+    req: VersionReq,
+    optional: bool,
+}
+
+/// Write the dependency graph of a package, resolved like `--resolve`, in the format chosen
+/// with `--emit`
+///
+/// The JSON format only keeps what's needed to analyse the graph, the full releases are
+/// available with `--resolve --type=json`
+pub fn run(graph: &GraphOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let packages = [graph.package.clone()];
+    let (_, resolved) = crate::resolve_consistent(&packages, policy, options)?;
+
+    match graph.emit {
+        GraphFormat::Dot => print!("{}", resolved.to_dot()),
+        GraphFormat::Graphml => print!("{}", resolved.to_graphml()),
+        GraphFormat::Json => {
+            let graph = Graph {
+                nodes: resolved
+                    .nodes()
+                    .map(|(id, release)| Node {
+                        id,
+                        name: release.name.clone(),
+                        version: release.vers.clone(),
+                        yanked: release.yanked,
+                    })
+                    .collect(),
+                edges: resolved
+                    .edges()
+                    .iter()
+                    .map(|edge| Edge {
+                        from: edge.from,
+                        to: edge.to,
+                        kind: edge.dependency.kind.as_str().to_owned(),
+                        req: edge.dependency.req.clone(),
+                        optional: edge.dependency.optional,
+                    })
+                    .collect(),
+                roots: resolved.roots().to_vec(),
+            };
+
+            match options.format {
+                Format::Pretty => println!("{}", serde_json::to_string_pretty(&graph)?),
+                _ => println!("{}", serde_json::to_string(&graph)?),
+            }
+        }
+    }
+
+    Ok(())
+}
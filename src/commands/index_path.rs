@@ -0,0 +1,45 @@
+use crate::cli::{IndexPathOptions, Options};
+use anyhow::Result;
+use cargo_lookup::{get_index_path, CRATES_IO_INDEX_URL};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    path: String,
+}
+
+/// Print where each crate's file is in the index, computed from the name alone
+pub fn run(index_path: &IndexPathOptions, options: &Options) -> Result<()> {
+    // Accept index URLs as they are written in cargo's config, e.g. `sparse+https://...`
+    let index_url = options
+        .index_url
+        .as_deref()
+        .unwrap_or(CRATES_IO_INDEX_URL)
+        .trim_start_matches("sparse+")
+        .trim_end_matches('/');
+
+    let entries = index_path
+        .names
+        .iter()
+        .map(|name| {
+            let path = get_index_path(name)?;
+            Ok(Entry {
+                name: name.clone(),
+                path: if index_path.url {
+                    format!("{index_url}/{path}")
+                } else {
+                    path
+                },
+            })
+        })
+        .collect::<Result<Vec<Entry>>>()?;
+
+    if !crate::print_structured(&entries, options)? {
+        for Entry { path, .. } in entries {
+            println!("{path}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::cli::{InfoOptions, Options};
+use anyhow::{anyhow, Context, Result};
+use cargo_lookup::{
+    api::{CrateDetails, CrateInfo, Owner},
+    docs_rs::DocsStatus,
+    Release, SelectionPolicy,
+};
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Info {
+    version: Version,
+    /// The newest pre-release, if it's newer than `version`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prerelease: Option<Version>,
+    #[serde(flatten)]
+    info: CrateInfo,
+    license: Option<String>,
+    rust_version: Option<Version>,
+    features: Vec<String>,
+    downloads: Downloads,
+    /// Missing if the registry's API doesn't list owners, which is also noted in `unavailable`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owners: Option<Vec<Owner>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs: Option<DocsStatus>,
+    /// Sources that were requested but could not be reached
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unavailable: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct Downloads {
+    total: u64,
+    recent: Option<u64>,
+    /// Downloads of `version`
+    version: Option<u64>,
+}
+
+/// Show the description, links, license, latest releases, features, downloads, and owners of a
+/// package, and optionally the documentation status of it's latest release
+///
+/// Details come from both the index and the registry's API. Owners are left out if the API
+/// can't list them, since not every registry supports it
+pub fn run(info: &InfoOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let package = crate::query(&info.package, options, policy)?.package()?;
+    crate::report_warnings(&package);
+
+    // Crates with only pre-releases show their newest pre-release, which is still subject to
+    // the rest of the policy like `--msrv`
+    let prerelease_policy = policy.clone().include_prereleases(true);
+    let newest_prerelease = package
+        .releases()
+        .iter()
+        .filter(|release| !release.vers.pre.is_empty())
+        .filter(|release| prerelease_policy.allows(release, None))
+        .max_by(|a, b| a.vers.cmp(&b.vers));
+    let release = package
+        .select(None, policy)
+        .or(newest_prerelease)
+        .ok_or_else(|| anyhow!("failed to find a matching release of `{}`", info.package))?;
+    let prerelease = newest_prerelease
+        .filter(|prerelease| prerelease.vers > release.vers)
+        .map(|prerelease| prerelease.vers.clone());
+
+    let client = crate::api_client(options)?;
+    let CrateDetails {
+        info: crate_info,
+        downloads,
+        versions,
+    } = client
+        .crate_details(package.name())
+        .with_context(|| format!("failed to fetch info for `{}`", package.name()))?;
+
+    let mut unavailable = Vec::new();
+    let owners = match client.owners(package.name()) {
+        Ok(owners) => Some(owners),
+        Err(error) => {
+            eprintln!("warning: owners unavailable, continuing without them: {error}");
+            unavailable.push("owners");
+            None
+        }
+    };
+
+    let docs = if info.docs {
+        match package.docs_status() {
+            Ok(docs) => docs,
+            Err(error) => {
+                eprintln!("warning: docs unavailable, continuing without them: {error}");
+                unavailable.push("docs");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let info = Info {
+        version: release.vers.clone(),
+        prerelease,
+        info: crate_info,
+        license: versions
+            .get(&release.vers)
+            .and_then(|version| version.license.clone()),
+        rust_version: release.minimum_rust_version(),
+        features: features(release),
+        downloads: Downloads {
+            total: downloads.total,
+            recent: downloads.recent,
+            version: downloads.version(&release.vers),
+        },
+        owners,
+        docs,
+        unavailable,
+    };
+
+    if !crate::print_structured(&info, options)? {
+        print(&info);
+    }
+
+    Ok(())
+}
+
+/// The release's features, leaving out `default` which every crate may have
+fn features(release: &Release) -> Vec<String> {
+    release
+        .all_features()
+        .into_keys()
+        .filter(|feature| feature != "default")
+        .collect()
+}
+
+fn print(
+    Info {
+        version,
+        prerelease,
+        info,
+        license,
+        rust_version,
+        features,
+        downloads,
+        owners,
+        docs,
+        ..
+    }: &Info,
+) {
+    println!("{} {version}", info.name);
+
+    if let Some(description) = &info.description {
+        println!("  {}", description.trim());
+    }
+
+    if let Some(prerelease) = prerelease {
+        println!("  pre-release: {prerelease}");
+    }
+    println!("  license: {}", license.as_deref().unwrap_or("unknown"));
+    if let Some(rust_version) = rust_version {
+        println!("  rust-version: {rust_version}");
+    }
+
+    let links = [
+        ("homepage", &info.homepage),
+        ("repository", &info.repository),
+        ("documentation", &info.documentation),
+    ];
+    for (label, link) in links {
+        if let Some(link) = link {
+            println!("  {label}: {link}");
+        }
+    }
+
+    if !info.keywords.is_empty() {
+        println!("  keywords: {}", info.keywords.join(", "));
+    }
+    if !info.categories.is_empty() {
+        println!("  categories: {}", info.categories.join(", "));
+    }
+    if !features.is_empty() {
+        println!("  features: {}", features.join(", "));
+    }
+
+    let mut counts = vec![format!("{} total", downloads.total)];
+    if let Some(recent) = downloads.recent {
+        counts.push(format!("{recent} recent"));
+    }
+    if let Some(count) = downloads.version {
+        counts.push(format!("{count} of {version}"));
+    }
+    println!("  downloads: {}", counts.join(", "));
+
+    if let Some(owners) = owners.as_ref().filter(|owners| !owners.is_empty()) {
+        let owners = owners
+            .iter()
+            .map(|owner| format!("{} ({})", owner.login, owner.kind))
+            .collect::<Vec<String>>();
+        println!("  owners: {}", owners.join(", "));
+    }
+    if let Some(docs) = docs {
+        println!("  docs.rs: {docs}");
+    }
+}
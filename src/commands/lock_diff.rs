@@ -0,0 +1,63 @@
+use crate::{
+    cli::{LockDiffOptions, Options},
+    project,
+};
+use anyhow::{bail, Result};
+use cargo_lookup::{DependencyKind, SelectionPolicy};
+
+/// Resolve the roots of a lockfile afresh and print how the result differs from it
+///
+/// Lockfiles don't record the requirements their roots were added with, so each root is
+/// resolved with a caret requirement on it's locked version, like `cargo update` with the
+/// default requirements `cargo add` writes. Fails if anything would change
+pub fn run(lock: &LockDiffOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let path = &lock.lockfile;
+    let lockfile = project::read_lockfile(path)?;
+
+    let roots = lockfile
+        .roots()
+        .map(|root| format!("{}@^{}", root.name, root.version))
+        .collect::<Vec<String>>();
+    // Dev dependencies of registry packages are never locked
+    let (_, graph) = crate::resolve_with_dep_kinds(
+        &roots,
+        [DependencyKind::Normal, DependencyKind::Build],
+        policy,
+        options,
+    )?;
+    let diff = lockfile.compare(&graph);
+
+    if !crate::print_structured(&diff, options)? {
+        for change in &diff.changed {
+            let breaking = if change.bump.is_breaking() {
+                " (breaking)"
+            } else {
+                ""
+            };
+            println!(
+                "{}: {} -> {}{breaking}",
+                change.name, change.version.old, change.version.new
+            );
+        }
+        for added in &diff.added {
+            println!("{}: added {}", added.name, added.version);
+        }
+        for removed in &diff.removed {
+            println!("{}: removed {}", removed.name, removed.version);
+        }
+        for change in &diff.checksums {
+            println!(
+                "{} {}: locked checksum {} doesn't match the index's {}",
+                change.name, change.version, change.checksum.old, change.checksum.new
+            );
+        }
+    }
+
+    if !diff.is_empty() {
+        let changes =
+            diff.changed.len() + diff.added.len() + diff.removed.len() + diff.checksums.len();
+        bail!("{changes} package(s) differ from `{}`", path.display());
+    }
+
+    Ok(())
+}
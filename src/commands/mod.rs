@@ -0,0 +1,23 @@
+pub mod audit_lock;
+pub mod available;
+pub mod capabilities;
+pub mod diff;
+pub mod download;
+pub mod downloads;
+pub mod exists;
+pub mod graph;
+pub mod index_path;
+pub mod info;
+pub mod lock_diff;
+pub mod msrv;
+pub mod outdated;
+pub mod owners;
+pub mod pin;
+pub mod raw;
+pub mod rdeps;
+pub mod sbom;
+pub mod search;
+pub mod tree;
+pub mod tree_size;
+pub mod verify;
+pub mod why;
@@ -0,0 +1,70 @@
+use crate::cli::{MsrvOptions, Options};
+use anyhow::Result;
+use cargo_lookup::{DependencyKind, SelectionPolicy};
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct TreeMsrv {
+    /// The highest `rust_version` in the tree, if any release declares one
+    rust_version: Option<Version>,
+    /// The releases declaring that `rust_version`
+    imposed_by: Vec<Step>,
+    /// The releases without a `rust_version`
+    unspecified: Vec<Step>,
+}
+
+#[derive(Debug, Serialize)]
+struct Step {
+    name: String,
+    version: Version,
+}
+
+/// Print the minimum supported rust version of a package's dependency tree, and the crates that
+/// impose it
+///
+/// Only normal and build dependencies are needed to use the package, so dev dependencies are
+/// left out. With `--msrv`, the tree is resolved to releases supporting that version, so this
+/// fails if there's no such tree
+pub fn run(msrv: &MsrvOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let packages = [msrv.package.clone()];
+    let dep_kinds = [DependencyKind::Normal, DependencyKind::Build];
+    let (_, graph) = crate::resolve_with_dep_kinds(&packages, dep_kinds, policy, options)?;
+
+    let tree_msrv = graph.msrv();
+    let step = |id| {
+        let release = graph.release(id);
+        Step {
+            name: release.name.clone(),
+            version: release.vers.clone(),
+        }
+    };
+    let tree_msrv = TreeMsrv {
+        rust_version: tree_msrv.rust_version,
+        imposed_by: tree_msrv.imposed_by.into_iter().map(step).collect(),
+        unspecified: tree_msrv.unspecified.into_iter().map(step).collect(),
+    };
+
+    if !crate::print_structured(&tree_msrv, options)? {
+        match &tree_msrv.rust_version {
+            Some(rust_version) => {
+                let imposed_by = tree_msrv
+                    .imposed_by
+                    .iter()
+                    .map(|Step { name, version }| format!("{name} {version}"))
+                    .collect::<Vec<String>>();
+                println!("{rust_version} (required by {})", imposed_by.join(", "));
+            }
+            None => println!("no crate in the tree declares a rust version"),
+        }
+
+        if !tree_msrv.unspecified.is_empty() {
+            println!(
+                "{} crate(s) don't declare a rust version",
+                tree_msrv.unspecified.len()
+            );
+        }
+    }
+
+    Ok(())
+}
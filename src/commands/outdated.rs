@@ -0,0 +1,321 @@
+use crate::{
+    cache::Packages,
+    cli::{Options, OutdatedOptions},
+    jobs, project,
+    table::Table,
+};
+use anyhow::{Context, Result};
+use cargo_lookup::{
+    lockfile::Lockfile,
+    manifest::{manifest_requirement, update_requirements, RequirementUpdate},
+    outdated::Outdated,
+    Dependency, SelectionPolicy,
+};
+use semver::{Version, VersionReq};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process, slice,
+};
+
+/// Lines of unchanged context shown around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// List the dependencies of a project that have newer releases than the ones in use
+///
+/// The versions in use are read from the project's lockfile when there is one. Without one, the
+/// newest release matching each requirement is taken to be in use, so only dependencies whose
+/// requirement holds them back are listed
+pub fn run(outdated: &OutdatedOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let declared = project::manifest_dependencies(&outdated.manifest_path, options)?;
+
+    // The same requirement with different features is only checked once
+    let mut dependencies = declared.clone();
+    dependencies.sort_by(|a, b| {
+        (a.package_name(), a.req.to_string()).cmp(&(b.package_name(), b.req.to_string()))
+    });
+    dependencies.dedup_by(|a, b| a.package_name() == b.package_name() && a.req == b.req);
+
+    let lockfile = match outdated
+        .lockfile
+        .clone()
+        .or_else(|| project::find_lockfile(&outdated.manifest_path))
+    {
+        Some(path) => Some(project::read_lockfile(&path)?),
+        None => None,
+    };
+
+    let packages = Packages::default();
+    let checked = jobs::parallel_map(&dependencies, options.jobs(), |dependency| {
+        let query = crate::query(dependency.package_name(), options, policy)?;
+        let package = packages.fetch(&query)?;
+        let locked = lockfile
+            .as_ref()
+            .and_then(|lockfile| locked_version(lockfile, dependency));
+
+        Ok::<_, anyhow::Error>(package.outdated(&dependency.req, locked.as_ref(), policy))
+    });
+
+    let mut outdated_deps = Vec::new();
+    for (dependency, checked) in dependencies.into_iter().zip(checked) {
+        let checked = checked?;
+        if checked.is_outdated() {
+            outdated_deps.push((dependency, checked));
+        }
+    }
+
+    let found = !outdated_deps.is_empty();
+
+    if outdated.suggest || outdated.diff {
+        // Suggestions are made for every declaration of an outdated dependency, since they may
+        // be renamed or enable different features
+        let mut suggested = Vec::new();
+        for dependency in declared {
+            let req = outdated_deps
+                .iter()
+                .find(|(checked, _)| {
+                    checked.package_name() == dependency.package_name()
+                        && checked.req == dependency.req
+                })
+                .and_then(|(_, checked)| checked.suggested_req());
+
+            if let Some(req) = req {
+                suggested.push((dependency, req));
+            }
+        }
+
+        if outdated.diff {
+            print_diff(&outdated.manifest_path, &suggested)?;
+        } else {
+            print_suggestions(&suggested, options)?;
+        }
+    } else {
+        let checked = outdated_deps
+            .into_iter()
+            .map(|(_, checked)| checked)
+            .collect::<Vec<Outdated>>();
+
+        print_outdated(&checked, options)?;
+    }
+
+    // Like `git diff --exit-code`, so CI can fail when there are updates
+    if outdated.exit_code && found {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print the in use, newest compatible, and latest versions of each outdated dependency
+fn print_outdated(outdated: &[Outdated], options: &Options) -> Result<()> {
+    let version = |version: &Option<Version>| {
+        version
+            .as_ref()
+            .map_or("none".to_owned(), Version::to_string)
+    };
+
+    if options.format.is_table() {
+        let mut table =
+            Table::with_header(&["name", "requirement", "current", "compatible", "latest"]);
+        for Outdated {
+            name,
+            req,
+            current,
+            compatible,
+            latest,
+        } in outdated
+        {
+            table.push(vec![
+                name.clone(),
+                req.to_string(),
+                version(current),
+                version(compatible),
+                version(latest),
+            ]);
+        }
+
+        crate::print_table(&table, options);
+        return Ok(());
+    }
+
+    if !crate::print_structured(&outdated, options)? {
+        for Outdated {
+            name,
+            req,
+            current,
+            compatible,
+            latest,
+        } in outdated
+        {
+            println!(
+                "{name} {req}: {} in use, {} compatible, {} latest",
+                version(current),
+                version(compatible),
+                version(latest)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Print each outdated dependency declared with a requirement on it's newest release, ready to
+/// paste into a `Cargo.toml`
+fn print_suggestions(suggested: &[(Dependency, VersionReq)], options: &Options) -> Result<()> {
+    if crate::print_structured(&requirement_updates(suggested), options)? {
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+    for (dependency, req) in suggested {
+        let line = Dependency {
+            req: req.clone(),
+            ..dependency.clone()
+        }
+        .to_manifest_line();
+
+        if !lines.contains(&line) {
+            println!("{line}");
+            lines.push(line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a unified diff updating the requirements of outdated dependencies in every manifest of
+/// the project
+///
+/// Requirements a workspace member inherits with `workspace = true` are updated in the
+/// workspace root's manifest. A warning is printed for any requirement that isn't found
+fn print_diff(manifest_path: &Path, suggested: &[(Dependency, VersionReq)]) -> Result<()> {
+    let updates = requirement_updates(suggested);
+    let mut manifests = project::manifest_paths(manifest_path)?
+        .into_iter()
+        .map(|path| (path, updates.clone()))
+        .collect::<Vec<(PathBuf, Vec<RequirementUpdate>)>>();
+
+    if let Some(root) = project::workspace_root(manifest_path)? {
+        let member = project::read_manifest(manifest_path)?;
+        let inherited = member.inherited();
+        let inherited_updates = updates
+            .iter()
+            .filter(|update| inherited.contains(&update.name.as_str()))
+            .cloned()
+            .collect();
+        manifests.push((root, inherited_updates));
+    }
+
+    let mut applied = vec![false; updates.len()];
+    for (path, manifest_updates) in manifests {
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let updated = update_requirements(&contents, &manifest_updates);
+
+        if updated != contents {
+            print!("{}", unified_diff(&path, &contents, &updated));
+        }
+
+        for (update, applied) in updates.iter().zip(&mut applied) {
+            *applied |= manifest_updates.contains(update)
+                && update_requirements(&contents, slice::from_ref(update)) != contents;
+        }
+    }
+
+    for (update, applied) in updates.iter().zip(applied) {
+        if !applied {
+            eprintln!(
+                "warning: couldn't find the requirement `{}` of `{}` in the manifests, update it by hand",
+                update.from, update.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the distinct requirement changes of the suggested dependencies
+fn requirement_updates(suggested: &[(Dependency, VersionReq)]) -> Vec<RequirementUpdate> {
+    let mut updates = Vec::new();
+
+    for (dependency, req) in suggested {
+        let update = RequirementUpdate {
+            name: dependency.name.clone(),
+            from: dependency.req.clone(),
+            to: manifest_requirement(req),
+        };
+        if !updates.contains(&update) {
+            updates.push(update);
+        }
+    }
+
+    updates
+}
+
+/// Write a unified diff between two versions of a file that have the same number of lines, as
+/// they do when only requirements are replaced
+fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+    let changed = (0..old.len())
+        .filter(|&line| old.get(line) != new.get(line))
+        .collect::<Vec<usize>>();
+
+    let path = path.display();
+    let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+
+    let mut index = 0;
+    while index < changed.len() {
+        // Changes close enough for their context to overlap share a hunk
+        let mut last = index;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= DIFF_CONTEXT * 2 {
+            last += 1;
+        }
+
+        let start = changed[index].saturating_sub(DIFF_CONTEXT);
+        let end = (changed[last] + DIFF_CONTEXT + 1).min(old.len());
+        let len = end - start;
+        diff.push_str(&format!(
+            "@@ -{},{len} +{},{len} @@\n",
+            start + 1,
+            start + 1
+        ));
+
+        let mut line = start;
+        while line < end {
+            if !changed[index..=last].contains(&line) {
+                diff.push_str(&format!(" {}\n", old[line]));
+                line += 1;
+                continue;
+            }
+
+            // A run of changed lines is shown as removed, then added
+            let run = (line..end)
+                .take_while(|line| changed[index..=last].contains(line))
+                .collect::<Vec<usize>>();
+            for &changed in &run {
+                diff.push_str(&format!("-{}\n", old[changed]));
+            }
+            for &changed in &run {
+                diff.push_str(&format!("+{}\n", new[changed]));
+            }
+            line += run.len();
+        }
+
+        index = last + 1;
+    }
+
+    diff
+}
+
+/// Return the newest locked version of a dependency's package matching it's requirement
+fn locked_version(lockfile: &Lockfile, dependency: &Dependency) -> Option<Version> {
+    lockfile
+        .packages
+        .iter()
+        .filter(|package| package.name == dependency.package_name() && package.is_registry())
+        .map(|package| &package.version)
+        .filter(|version| dependency.req.matches(version))
+        .max()
+        .cloned()
+}
@@ -0,0 +1,26 @@
+use crate::cli::{Options, OwnersOptions};
+use anyhow::{Context, Result};
+use cargo_lookup::api::Owner;
+
+/// List the users and teams that own a crate, using the API of the registry with `--index-url`
+pub fn run(owners: &OwnersOptions, options: &Options) -> Result<()> {
+    let package = &owners.package;
+    let client = crate::api_client(options)?;
+    let owners = client
+        .owners(package)
+        .with_context(|| format!("failed to fetch owners of `{package}`"))?;
+
+    if !crate::print_structured(&owners, options)? {
+        for Owner {
+            login, kind, name, ..
+        } in owners
+        {
+            match name {
+                Some(name) => println!("{login} ({kind}, {name})"),
+                None => println!("{login} ({kind})"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::{
+    cli::{Options, PinOptions},
+    jobs,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_lookup::SelectionPolicy;
+use semver::VersionReq;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Write a pin file for the packages listed in `--from-file`, or check an existing one for drift
+pub fn run(pin: &PinOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    match (&pin.verify, &pin.from_file) {
+        (Some(pins), _) => verify(pins, options, policy),
+        (None, Some(from_file)) => write(from_file, pin.out.as_deref(), options, policy),
+        (None, None) => bail!("either `--from-file` or `--verify` is required"),
+    }
+}
+
+fn write(
+    from_file: &Path,
+    out: Option<&Path>,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<()> {
+    let contents = fs::read_to_string(from_file)
+        .with_context(|| format!("failed to read `{}`", from_file.display()))?;
+
+    let specs = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<&str>>();
+
+    let releases = jobs::parallel_map(&specs, options.jobs(), |spec| {
+        crate::query(spec, options, policy)?
+            .submit()?
+            .ok_or_else(|| anyhow!("failed to find a matching release of `{spec}`"))
+    });
+
+    let mut pins = BTreeMap::new();
+    for release in releases {
+        let release = release?;
+        pins.insert(release.name, format!("={}", release.vers));
+    }
+
+    let pins = toml::to_string(&pins)?;
+
+    match out {
+        Some(out) => {
+            fs::write(out, pins).with_context(|| format!("failed to write `{}`", out.display()))?
+        }
+        None => print!("{pins}"),
+    }
+
+    Ok(())
+}
+
+fn verify(path: &Path, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let pins: BTreeMap<String, String> = toml::from_str(&contents)
+        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+
+    let pins = pins.into_iter().collect::<Vec<(String, String)>>();
+    let latest = jobs::parallel_map(&pins, options.jobs(), |(name, _)| {
+        crate::query(name, options, policy)?
+            .submit()?
+            .ok_or_else(|| anyhow!("failed to find a matching release of `{name}`"))
+    });
+
+    let mut drifted = 0;
+
+    for ((name, pinned), latest) in pins.into_iter().zip(latest) {
+        let pinned_req = VersionReq::parse(&pinned)
+            .with_context(|| format!("invalid pin for `{name}`: `{pinned}`"))?;
+        let latest = latest?;
+
+        if !pinned_req.matches(&latest.vers) {
+            let pinned = pinned.trim_start_matches('=');
+            let latest = &latest.vers;
+            println!("{name}: pinned {pinned}, latest {latest}");
+            drifted += 1;
+        }
+    }
+
+    if drifted > 0 {
+        bail!("{drifted} pinned package(s) have drifted from the latest release");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::cli::{Options, RawOptions};
+use anyhow::{anyhow, Context, Result};
+use cargo_lookup::SelectionPolicy;
+use semver::Version;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Line {
+    vers: Version,
+}
+
+/// Print the index file of a crate exactly as the registry serves it, for piping into `jq` or
+/// debugging a registry
+///
+/// With a version requirement, only the lines of matching releases are printed. Every other line
+/// is left untouched
+pub fn run(raw: &RawOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let query = crate::query(&raw.package, options, policy)?;
+    let index = match query.raw_index() {
+        Ok(index) => index,
+        Err(error) if error.is_not_found() && options.index_url.is_none() => {
+            return Err(crate::not_found(query.name(), error))
+        }
+        Err(error) => return Err(anyhow!(error)),
+    };
+
+    for line in index.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(version_req) = query.version_req() {
+            let Line { vers } = serde_json::from_str(line)
+                .with_context(|| format!("failed to parse index line: {line}"))?;
+            if !version_req.matches(&vers) {
+                continue;
+            }
+        }
+
+        println!("{line}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::cli::{Options, RdepsOptions};
+use anyhow::{Context, Result};
+use cargo_lookup::api::ReverseDependency;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Dependents {
+    total: u64,
+    /// Missing with `--count-only`, since dependents aren't fetched then
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependents: Option<Vec<ReverseDependency>>,
+}
+
+/// List, or just count, the crates that depend on a crate, using the API of the registry with
+/// `--index-url`
+///
+/// Counting takes a single request, so `--count-only` is quicker for impact estimates
+pub fn run(rdeps: &RdepsOptions, options: &Options) -> Result<()> {
+    let package = &rdeps.package;
+    let client = crate::api_client(options)?;
+
+    let dependents = if rdeps.count_only {
+        client
+            .reverse_dependency_count(package)
+            .map(|total| Dependents {
+                total,
+                dependents: None,
+            })
+    } else {
+        client
+            .reverse_dependencies(package)
+            .map(|dependents| Dependents {
+                total: dependents.total,
+                dependents: Some(dependents.dependents),
+            })
+    }
+    .with_context(|| format!("failed to fetch reverse dependencies of `{package}`"))?;
+
+    if !crate::print_structured(&dependents, options)? {
+        println!("{package}: {} dependents", dependents.total);
+
+        for ReverseDependency {
+            name,
+            vers,
+            req,
+            kind,
+            optional,
+            ..
+        } in dependents.dependents.unwrap_or_default()
+        {
+            let optional = if optional { ", optional" } else { "" };
+            println!("  {name} {vers} ({req}, {kind}{optional})");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use crate::{
+    cache::Packages,
+    cli::{Format, Options, SbomFormat, SbomOptions},
+    jobs, project,
+};
+use anyhow::{anyhow, Result};
+use cargo_lookup::{
+    api::{Owner, OwnerKind},
+    graph::DependencyGraph,
+    lockfile::{LockedPackage, Lockfile},
+    sbom::{Sbom, Supplier},
+    DependencyKind, SelectionPolicy, CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use std::{collections::BTreeMap, path::Path};
+
+/// Write a software bill of materials for a package's dependency tree, or for the registry
+/// packages of a lockfile
+///
+/// Only normal and build dependencies end up in what's shipped, so dev dependencies are left
+/// out. Licenses and suppliers are fetched from the registry's API unless `--no-api` is passed,
+/// and are left out if the API can't be reached
+pub fn run(sbom: &SbomOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+
+    let graph = match (&sbom.lockfile, &sbom.package) {
+        (Some(path), _) => lockfile_graph(path, options, policy)?,
+        (None, Some(package)) => {
+            let packages = [package.clone()];
+            let dep_kinds = [DependencyKind::Normal, DependencyKind::Build];
+            crate::resolve_with_dep_kinds(&packages, dep_kinds, policy, options)?.1
+        }
+        (None, None) => unreachable!("clap requires a package or `--lockfile`"),
+    };
+
+    let mut bom = Sbom::new(&graph, index_url);
+    if !sbom.no_api {
+        for (name, (licenses, supplier)) in api_data(&graph, options) {
+            for (version, license) in licenses {
+                if graph.find(&name, &version).is_some() {
+                    bom = bom.with_license(&name, &version, &license);
+                }
+            }
+            if let Some(supplier) = supplier {
+                bom = bom.with_supplier(&name, supplier);
+            }
+        }
+    }
+
+    let document = match sbom.emit {
+        SbomFormat::Cyclonedx => bom.to_cyclonedx(),
+        SbomFormat::Spdx => bom.to_spdx(),
+    };
+    match options.format {
+        Format::Pretty => println!("{}", serde_json::to_string_pretty(&document)?),
+        _ => println!("{}", serde_json::to_string(&document)?),
+    }
+
+    Ok(())
+}
+
+/// Build a graph of the registry packages in a lockfile, with each locked release looked up in
+/// the index it's locked from so it's checksum comes from there
+fn lockfile_graph(
+    path: &Path,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<DependencyGraph> {
+    let lockfile = project::read_lockfile(path)?;
+    let locked = lockfile
+        .packages
+        .iter()
+        .filter(|package| package.is_registry())
+        .collect::<Vec<&LockedPackage>>();
+
+    let packages = Packages::default();
+    let releases = jobs::parallel_map(&locked, options.jobs(), |locked| {
+        // Packages are looked up in the registry they're locked from
+        let index_url = locked.index_url().ok_or_else(|| {
+            anyhow!(
+                "`{} {}` is from a git index, only sparse indexes are supported",
+                locked.name,
+                locked.version
+            )
+        })?;
+        let query = crate::query(&locked.name, options, policy)?.with_index(index_url);
+        let package = packages.fetch(&query)?;
+
+        package
+            .releases()
+            .iter()
+            .find(|release| release.vers == locked.version)
+            .cloned()
+            .ok_or_else(|| anyhow!("`{} {}` isn't in the index", locked.name, locked.version))
+    });
+
+    let mut graph = DependencyGraph::new();
+    let mut ids = BTreeMap::new();
+    for (locked, release) in locked.iter().zip(releases) {
+        let id = graph.add_release(release?);
+        ids.insert((&locked.name, &locked.version), id);
+    }
+
+    for root in lockfile.roots() {
+        graph.add_root(ids[&(&root.name, &root.version)]);
+    }
+    for locked in &locked {
+        let from = ids[&(&locked.name, &locked.version)];
+        let deps = graph.release(from).deps.clone();
+
+        for dependency in locked_dependencies(&lockfile, locked) {
+            let to = ids[&(&dependency.name, &dependency.version)];
+
+            // A crate can be both a normal and a build dependency, with an edge for each
+            for dep in deps.iter().filter(|dep| {
+                dep.kind != DependencyKind::Dev
+                    && dep.package_name() == dependency.name
+                    && dep.req.matches(&dependency.version)
+            }) {
+                graph.add_edge(from, to, dep.clone());
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Return the registry packages a locked package depends on
+fn locked_dependencies<'a>(
+    lockfile: &'a Lockfile,
+    locked: &'a LockedPackage,
+) -> impl Iterator<Item = &'a LockedPackage> {
+    locked
+        .dependencies
+        .iter()
+        .filter_map(|entry| lockfile.dependency(entry))
+        .filter(|dependency| dependency.is_registry())
+}
+
+type ApiData = (BTreeMap<Version, String>, Option<Supplier>);
+
+/// Fetch the licenses of each package in the graph, and it's first owner as the supplier
+///
+/// Like other enrichment, this is best-effort, so packages the API can't answer for are left
+/// without them
+fn api_data(graph: &DependencyGraph, options: &Options) -> BTreeMap<String, ApiData> {
+    let client = match crate::api_client(options) {
+        Ok(client) => client,
+        Err(error) => {
+            eprintln!(
+                "warning: licenses and suppliers unavailable, continuing without them: {error}"
+            );
+            return BTreeMap::new();
+        }
+    };
+
+    let mut names = graph
+        .releases()
+        .iter()
+        .map(|release| release.name.clone())
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+
+    let results = jobs::parallel_map(&names, options.jobs(), |name| {
+        let licenses = client.licenses(name)?;
+        let supplier = client.owners(name)?.into_iter().next().map(supplier);
+        cargo_lookup::Result::Ok((licenses, supplier))
+    });
+
+    let mut data = BTreeMap::new();
+    let mut failed = false;
+    for (name, result) in names.into_iter().zip(results) {
+        match result {
+            Ok(api_data) => {
+                data.insert(name, api_data);
+            }
+            Err(error) if !failed => {
+                eprintln!(
+                    "warning: licenses and suppliers unavailable for some crates, continuing without them: {error}"
+                );
+                failed = true;
+            }
+            Err(_) => {}
+        }
+    }
+
+    data
+}
+
+/// Describe an owner as a supplier, by their display name if they have one
+fn supplier(owner: Owner) -> Supplier {
+    let name = owner.name.unwrap_or(owner.login);
+
+    match owner.kind {
+        OwnerKind::User => Supplier::Person(name),
+        OwnerKind::Team => Supplier::Organization(name),
+    }
+}
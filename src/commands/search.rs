@@ -0,0 +1,36 @@
+use crate::cli::{Options, SearchOptions};
+use anyhow::Result;
+use cargo_lookup::api::SearchResult;
+
+/// Search the registry for crates, printing them like `cargo search`
+pub fn run(search: &SearchOptions, options: &Options) -> Result<()> {
+    let client = crate::api_client(options)?;
+    let results = client.search(&search.terms.join(" "), search.limit)?;
+
+    if !crate::print_structured(&results, options)? {
+        let width = results
+            .iter()
+            .map(|result| result.name.len() + result.latest.to_string().len())
+            .max()
+            .unwrap_or_default();
+
+        for SearchResult {
+            name,
+            description,
+            latest,
+        } in results
+        {
+            let line = format!("{name} = \"{latest}\"");
+            match description {
+                // Descriptions can span several lines, so only the first is shown
+                Some(description) => {
+                    let description = description.lines().next().unwrap_or_default().trim();
+                    println!("{line:<width$} # {description}", width = width + 5);
+                }
+                None => println!("{line}"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use crate::cli::{Options, TreeOptions};
+use anyhow::Result;
+use cargo_lookup::SelectionPolicy;
+
+/// Print the dependency tree of a package, resolved like `--resolve`
+///
+/// Features, `--target`, and dependency kinds are respected the same way, and releases that
+/// appear more than once are only expanded the first time
+pub fn run(tree: &TreeOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let packages = [tree.package.clone()];
+    let (_, graph) = crate::resolve_consistent(&packages, policy, options)?;
+
+    print!("{}", graph.to_tree(tree.depth, tree.charset.clone().into()));
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::{
+    cache::Packages,
+    cli::{Options, TreeSizeOptions},
+    jobs,
+};
+use anyhow::{anyhow, Result};
+use cargo_lookup::{
+    download::RegistryConfig, normalize_name, DependencyKind, Release, SelectionPolicy,
+    CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+#[derive(Debug, Serialize)]
+struct TreeSize {
+    /// The combined size of every `.crate` file whose size is known
+    total: u64,
+    /// Crates in the tree, largest first
+    crates: Vec<CrateSize>,
+}
+
+#[derive(Debug, Serialize)]
+struct CrateSize {
+    name: String,
+    version: Version,
+    size: Option<u64>,
+}
+
+/// Sum the `.crate` sizes of a package and everything it depends on
+///
+/// Only normal and build dependencies enabled by the features each dependent asks for are
+/// included. Without `--target`, dependencies for every target are counted, so this is an upper
+/// bound for any one platform
+pub fn run(tree: &TreeSizeOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let releases = resolve_tree(tree, options, policy)?;
+
+    let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+    let config = RegistryConfig::fetch(index_url)?;
+    let sizes = jobs::parallel_map(&releases, options.jobs(), |release| {
+        config.crate_size(release)
+    });
+
+    let mut crates = releases
+        .into_iter()
+        .zip(sizes)
+        .map(|(release, size)| {
+            Ok(CrateSize {
+                name: release.name,
+                version: release.vers,
+                size: size?,
+            })
+        })
+        .collect::<Result<Vec<CrateSize>>>()?;
+    crates.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    let tree_size = TreeSize {
+        total: crates.iter().filter_map(|krate| krate.size).sum(),
+        crates,
+    };
+
+    if !crate::print_structured(&tree_size, options)? {
+        for CrateSize {
+            name,
+            version,
+            size,
+        } in &tree_size.crates
+        {
+            let size = size.map_or("unknown".to_owned(), crate::human_size);
+            println!("{size:>10} {name} {version}");
+        }
+
+        let count = tree_size.crates.len();
+        let total = crate::human_size(tree_size.total);
+        println!("{total:>10} total ({count} crates)");
+    }
+
+    Ok(())
+}
+
+/// Resolve a package and every dependency it enables, each only once
+fn resolve_tree(
+    tree: &TreeSizeOptions,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<Vec<Release>> {
+    let root_features = crate::query(&tree.package, options, policy)?
+        .features()
+        .to_vec();
+
+    // Each entry is a query, and the features and default features its dependent enables
+    let mut queue = VecDeque::from([(tree.package.clone(), root_features, true)]);
+    let mut seen = HashSet::new();
+    let packages = Packages::default();
+    let mut resolved: Vec<Release> = Vec::new();
+
+    while let Some(next) = queue.pop_front() {
+        if !seen.insert(next.clone()) {
+            continue;
+        }
+
+        let (package, features, default_features) = next;
+        let query = crate::query(&package, options, policy)?;
+        let release = packages
+            .fetch(&query)?
+            .select(query.version_req(), policy)
+            .cloned()
+            .ok_or_else(|| anyhow!("failed to find a matching release of `{package}`"))?;
+
+        let name = normalize_name(&release.name);
+        if resolved
+            .iter()
+            .any(|res| normalize_name(&res.name) == name && res.vers == release.vers)
+        {
+            continue;
+        }
+
+        let selected = features.iter().map(String::as_str).collect::<Vec<&str>>();
+        // Features a dependent asks for may not exist in the release that was selected
+        let activated = release
+            .activated_deps(&selected, default_features)
+            .or_else(|_| release.activated_deps(&[], default_features))?;
+
+        for activated in activated {
+            let dep = activated.dependency;
+            if dep.kind == DependencyKind::Dev || !options.includes_dep_target(dep) {
+                continue;
+            }
+
+            queue.push_back((
+                format!("{}@{}", dep.package_name(), dep.req),
+                activated.features.into_iter().collect(),
+                dep.default_features,
+            ));
+        }
+
+        resolved.push(release);
+    }
+
+    Ok(resolved)
+}
@@ -0,0 +1,141 @@
+use crate::cli::{Options, VerifyOptions};
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_lookup::{checksum::parse_crate_file_name, error::Error, SelectionPolicy};
+use semver::Version;
+use serde::Serialize;
+use std::{fs::File, path::Path};
+
+#[derive(Debug, Serialize)]
+struct Verified {
+    path: String,
+    name: String,
+    version: Version,
+    status: Status,
+    /// Why the file couldn't be verified, or how it's checksum differs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Ok,
+    /// The release is in the index, but yanked
+    Yanked,
+    Mismatch,
+    Failed,
+}
+
+/// Check `.crate` files against the checksums of their releases in the index, e.g. files that
+/// were vendored or mirrored
+///
+/// The name and version of each file come from it's file name unless they're given with `--name`
+/// and `--version`. Fails if any file doesn't match or can't be checked. Yanked releases still
+/// pass, with a warning
+pub fn run(verify: &VerifyOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    if verify.name.is_some() && verify.files.len() > 1 {
+        bail!("`--name` and `--version` can only be given for a single file");
+    }
+
+    let verified = verify
+        .files
+        .iter()
+        .map(|path| {
+            let (name, version) = match (&verify.name, &verify.version) {
+                (Some(name), Some(version)) => (name.clone(), version.clone()),
+                _ => release_of(path)?,
+            };
+            let (status, error) = match check(path, &name, &version, options, policy) {
+                Ok(status) => (status, None),
+                Err(error) => match error.downcast_ref::<Error>() {
+                    Some(Error::ChecksumMismatch { .. }) => (Status::Mismatch, Some(error)),
+                    _ => (Status::Failed, Some(error)),
+                },
+            };
+
+            Ok(Verified {
+                path: path.display().to_string(),
+                name,
+                version,
+                status,
+                error: error.map(|error| format!("{error:#}")),
+            })
+        })
+        .collect::<Result<Vec<Verified>>>()?;
+
+    if !crate::print_structured(&verified, options)? {
+        print(&verified);
+    }
+
+    let failed = verified
+        .iter()
+        .filter(|verified| matches!(verified.status, Status::Mismatch | Status::Failed))
+        .count();
+    if failed > 0 {
+        return Err(anyhow!(
+            "{failed} of {} files failed to verify",
+            verified.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Read the name and version of a release from it's `.crate` file name
+fn release_of(path: &Path) -> Result<(String, Version)> {
+    path.file_name()
+        .and_then(|file_name| parse_crate_file_name(&file_name.to_string_lossy()))
+        .ok_or_else(|| {
+            anyhow!(
+                "`{}` isn't named like `<name>-<version>.crate`, pass `--name` and `--version`",
+                path.display()
+            )
+        })
+}
+
+/// Check a file against the checksum of it's release in the index
+fn check(
+    path: &Path,
+    name: &str,
+    version: &Version,
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<Status> {
+    let package = crate::query(name, options, policy)?.package()?;
+    let release = package
+        .releases()
+        .iter()
+        .find(|release| &release.vers == version)
+        .ok_or_else(|| anyhow!("`{name} {version}` isn't in the index"))?;
+
+    let file = File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?;
+    release.verify_checksum_reader(file)?;
+
+    Ok(if release.yanked {
+        Status::Yanked
+    } else {
+        Status::Ok
+    })
+}
+
+fn print(verified: &[Verified]) {
+    for Verified {
+        path,
+        name,
+        version,
+        status,
+        error,
+    } in verified
+    {
+        let error = error.as_deref().unwrap_or_default();
+        match status {
+            Status::Ok => println!("ok {path} ({name} {version})"),
+            Status::Yanked => {
+                println!("ok {path} ({name} {version})");
+                eprintln!("warning: {name} {version} is yanked");
+            }
+            Status::Mismatch => println!("mismatch {path} ({name} {version}): {error}"),
+            Status::Failed => eprintln!("error: failed to verify {path}: {error}"),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::cli::{Options, WhyOptions};
+use anyhow::{bail, Result};
+use cargo_lookup::SelectionPolicy;
+use semver::Version;
+use serde::Serialize;
+
+/// A release along a dependency path
+#[derive(Debug, Serialize)]
+struct Step {
+    name: String,
+    version: Version,
+    /// Set on the first step of a path that stops at a release whose dependents were already
+    /// shown, like `(*)` in `cargo tree`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    repeated: bool,
+}
+
+/// Print every dependency chain from a package to a crate in it's resolved dependency tree
+///
+/// Like `cargo tree --invert`, chains through a release that was already shown stop at it,
+/// marked with `(*)`
+///
+/// The tree is resolved like `--resolve`, so features, `--target`, and dependency kinds are
+/// respected the same way
+pub fn run(why: &WhyOptions, options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let packages = [why.package.clone()];
+    let (_, graph) = crate::resolve_consistent(&packages, policy, options)?;
+
+    let paths = graph
+        .paths_to(&why.dependency)
+        .into_iter()
+        .map(|path| {
+            let repeated = !graph.roots().contains(&path[0]);
+            path.into_iter()
+                .enumerate()
+                .map(|(index, id)| {
+                    let release = graph.release(id);
+                    Step {
+                        name: release.name.clone(),
+                        version: release.vers.clone(),
+                        repeated: repeated && index == 0,
+                    }
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<Step>>>();
+
+    if paths.is_empty() {
+        bail!(
+            "`{}` isn't in the dependency tree of `{}`",
+            why.dependency,
+            why.package
+        );
+    }
+
+    if !crate::print_structured(&paths, options)? {
+        for path in paths {
+            let path = path
+                .iter()
+                .map(|step| {
+                    let marker = if step.repeated { " (*)" } else { "" };
+                    format!("{} {}{marker}", step.name, step.version)
+                })
+                .collect::<Vec<String>>();
+            println!("{}", path.join(" -> "));
+        }
+    }
+
+    Ok(())
+}
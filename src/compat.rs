@@ -0,0 +1,66 @@
+//! Semver compatibility between versions, following cargo's caret semantics
+//!
+//! Cargo treats the leftmost non-zero version component as the breaking one, so `0.1.0` to
+//! `0.2.0` is as breaking as `1.0.0` to `2.0.0`, and any change to a `0.0.x` version is breaking.
+//!
+//! More info can be found in [The Cargo Book](https://doc.rust-lang.org/cargo/reference/resolver.html#semver-compatibility)
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// The kind of change between two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    /// Only the patch version or pre-release changed, or the versions are the same
+    Patch,
+    /// A compatible change that isn't just a patch, e.g. `1.2.0` to `1.3.0` or `0.1.2` to `0.1.3`
+    Minor,
+    /// A breaking change, e.g. `1.2.0` to `2.0.0` or `0.1.0` to `0.2.0`
+    Major,
+}
+
+impl Bump {
+    /// Whether a caret requirement on one version wouldn't be satisfied by the other
+    pub fn is_breaking(self) -> bool {
+        self == Bump::Major
+    }
+
+    /// Return the bump as a lowercase word, e.g. `minor`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+impl std::fmt::Display for Bump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Classify the change between two versions
+///
+/// The classification doesn't depend on direction, so a downgrade is classified the same as the
+/// equivalent upgrade
+pub fn compat(from: &Version, to: &Version) -> Bump {
+    let from_parts = [from.major, from.minor, from.patch];
+    let to_parts = [to.major, to.minor, to.patch];
+
+    // Index of the component cargo treats as breaking for the older of the two versions
+    let lower = if from <= to { from_parts } else { to_parts };
+    let breaking = lower.iter().position(|part| *part != 0).unwrap_or(2);
+    let changed = from_parts
+        .iter()
+        .zip(to_parts)
+        .position(|(from, to)| *from != to);
+
+    match changed {
+        Some(index) if index <= breaking => Bump::Major,
+        Some(index) if index == breaking + 1 => Bump::Minor,
+        _ => Bump::Patch,
+    }
+}
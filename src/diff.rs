@@ -0,0 +1,304 @@
+//! Differences between two releases of a package
+//!
+//! Dependencies are matched by name, kind, and target. A dependency that only changed kind (e.g.
+//! moved from `[dependencies]` to `[dev-dependencies]`) is reported as changed rather than as
+//! removed and added again
+//!
+//! Changes to features across many releases, e.g. to find the first release with a feature, are
+//! tracked with [`feature_history`]
+
+use crate::{
+    compat::{compat, Bump},
+    Dependency, Release,
+};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// A value that differs between two releases
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    /// The value in the release being compared from
+    pub old: T,
+    /// The value in the release being compared to
+    pub new: T,
+}
+
+/// The differences between two releases, see [`Release::diff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseDiff {
+    /// The name of the package
+    pub name: String,
+    /// The version being compared from
+    pub from: Version,
+    /// The version being compared to
+    pub to: Version,
+    /// How breaking the version change is under cargo's caret semantics
+    pub bump: Bump,
+    /// Dependencies only in the newer release
+    pub added_deps: Vec<Dependency>,
+    /// Dependencies only in the older release
+    pub removed_deps: Vec<Dependency>,
+    /// Dependencies in both releases with a different requirement, kind, or declaration
+    pub changed_deps: Vec<Change<Dependency>>,
+    /// Features only in the newer release
+    pub added_features: BTreeSet<String>,
+    /// Features only in the older release
+    pub removed_features: BTreeSet<String>,
+    /// Features in both releases that enable something different
+    pub changed_features: BTreeSet<String>,
+    /// The change in minimum supported rust version, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<Change<Option<VersionReq>>>,
+    /// The change in yank status, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yanked: Option<Change<bool>>,
+}
+
+impl ReleaseDiff {
+    /// Whether nothing other than the version differs between the releases
+    pub fn is_empty(&self) -> bool {
+        self.added_deps.is_empty()
+            && self.removed_deps.is_empty()
+            && self.changed_deps.is_empty()
+            && self.added_features.is_empty()
+            && self.removed_features.is_empty()
+            && self.changed_features.is_empty()
+            && self.rust_version.is_none()
+            && self.yanked.is_none()
+    }
+}
+
+/// Displays a summary line followed by one line per difference, e.g.
+///
+/// ```text
+/// foo 1.0.0 -> 1.1.0 (minor)
+///   + dep bar ^1 (normal)
+///   ~ dep baz ^0.3 -> ^0.4 (normal)
+///   - feature nightly
+/// ```
+impl std::fmt::Display for ReleaseDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            name,
+            from,
+            to,
+            bump,
+            ..
+        } = self;
+        write!(f, "{name} {from} -> {to} ({bump})")?;
+
+        for dep in &self.added_deps {
+            write!(f, "\n  + dep {} {} ({})", dep.name, dep.req, context(dep))?;
+        }
+        for dep in &self.removed_deps {
+            write!(f, "\n  - dep {} {} ({})", dep.name, dep.req, context(dep))?;
+        }
+        for Change { old, new } in &self.changed_deps {
+            write!(f, "\n  ~ dep {}", new.name)?;
+
+            if old.req != new.req {
+                write!(f, " {} -> {}", old.req, new.req)?;
+            }
+
+            if old.kind == new.kind {
+                write!(f, " ({})", context(new))?;
+            } else {
+                write!(f, " ({} -> {})", old.kind, context(new))?;
+            }
+        }
+        for feature in &self.added_features {
+            write!(f, "\n  + feature {feature}")?;
+        }
+        for feature in &self.removed_features {
+            write!(f, "\n  - feature {feature}")?;
+        }
+        for feature in &self.changed_features {
+            write!(f, "\n  ~ feature {feature}")?;
+        }
+        if let Some(Change { old, new }) = &self.rust_version {
+            let display = |version: &Option<VersionReq>| {
+                version
+                    .as_ref()
+                    .map_or_else(|| "none".to_owned(), |version| version.to_string())
+                    .trim_start_matches('^')
+                    .to_owned()
+            };
+            write!(f, "\n  ~ rust-version {} -> {}", display(old), display(new))?;
+        }
+        if let Some(Change { new, .. }) = &self.yanked {
+            let status = if *new { "yanked" } else { "unyanked" };
+            write!(f, "\n  ~ {status}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Release {
+    /// Compare this release to a newer one, reporting what changed between them
+    ///
+    /// Features are compared after merging `features2`, see [`Release::all_features`]
+    pub fn diff(&self, other: &Release) -> ReleaseDiff {
+        let (added_deps, removed_deps, changed_deps) = diff_deps(&self.deps, &other.deps);
+
+        let old_features = self.all_features();
+        let new_features = other.all_features();
+
+        let added_features = new_features
+            .keys()
+            .filter(|name| !old_features.contains_key(*name))
+            .cloned()
+            .collect();
+        let removed_features = old_features
+            .keys()
+            .filter(|name| !new_features.contains_key(*name))
+            .cloned()
+            .collect();
+        let changed_features = old_features
+            .iter()
+            .filter(|(name, enables)| {
+                new_features.get(*name).is_some_and(|new| {
+                    new.iter().collect::<BTreeSet<_>>() != enables.iter().collect::<BTreeSet<_>>()
+                })
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        ReleaseDiff {
+            name: other.name.clone(),
+            from: self.vers.clone(),
+            to: other.vers.clone(),
+            bump: compat(&self.vers, &other.vers),
+            added_deps,
+            removed_deps,
+            changed_deps,
+            added_features,
+            removed_features,
+            changed_features,
+            rust_version: change(&self.rust_version, &other.rust_version),
+            yanked: change(&self.yanked, &other.yanked),
+        }
+    }
+}
+
+/// The features a release added and removed, compared to the release before it, see
+/// [`feature_history`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureChanges {
+    /// The version of the release
+    pub version: Version,
+    /// Features the release has that the release before it doesn't. For the oldest release,
+    /// every feature it has
+    pub added: BTreeSet<String>,
+    /// Features the release before it has that the release doesn't
+    pub removed: BTreeSet<String>,
+}
+
+/// Track the features of releases from oldest to newest, reporting each release that added or
+/// removed a feature, along with the oldest release
+///
+/// Releases are compared in version order, whatever order they're given in. Features are
+/// compared after merging `features2`, see [`Release::all_features`]
+pub fn feature_history<'a, I>(releases: I) -> Vec<FeatureChanges>
+where
+    I: IntoIterator<Item = &'a Release>,
+{
+    let mut releases = releases.into_iter().collect::<Vec<&Release>>();
+    releases.sort_by(|a, b| a.vers.cmp(&b.vers));
+
+    let mut history = Vec::new();
+    let mut previous: Option<BTreeSet<String>> = None;
+
+    for release in releases {
+        let features = release
+            .all_features()
+            .into_keys()
+            .collect::<BTreeSet<String>>();
+        let (added, removed) = match &previous {
+            Some(previous) => (
+                features.difference(previous).cloned().collect(),
+                previous.difference(&features).cloned().collect(),
+            ),
+            None => (features.clone(), BTreeSet::new()),
+        };
+
+        if previous.is_none() || !added.is_empty() || !removed.is_empty() {
+            history.push(FeatureChanges {
+                version: release.vers.clone(),
+                added,
+                removed,
+            });
+        }
+        previous = Some(features);
+    }
+
+    history
+}
+
+/// The kind of a dependency, and it's target if it has one
+fn context(dep: &Dependency) -> String {
+    match &dep.target {
+        Some(target) => format!("{}, {target}", dep.kind),
+        None => dep.kind.to_string(),
+    }
+}
+
+fn change<T: Clone + PartialEq>(old: &T, new: &T) -> Option<Change<T>> {
+    (old != new).then(|| Change {
+        old: old.clone(),
+        new: new.clone(),
+    })
+}
+
+type DepsDiff = (Vec<Dependency>, Vec<Dependency>, Vec<Change<Dependency>>);
+
+fn diff_deps(old: &[Dependency], new: &[Dependency]) -> DepsDiff {
+    let mut removed = old.to_vec();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    // Pair dependencies with the same name, kind, and target first
+    for dep in new {
+        let same = removed.iter().position(|candidate| {
+            candidate.name == dep.name
+                && candidate.kind == dep.kind
+                && candidate.target == dep.target
+        });
+
+        match same {
+            Some(index) => {
+                let old = removed.remove(index);
+                if old != *dep {
+                    changed.push(Change {
+                        old,
+                        new: dep.clone(),
+                    });
+                }
+            }
+            None => added.push(dep.clone()),
+        }
+    }
+
+    // Then treat what's left with the same name and target as a change of kind
+    added.retain(|dep| {
+        let moved = removed
+            .iter()
+            .position(|candidate| candidate.name == dep.name && candidate.target == dep.target);
+
+        match moved {
+            Some(index) => {
+                changed.push(Change {
+                    old: removed.remove(index),
+                    new: dep.clone(),
+                });
+                false
+            }
+            None => true,
+        }
+    });
+
+    changed.sort_by(|a, b| a.new.cmp(&b.new));
+
+    (added, removed, changed)
+}
@@ -0,0 +1,143 @@
+//! Documentation build status from [docs.rs](https://docs.rs)
+//!
+//! docs.rs builds documentation for every release published to crates.io. A release whose
+//! documentation failed to build has no docs.rs page, which is worth knowing before depending on
+//! it
+
+use crate::{error::Error, validate_crate_name, Package, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// The docs.rs URL
+pub const DOCS_RS_URL: &str = "https://docs.rs";
+
+/// How many releases to check when looking for the latest one with documentation
+const MAX_CHECKED: usize = 5;
+
+/// A client for docs.rs, or a compatible documentation host
+#[derive(Debug, Clone)]
+pub struct Client {
+    url: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(DOCS_RS_URL)
+    }
+}
+
+impl Client {
+    /// Create a client for a documentation host at a custom URL
+    pub fn new<T>(url: T) -> Self
+    where
+        String: From<T>,
+    {
+        let url = String::from(url).trim_end_matches('/').to_owned();
+
+        Self { url }
+    }
+
+    /// Fetch the documentation build status of a release
+    ///
+    /// Releases docs.rs doesn't know about, e.g. ones that haven't been built yet, are reported as
+    /// not built
+    pub fn build_status(&self, name: &str, version: &Version) -> Result<BuildStatus> {
+        validate_crate_name(name).map_err(Error::InvalidName)?;
+
+        match crate::get(&format!("{}/crate/{name}/{version}/status.json", self.url)) {
+            Ok(content) => BuildStatus::from_json(content),
+            Err(error) if error.is_not_found() => Ok(BuildStatus {
+                version: version.clone(),
+                built: false,
+            }),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Fetch the build status of a package's latest stable release, and find the latest release
+    /// that has documentation
+    ///
+    /// Yanked releases are skipped. Only the few most recent releases are checked, so
+    /// [`DocsStatus::latest_documented`] may be `None` even if an older release has documentation
+    pub fn docs_status(&self, package: &Package) -> Result<Option<DocsStatus>> {
+        let mut candidates = package
+            .releases()
+            .iter()
+            .rev()
+            .filter(|release| !release.yanked && release.vers.pre.is_empty())
+            .take(MAX_CHECKED);
+
+        let Some(latest) = candidates.next() else {
+            return Ok(None);
+        };
+        let latest = self.build_status(package.name(), &latest.vers)?;
+
+        let mut latest_documented = latest.built.then(|| latest.version.clone());
+        for release in candidates {
+            if latest_documented.is_some() {
+                break;
+            }
+
+            let status = self.build_status(package.name(), &release.vers)?;
+            latest_documented = status.built.then_some(status.version);
+        }
+
+        Ok(Some(DocsStatus {
+            latest,
+            latest_documented,
+        }))
+    }
+}
+
+/// Whether documentation was built for a release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildStatus {
+    /// The version of the release
+    pub version: Version,
+    /// Whether documentation was built successfully
+    #[serde(alias = "doc_status")]
+    pub built: bool,
+}
+
+impl BuildStatus {
+    /// Parse a build status from the json response of docs.rs's `status.json` endpoint
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)
+    }
+}
+
+/// The documentation status of a package, see [`Client::docs_status`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocsStatus {
+    /// The build status of the latest stable release
+    pub latest: BuildStatus,
+    /// The latest release with documentation, if one was found
+    pub latest_documented: Option<Version>,
+}
+
+impl std::fmt::Display for DocsStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let BuildStatus { version, built } = &self.latest;
+
+        match (built, &self.latest_documented) {
+            (true, _) => write!(f, "built for {version}"),
+            (false, Some(documented)) => write!(
+                f,
+                "not built for {version}, latest documented is {documented}"
+            ),
+            (false, None) => write!(f, "not built for {version}"),
+        }
+    }
+}
+
+impl Package {
+    /// Fetch the documentation status of this package from docs.rs
+    ///
+    /// See [`Client::docs_status`]
+    pub fn docs_status(&self) -> Result<Option<DocsStatus>> {
+        Client::default().docs_status(self)
+    }
+}
@@ -0,0 +1,118 @@
+//! Where `.crate` files are downloaded from, how big they are, and downloading them
+//!
+//! Registries describe their download URLs in the `dl` field of their index's `config.json`.
+//!
+//! More info can be found in [The Cargo Book](https://doc.rust-lang.org/cargo/reference/registry-index.html#index-configuration)
+
+use crate::{error::Error, index_prefix, Release, Result, CRATES_IO_INDEX_URL};
+use serde::{Deserialize, Serialize};
+
+/// Where crates.io serves `.crate` files from
+pub const CRATES_IO_DL_URL: &str = "https://static.crates.io/crates";
+
+/// Markers that may appear in a registry's download URL
+const MARKERS: &[&str] = &[
+    "{crate}",
+    "{version}",
+    "{prefix}",
+    "{lowerprefix}",
+    "{sha256-checksum}",
+];
+
+/// The configuration of a registry, from it's index's `config.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryConfig {
+    /// The URL `.crate` files are downloaded from, which may contain markers like `{crate}`
+    pub dl: String,
+    /// The URL of the registry's web API, if it has one
+    #[serde(default)]
+    pub api: Option<String>,
+}
+
+impl RegistryConfig {
+    /// The configuration of crates.io
+    pub fn crates_io() -> Self {
+        Self {
+            dl: CRATES_IO_DL_URL.to_owned(),
+            api: Some("https://crates.io".to_owned()),
+        }
+    }
+
+    /// Fetch the configuration of the registry with this index
+    ///
+    /// The crates.io configuration is known, so it isn't fetched
+    pub fn fetch(index_url: &str) -> Result<Self> {
+        let index_url = index_url
+            .trim_start_matches("sparse+")
+            .trim_end_matches('/');
+
+        if index_url == CRATES_IO_INDEX_URL {
+            return Ok(Self::crates_io());
+        }
+
+        Self::from_json(crate::get(&format!("{index_url}/config.json"))?)
+    }
+
+    /// Parse the contents of an index's `config.json`
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)
+    }
+
+    /// Return the URL a release's `.crate` file is downloaded from
+    ///
+    /// If `dl` has no markers, `/{crate}/{version}/download` is appended to it, as cargo does
+    pub fn download_url(&self, release: &Release) -> String {
+        let dl = self.dl.trim_end_matches('/');
+        let Release {
+            name, vers, cksum, ..
+        } = release;
+
+        if !MARKERS.iter().any(|marker| dl.contains(marker)) {
+            return format!("{dl}/{name}/{vers}/download");
+        }
+
+        let prefix = index_prefix(name);
+
+        dl.replace("{crate}", name)
+            .replace("{version}", &vers.to_string())
+            .replace("{lowerprefix}", &prefix.to_ascii_lowercase())
+            .replace("{prefix}", &prefix)
+            .replace("{sha256-checksum}", cksum)
+    }
+
+    /// Find the size in bytes of a release's `.crate` file, without downloading it
+    ///
+    /// Returns `None` if the registry doesn't report the size
+    pub fn crate_size(&self, release: &Release) -> Result<Option<u64>> {
+        crate::content_length(&self.download_url(release))
+    }
+
+    /// Download a release's `.crate` file, checking it against the checksum in the index
+    ///
+    /// Fails with [`Error::ChecksumMismatch`] if the file doesn't match [`Release::cksum`]
+    pub fn download(&self, release: &Release) -> Result<Vec<u8>> {
+        let bytes = crate::get_bytes(&self.download_url(release))?;
+        release.verify_checksum(&bytes)?;
+
+        Ok(bytes)
+    }
+}
+
+impl Release {
+    /// Find the size in bytes of this release's `.crate` file on crates.io, without downloading it
+    ///
+    /// See [`RegistryConfig::crate_size`] to use another registry
+    pub fn crate_size(&self) -> Result<Option<u64>> {
+        RegistryConfig::crates_io().crate_size(self)
+    }
+
+    /// Download this release's `.crate` file from crates.io, checking it against it's checksum
+    ///
+    /// See [`RegistryConfig::download`] to use another registry
+    pub fn download(&self) -> Result<Vec<u8>> {
+        RegistryConfig::crates_io().download(self)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{cli::Options, jobs};
+use cargo_lookup::{
+    advisories::Advisory,
+    api::{self, VersionInfo},
+    download::RegistryConfig,
+    extensions::{Extensions, Lifecycle},
+    normalize_name, Package, Release, CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Data gathered from sources other than the index, used to annotate resolved releases
+///
+/// Enrichment is best-effort: a source that fails is reported as unavailable, and results are
+/// still produced from the index data alone
+#[derive(Debug, Default)]
+pub struct Enrichment {
+    extensions: Option<Extensions>,
+    api: Option<ApiData>,
+    registry_config: Option<RegistryConfig>,
+    unavailable: Vec<&'static str>,
+}
+
+/// The details of each version of a package from the registry's web API
+type Versions = Arc<BTreeMap<Version, VersionInfo>>;
+
+/// Publish dates and licenses from the registry's web API
+#[derive(Debug)]
+struct ApiData {
+    client: api::Client,
+    /// The fields requested on the command line, `published` and `license`
+    sources: Vec<&'static str>,
+    /// The version details of each package, or `None` if they couldn't be fetched. The lock is
+    /// held while fetching, so requests are sent one at a time as crates.io asks
+    versions: Mutex<HashMap<String, Option<Versions>>>,
+    warned: AtomicBool,
+}
+
+impl ApiData {
+    /// Return the version details of a package, fetching them the first time they're needed
+    fn versions(&self, name: &str) -> Option<Versions> {
+        let mut fetched = self.versions.lock().expect("versions lock");
+
+        fetched
+            .entry(normalize_name(name))
+            .or_insert_with(|| match self.client.versions(name) {
+                Ok(versions) => Some(Arc::new(versions)),
+                Err(error) => {
+                    if !self.warned.swap(true, Ordering::Relaxed) {
+                        eprintln!(
+                            "warning: {} unavailable for some crates, continuing without them: {error}",
+                            self.sources.join(" and ")
+                        );
+                    }
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+impl Enrichment {
+    /// Load every enrichment source requested on the command line
+    pub fn load(options: &Options) -> Self {
+        let mut enrichment = Self::default();
+
+        if let Some(url) = options.extensions.as_deref() {
+            enrichment.extensions = enrichment.record("extensions", Extensions::fetch(url));
+        }
+
+        if options.wants_sizes() {
+            let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+            enrichment.registry_config =
+                enrichment.record("crate_size", RegistryConfig::fetch(index_url));
+        }
+
+        let sources = [
+            (options.wants_dates(), "published"),
+            (options.wants_licenses(), "license"),
+        ]
+        .into_iter()
+        .filter_map(|(wanted, source)| wanted.then_some(source))
+        .collect::<Vec<&'static str>>();
+        if !sources.is_empty() {
+            match crate::api_client(options) {
+                Ok(client) => {
+                    enrichment.api = Some(ApiData {
+                        client,
+                        sources,
+                        versions: Mutex::default(),
+                        warned: AtomicBool::new(false),
+                    })
+                }
+                Err(error) => {
+                    eprintln!(
+                        "warning: {} unavailable, continuing without them: {error}",
+                        sources.join(" and ")
+                    );
+                    enrichment.unavailable.extend(sources);
+                }
+            }
+        }
+
+        enrichment
+    }
+
+    /// The registry's `config.json`, if crate sizes were requested and it could be fetched
+    pub fn registry_config(&self) -> Option<&RegistryConfig> {
+        self.registry_config.as_ref()
+    }
+
+    /// Set the publish dates and licenses requested on the command line on every release of a
+    /// package, leaving them unset if the API can't be reached
+    pub fn package(&self, package: Arc<Package>) -> Arc<Package> {
+        let Some(api) = &self.api else {
+            return package;
+        };
+
+        match api.versions(package.name()) {
+            Some(versions) => Arc::new((*package).clone().with_version_info(&versions)),
+            None => package,
+        }
+    }
+
+    /// Keep the result of an enrichment source, or mark it as unavailable if it failed
+    fn record<T, E>(&mut self, source: &'static str, result: Result<T, E>) -> Option<T>
+    where
+        E: Display,
+    {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                eprintln!("warning: {source} unavailable, continuing without it: {error}");
+                self.unavailable.push(source);
+                None
+            }
+        }
+    }
+
+    /// Annotate a release with all of the available enrichment data
+    pub fn entry(&self, mut release: Release) -> Entry {
+        let lifecycle = self
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.lifecycle(&release));
+
+        let mut unavailable = self.unavailable.clone();
+        if let Some(api) = &self.api {
+            match api.versions(&release.name) {
+                Some(versions) => {
+                    let info = versions.get(&release.vers);
+                    release.published = info.map(|info| info.published.clone());
+                    release.license = info.and_then(|info| info.license.clone());
+                }
+                None => unavailable.extend(&api.sources),
+            }
+        }
+
+        Entry {
+            release,
+            lifecycle,
+            advisories: Vec::new(),
+            crate_size: None,
+            unavailable,
+        }
+    }
+
+    /// Find the size of each entry's `.crate` file, making up to `jobs` requests at once
+    pub fn check_sizes(&self, entries: &mut [Entry], jobs: usize) {
+        let Some(config) = &self.registry_config else {
+            return;
+        };
+
+        let results = jobs::parallel_map(entries, jobs, |entry| config.crate_size(&entry.release));
+
+        let mut failed = false;
+        for (entry, result) in entries.iter_mut().zip(results) {
+            match result {
+                Ok(size) => entry.crate_size = size,
+                Err(error) => {
+                    if !failed {
+                        eprintln!(
+                            "warning: crate sizes unavailable, continuing without them: {error}"
+                        );
+                        failed = true;
+                    }
+                    entry.unavailable.push("crate_size");
+                }
+            }
+        }
+    }
+
+    /// Fetch the advisories affecting each entry's release, making up to `jobs` requests at once
+    pub fn check_advisories(entries: &mut [Entry], jobs: usize) {
+        let results = jobs::parallel_map(entries, jobs, |entry| entry.release.advisories());
+
+        let mut failed = false;
+        for (entry, result) in entries.iter_mut().zip(results) {
+            match result {
+                Ok(advisories) => entry.advisories = advisories,
+                Err(error) => {
+                    if !failed {
+                        eprintln!(
+                            "warning: advisories unavailable, continuing without them: {error}"
+                        );
+                        failed = true;
+                    }
+                    entry.unavailable.push("advisories");
+                }
+            }
+        }
+    }
+}
+
+/// A resolved release along with any extra data gathered for it
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub release: Release,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<Lifecycle>,
+    /// Advisories affecting the release, if they were checked
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub advisories: Vec<Advisory>,
+    /// The size of the release's `.crate` file in bytes, if it was checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crate_size: Option<u64>,
+    /// Enrichment sources that were requested but could not be reached
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unavailable: Vec<&'static str>,
+}
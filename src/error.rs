@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 #[derive(Debug)]
 pub enum Error {
     InvalidVersion(semver::Error),
@@ -6,10 +8,26 @@ pub enum Error {
     Serialize(serde_json::Error),
     Deserialize(serde_json::Error),
     FromIndexFile(&'static str),
+    CrossCheck(String),
+    InvalidTarget(String),
+    UnknownFeature(String),
+    ChecksumMismatch { expected: String, actual: String },
+    InvalidQuery(String),
+    InvalidName(NameError),
+    NoApi(String),
+    Resolve(String),
+    Cycle(Vec<String>),
 }
 
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Whether this error is the registry reporting that an index file doesn't exist
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::Request(error) if matches!(**error, ureq::Error::Status(404, _)))
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -19,6 +37,58 @@ impl std::fmt::Display for Error {
             Error::Serialize(error) => write!(f, "failed to serialize: {error}"),
             Error::Deserialize(error) => write!(f, "failed to deserialize: {error}"),
             Error::FromIndexFile(error) => write!(f, "failed to populate from index file: {error}"),
+            Error::CrossCheck(error) => write!(f, "index cross-check failed: {error}"),
+            Error::InvalidTarget(target) => write!(f, "invalid target: `{target}`"),
+            Error::UnknownFeature(feature) => write!(f, "unknown feature: `{feature}`"),
+            Error::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "checksum mismatch: expected `{expected}`, found `{actual}`"
+                )
+            }
+            Error::InvalidQuery(param) => write!(f, "invalid query parameter: `{param}`"),
+            Error::InvalidName(error) => write!(f, "invalid crate name: {error}"),
+            Error::NoApi(index) => write!(f, "registry at `{index}` doesn't have a web API"),
+            Error::Resolve(error) => write!(f, "failed to resolve dependencies: {error}"),
+            Error::Cycle(releases) => {
+                write!(f, "dependency cycle: {}", releases.join(" -> "))
+            }
+        }
+    }
+}
+
+/// The reason a name isn't a valid crate name, see [`crate::validate_crate_name`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameError {
+    /// The name is empty
+    Empty,
+    /// The name is longer than [`crate::MAX_NAME_LENGTH`], holding it's length
+    TooLong(usize),
+    /// The name starts with something other than an ASCII letter
+    InvalidStart(char),
+    /// The name contains a character other than an ASCII letter, digit, `-`, or `_`
+    InvalidChar(char),
+}
+
+impl std::error::Error for NameError {}
+
+impl std::fmt::Display for NameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "name is empty"),
+            NameError::TooLong(len) => write!(
+                f,
+                "name is {len} characters long, the maximum is {}",
+                crate::MAX_NAME_LENGTH
+            ),
+            NameError::InvalidStart(char) => {
+                write!(f, "name must start with a letter, not `{char}`")
+            }
+            NameError::InvalidChar(char) => write!(
+                f,
+                "invalid character `{char}`, only letters, numbers, `-`, and `_` are allowed"
+            ),
         }
     }
 }
@@ -0,0 +1,122 @@
+//! Lifecycle metadata for packages, published separately from the registry index
+//!
+//! Registries, or users maintaining their own sidecar file, can publish an extensions JSON file
+//! annotating crates with deprecation notices, end-of-life dates, and support tiers per version:
+//!
+//! ```json
+//! {
+//!   "crates": {
+//!     "internal-http": {
+//!       "deprecated": "use `internal-client` instead",
+//!       "versions": [
+//!         { "req": "<2.0.0", "eol": "2024-06-30", "tier": "unsupported" },
+//!         { "req": "^2", "tier": "supported" }
+//!       ]
+//!     }
+//!   }
+//! }
+//! ```
+
+use crate::{error::Error, Release, Result};
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Lifecycle annotations for a set of crates, keyed by crate name
+///
+/// Crate names are matched the same way the registry matches them, see [`crate::normalize_name`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Extensions {
+    #[serde(default)]
+    pub crates: BTreeMap<String, CrateExtension>,
+}
+
+/// Lifecycle annotations for a single crate
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrateExtension {
+    /// A deprecation notice that applies to every version of the crate
+    #[serde(default)]
+    pub deprecated: Option<String>,
+    /// Annotations for ranges of versions. The first entry matching a release is used
+    #[serde(default)]
+    pub versions: Vec<VersionLifecycle>,
+}
+
+/// Lifecycle annotations for the versions of a crate matching a requirement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionLifecycle {
+    /// The versions these annotations apply to
+    pub req: VersionReq,
+    /// The end-of-life date for these versions
+    #[serde(default)]
+    pub eol: Option<String>,
+    /// The support tier for these versions, e.g. `supported` or `security-only`
+    #[serde(default)]
+    pub tier: Option<String>,
+}
+
+/// The merged lifecycle annotations for a specific release
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lifecycle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+}
+
+impl Extensions {
+    /// Fetch and parse an extensions file from a URL
+    pub fn fetch(url: &str) -> Result<Self> {
+        Self::from_json(crate::get(url)?)
+    }
+
+    /// Parse an extensions file from it's json representation
+    pub fn from_json<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        serde_json::from_str(content.as_ref()).map_err(Error::Deserialize)
+    }
+
+    /// Return the lifecycle annotations that apply to a release, if there are any
+    pub fn lifecycle(&self, release: &Release) -> Option<Lifecycle> {
+        let name = crate::normalize_name(&release.name);
+        let extension = self
+            .crates
+            .iter()
+            .find(|(crate_name, _)| crate::normalize_name(crate_name) == name)
+            .map(|(_, extension)| extension)?;
+        let version = extension
+            .versions
+            .iter()
+            .find(|version| version.req.matches(&release.vers));
+
+        let lifecycle = Lifecycle {
+            deprecated: extension.deprecated.clone(),
+            eol: version.and_then(|version| version.eol.clone()),
+            tier: version.and_then(|version| version.tier.clone()),
+        };
+
+        (lifecycle != Lifecycle::default()).then_some(lifecycle)
+    }
+}
+
+impl std::fmt::Display for Lifecycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut notes = Vec::new();
+
+        if let Some(deprecated) = &self.deprecated {
+            notes.push(format!("deprecated: {deprecated}"));
+        }
+        if let Some(eol) = &self.eol {
+            notes.push(format!("end of life {eol}"));
+        }
+        if let Some(tier) = &self.tier {
+            notes.push(format!("support tier {tier}"));
+        }
+
+        write!(f, "{}", notes.join(", "))
+    }
+}
@@ -0,0 +1,407 @@
+//! Feature resolution for releases
+//!
+//! Features can enable other features, optional dependencies (`dep:name`, or implicitly by
+//! naming the dependency), features of dependencies (`name/feature`), and features of
+//! dependencies only if they are enabled by something else (`name?/feature`).
+//!
+//! More info on the syntax can be found in [The Cargo Book](https://doc.rust-lang.org/cargo/reference/features.html)
+
+use crate::{error::Error, Dependency, Release, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A single value in a feature's list of things it enables
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeatureValue {
+    /// Another feature of the same release, e.g. `std`
+    Feature(String),
+    /// An optional dependency, e.g. `dep:serde`
+    Dep(String),
+    /// A feature of a dependency, e.g. `serde/derive`, or `serde?/derive` if `weak`
+    DepFeature {
+        dep: String,
+        feature: String,
+        weak: bool,
+    },
+}
+
+impl FeatureValue {
+    /// Parse a value as it is written in a release's features
+    pub fn parse(value: &str) -> Self {
+        if let Some(dep) = value.strip_prefix("dep:") {
+            return FeatureValue::Dep(dep.to_owned());
+        }
+
+        match value.split_once('/') {
+            Some((dep, feature)) => {
+                let (dep, weak) = match dep.strip_suffix('?') {
+                    Some(dep) => (dep, true),
+                    None => (dep, false),
+                };
+
+                FeatureValue::DepFeature {
+                    dep: dep.to_owned(),
+                    feature: feature.to_owned(),
+                    weak,
+                }
+            }
+            None => FeatureValue::Feature(value.to_owned()),
+        }
+    }
+}
+
+/// A graph of which features enable which, for answering questions about a release's features
+///
+/// Built by [`Release::feature_graph`] from the release's [normalized](Release::normalized)
+/// features, so implicit optional-dependency features are included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureGraph {
+    values: BTreeMap<String, Vec<FeatureValue>>,
+    optional_deps: BTreeSet<String>,
+    edges: BTreeMap<String, BTreeSet<String>>,
+    reverse: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl FeatureGraph {
+    /// Return an iterator over the names of every feature in the graph
+    pub fn features(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Return the values a feature enables directly, or `None` if there is no such feature
+    pub fn values(&self, feature: &str) -> Option<&[FeatureValue]> {
+        self.values.get(feature).map(Vec::as_slice)
+    }
+
+    /// Return an iterator over every feature transitively enabled by a feature
+    ///
+    /// The feature itself is only included if it's part of a cycle
+    pub fn enabled_by<'a>(&'a self, feature: &'a str) -> Walk<'a> {
+        Walk::new(&self.edges, feature)
+    }
+
+    /// Return an iterator over every feature that transitively enables a feature
+    ///
+    /// For example, this answers which features end up enabling `std`
+    pub fn enablers_of<'a>(&'a self, feature: &'a str) -> Walk<'a> {
+        Walk::new(&self.reverse, feature)
+    }
+
+    /// Return every feature that transitively enables an optional dependency
+    pub fn enabling_dep(&self, dep: &str) -> BTreeSet<&str> {
+        let direct = self
+            .values
+            .iter()
+            .filter(|(_, values)| {
+                values.iter().any(|value| match value {
+                    FeatureValue::Dep(name) => name == dep,
+                    FeatureValue::DepFeature {
+                        dep: name, weak, ..
+                    } => name == dep && !weak && self.optional_deps.contains(dep),
+                    FeatureValue::Feature(_) => false,
+                })
+            })
+            .map(|(feature, _)| feature.as_str())
+            .collect::<BTreeSet<&str>>();
+
+        let mut enabling = direct.clone();
+        for feature in direct {
+            enabling.extend(self.enablers_of(feature));
+        }
+
+        enabling
+    }
+
+    /// Return every group of features that enable each other in a cycle
+    ///
+    /// Cargo allows feature cycles, but they are usually a mistake
+    pub fn cycles(&self) -> Vec<BTreeSet<&str>> {
+        let mut cycles: Vec<BTreeSet<&str>> = Vec::new();
+
+        for feature in self.features() {
+            if cycles.iter().any(|cycle| cycle.contains(feature)) {
+                continue;
+            }
+
+            let reachable = self.enabled_by(feature).collect::<BTreeSet<&str>>();
+            if !reachable.contains(feature) {
+                continue;
+            }
+
+            let cycle = reachable
+                .into_iter()
+                .filter(|other| self.enabled_by(other).any(|back| back == feature))
+                .collect();
+            cycles.push(cycle);
+        }
+
+        cycles
+    }
+}
+
+/// A depth-first traversal over features in a [`FeatureGraph`], visiting each feature once
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    edges: &'a BTreeMap<String, BTreeSet<String>>,
+    stack: Vec<&'a str>,
+    seen: BTreeSet<&'a str>,
+}
+
+impl<'a> Walk<'a> {
+    fn new(edges: &'a BTreeMap<String, BTreeSet<String>>, start: &'a str) -> Self {
+        let mut walk = Self {
+            edges,
+            stack: Vec::new(),
+            seen: BTreeSet::new(),
+        };
+        walk.push_next(start);
+        walk
+    }
+
+    fn push_next(&mut self, feature: &str) {
+        for next in self.edges.get(feature).into_iter().flatten().rev() {
+            if !self.seen.contains(next.as_str()) {
+                self.stack.push(next);
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(feature) = self.stack.pop() {
+            if self.seen.insert(feature) {
+                self.push_next(feature);
+                return Some(feature);
+            }
+        }
+
+        None
+    }
+}
+
+/// The features and optional dependencies enabled by a feature selection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedFeatures {
+    /// Every feature of the release that is enabled, including implicit optional-dependency features
+    pub features: BTreeSet<String>,
+    /// Optional dependencies that are enabled, by the name used in the release's manifest
+    pub optional_deps: BTreeSet<String>,
+    /// Features enabled on dependencies by this release's features, by dependency name
+    pub dep_features: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ResolvedFeatures {
+    /// Whether a dependency is enabled. Non-optional dependencies are always enabled
+    pub fn enables(&self, dep: &Dependency) -> bool {
+        !dep.optional || self.optional_deps.contains(&dep.name)
+    }
+
+    /// Return the features enabled on a dependency, both from it's declaration and from features
+    pub fn features_for(&self, dep: &Dependency) -> BTreeSet<String> {
+        let mut features = dep.features.iter().cloned().collect::<BTreeSet<String>>();
+        features.extend(
+            self.dep_features
+                .get(&dep.name)
+                .into_iter()
+                .flatten()
+                .cloned(),
+        );
+        features
+    }
+}
+
+/// A dependency enabled by a feature selection, along with the features enabled on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActivatedDependency<'a> {
+    /// The dependency as declared by the release
+    pub dependency: &'a Dependency,
+    /// Every feature enabled on the dependency
+    ///
+    /// This doesn't include the dependency's default features, see [`Dependency::default_features`]
+    pub features: BTreeSet<String>,
+}
+
+impl Release {
+    /// Build a graph of this release's features, see [`FeatureGraph`]
+    pub fn feature_graph(&self) -> FeatureGraph {
+        let normalized = self.normalized();
+        let mut graph = FeatureGraph {
+            optional_deps: self
+                .deps
+                .iter()
+                .filter(|dep| dep.optional)
+                .map(|dep| dep.name.clone())
+                .collect(),
+            ..FeatureGraph::default()
+        };
+
+        for (feature, values) in normalized.features {
+            let values = values
+                .iter()
+                .map(|value| FeatureValue::parse(value))
+                .collect::<Vec<FeatureValue>>();
+
+            graph.values.insert(feature, values);
+        }
+
+        for (feature, values) in &graph.values {
+            for value in values {
+                // `name/feature` also enables a feature with the same name as the dependency
+                let enabled = match value {
+                    FeatureValue::Feature(name) => name,
+                    FeatureValue::DepFeature {
+                        dep, weak: false, ..
+                    } if graph.values.contains_key(dep) => dep,
+                    _ => continue,
+                };
+
+                graph
+                    .edges
+                    .entry(feature.clone())
+                    .or_default()
+                    .insert(enabled.clone());
+                graph
+                    .reverse
+                    .entry(enabled.clone())
+                    .or_default()
+                    .insert(feature.clone());
+            }
+        }
+
+        graph
+    }
+
+    /// Return every dependency enabled by a feature selection, with the features enabled on each
+    ///
+    /// Non-optional dependencies are always included, while optional dependencies are only
+    /// included if the selection activates them. See [`Release::resolve_features`]
+    pub fn activated_deps(
+        &self,
+        selected: &[&str],
+        default: bool,
+    ) -> Result<Vec<ActivatedDependency<'_>>> {
+        let resolved = self.resolve_features(selected, default)?;
+
+        Ok(self
+            .deps
+            .iter()
+            .filter(|dep| resolved.enables(dep))
+            .map(|dependency| ActivatedDependency {
+                dependency,
+                features: resolved.features_for(dependency),
+            })
+            .collect())
+    }
+
+    /// Resolve what the `default` feature enables, fully expanded
+    ///
+    /// This includes the implicit features of optional dependencies, which older index entries
+    /// rely on to enable dependencies by name. Useful for deciding whether depending on a release
+    /// with `default-features = false` is worth it
+    pub fn default_features(&self) -> ResolvedFeatures {
+        // Nothing is selected explicitly, so there's no unknown feature to fail on
+        self.resolve_features(&[], true).unwrap_or_default()
+    }
+
+    /// Resolve a feature selection into every feature and optional dependency it enables
+    ///
+    /// `selected` are the features requested explicitly, and `default` is whether the `default`
+    /// feature is enabled too. Enabled features are expanded transitively, including `dep:`
+    /// activations and weak `name?/feature` enables. Fails with [`Error::UnknownFeature`] if a
+    /// selected feature does not exist
+    pub fn resolve_features(&self, selected: &[&str], default: bool) -> Result<ResolvedFeatures> {
+        let features = self.all_features();
+
+        for feature in selected {
+            if !features.contains_key(*feature) && !self.has_implicit_feature(feature) {
+                return Err(Error::UnknownFeature(feature.to_string()));
+            }
+        }
+
+        let mut resolved = ResolvedFeatures::default();
+        let mut weak = Vec::new();
+        let mut queue = selected
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+
+        if default && features.contains_key("default") {
+            queue.push("default".to_owned());
+        }
+
+        while let Some(value) = queue.pop() {
+            if let Some(dep) = value.strip_prefix("dep:") {
+                if self.is_optional_dep(dep) {
+                    resolved.optional_deps.insert(dep.to_owned());
+                }
+            } else if let Some((dep, feature)) = value.split_once('/') {
+                if let Some(dep) = dep.strip_suffix('?') {
+                    weak.push((dep.to_owned(), feature.to_owned()));
+                    continue;
+                }
+
+                if features.contains_key(dep) || self.has_implicit_feature(dep) {
+                    queue.push(dep.to_owned());
+                } else if self.is_optional_dep(dep) {
+                    resolved.optional_deps.insert(dep.to_owned());
+                }
+
+                resolved
+                    .dep_features
+                    .entry(dep.to_owned())
+                    .or_default()
+                    .insert(feature.to_owned());
+            } else if let Some(enables) = features.get(&value) {
+                if resolved.features.insert(value.clone()) {
+                    queue.extend(enables.iter().cloned());
+                }
+            } else if self.has_implicit_feature(&value) {
+                resolved.features.insert(value.clone());
+                resolved.optional_deps.insert(value);
+            }
+        }
+
+        // Weak dependency features only apply if the dependency ended up enabled some other way
+        for (dep, feature) in weak {
+            let enabled = resolved.optional_deps.contains(&dep)
+                || self
+                    .deps
+                    .iter()
+                    .any(|candidate| candidate.name == dep && !candidate.optional);
+
+            if enabled {
+                resolved
+                    .dep_features
+                    .entry(dep)
+                    .or_default()
+                    .insert(feature);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Whether a dependency with the given name is optional
+    fn is_optional_dep(&self, name: &str) -> bool {
+        self.deps.iter().any(|dep| dep.optional && dep.name == name)
+    }
+
+    /// Whether an optional dependency gets an implicit feature of the same name
+    ///
+    /// Optional dependencies have an implicit feature unless some feature refers to them with
+    /// the `dep:` syntax, or an explicit feature has the same name
+    pub(crate) fn has_implicit_feature(&self, name: &str) -> bool {
+        let dep_syntax = format!("dep:{name}");
+        let features = self.all_features();
+
+        self.is_optional_dep(name)
+            && !features.contains_key(name)
+            && !features
+                .values()
+                .flatten()
+                .any(|value| *value == dep_syntax)
+    }
+}
@@ -0,0 +1,738 @@
+//! Dependency graphs of resolved releases
+//!
+//! A [`DependencyGraph`] has a node for every release in a resolution, and an edge for every
+//! dependency between them, keeping the [`Dependency`] the edge came from so it's kind,
+//! requirement, optionality, and target are on hand. See [`Resolution::graph`](crate::resolve::Resolution::graph)
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{resolve::resolve, Query, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let resolution = resolve(&["tokio@^1".parse::<Query>()?])?;
+//!     let graph = resolution.graph();
+//!
+//!     for root in graph.roots() {
+//!         for edge in graph.dependencies(*root) {
+//!             let release = graph.release(edge.to);
+//!             println!("{} {} ({})", release.name, release.vers, edge.dependency.req);
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Graphs can be rendered with [Graphviz](https://graphviz.org) using [`DependencyGraph::to_dot`],
+//! as [Mermaid](https://mermaid.js.org) diagrams using [`DependencyGraph::to_mermaid`], as
+//! GraphML using [`DependencyGraph::to_graphml`], or as a tree like `cargo tree` prints using
+//! [`DependencyGraph::to_tree`]. A graph can also be written as a `Cargo.lock` file, see
+//! [`DependencyGraph::to_lockfile_toml`]
+
+use crate::{
+    error::Error, lockfile::Lockfile, normalize_name, Dependency, DependencyKind, Release, Result,
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Attributes of a node in DOT output, e.g. `color` set to `red`
+pub type DotAttributes = BTreeMap<String, String>;
+
+/// The characters branches are drawn with by [`DependencyGraph::to_tree`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// Box-drawing characters, like `├──`
+    #[default]
+    Utf8,
+    /// Plain ASCII, like `|--`
+    Ascii,
+}
+
+impl Charset {
+    /// Return the branch to a child that has siblings after it, the branch to the last child,
+    /// and the indent below each of those
+    fn branches(self) -> [&'static str; 4] {
+        match self {
+            Charset::Utf8 => ["├── ", "└── ", "│   ", "    "],
+            Charset::Ascii => ["|-- ", "`-- ", "|   ", "    "],
+        }
+    }
+}
+
+/// Identifies a node of a [`DependencyGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Return the position of the node in [`DependencyGraph::releases`]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A dependency of one release on another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Edge {
+    /// The dependent release
+    pub from: NodeId,
+    /// The release the dependency resolved to
+    pub to: NodeId,
+    /// The dependency as declared by the dependent
+    pub dependency: Dependency,
+}
+
+/// A graph of releases and the dependencies between them, see the [module docs](self)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    #[serde(rename = "nodes")]
+    releases: Vec<Release>,
+    edges: Vec<Edge>,
+    roots: Vec<NodeId>,
+}
+
+impl DependencyGraph {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a release to the graph, returning it's node
+    ///
+    /// Releases are only added once, so adding a release that's already in the graph returns
+    /// the existing node
+    pub fn add_release(&mut self, release: Release) -> NodeId {
+        if let Some(id) = self.find(&release.name, &release.vers) {
+            return id;
+        }
+
+        self.releases.push(release);
+        NodeId(self.releases.len() - 1)
+    }
+
+    /// Mark a node as one of the roots the graph was resolved from
+    pub fn add_root(&mut self, id: NodeId) {
+        if !self.roots.contains(&id) {
+            self.roots.push(id);
+        }
+    }
+
+    /// Add an edge for a dependency from one node to another
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, dependency: Dependency) {
+        let edge = Edge {
+            from,
+            to,
+            dependency,
+        };
+
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    /// Add an edge for a dependency of a node to the newest release in the graph it allows,
+    /// returning that release's node
+    ///
+    /// Nothing is added if no release in the graph satisfies the dependency
+    pub fn connect(&mut self, from: NodeId, dependency: &Dependency) -> Option<NodeId> {
+        let to = self
+            .named(dependency.package_name())
+            .filter(|id| dependency.req.matches(&self.release(*id).vers))
+            .max_by(|a, b| self.release(*a).vers.cmp(&self.release(*b).vers))?;
+
+        self.add_edge(from, to, dependency.clone());
+        Some(to)
+    }
+
+    /// Add every release, edge, and root of another graph to this one
+    pub fn merge(&mut self, other: DependencyGraph) {
+        let ids = other
+            .releases
+            .into_iter()
+            .map(|release| self.add_release(release))
+            .collect::<Vec<NodeId>>();
+
+        for edge in other.edges {
+            self.add_edge(ids[edge.from.0], ids[edge.to.0], edge.dependency);
+        }
+        for root in other.roots {
+            self.add_root(ids[root.0]);
+        }
+    }
+
+    /// Return the number of releases in the graph
+    pub fn len(&self) -> usize {
+        self.releases.len()
+    }
+
+    /// Whether the graph has no releases
+    pub fn is_empty(&self) -> bool {
+        self.releases.is_empty()
+    }
+
+    /// Return the release of a node
+    ///
+    /// Panics if the node isn't from this graph
+    pub fn release(&self, id: NodeId) -> &Release {
+        &self.releases[id.0]
+    }
+
+    /// Return every release in the graph, in the order they were added
+    pub fn releases(&self) -> &[Release] {
+        &self.releases
+    }
+
+    /// Convert into every release in the graph, in the order they were added
+    pub fn into_releases(self) -> Vec<Release> {
+        self.releases
+    }
+
+    /// Return every node in the graph along with it's release
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &Release)> {
+        self.releases
+            .iter()
+            .enumerate()
+            .map(|(index, release)| (NodeId(index), release))
+    }
+
+    /// Return the nodes the graph was resolved from
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Return every edge in the graph
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Find the node of a release by name and version
+    pub fn find(&self, name: &str, version: &Version) -> Option<NodeId> {
+        self.named(name)
+            .find(|id| self.release(*id).vers == *version)
+    }
+
+    /// Return the nodes of every release of a package, which may be more than one if the graph
+    /// has semver-incompatible versions of it
+    pub fn named<'a>(&'a self, name: &str) -> impl Iterator<Item = NodeId> + 'a {
+        let name = normalize_name(name);
+
+        self.nodes()
+            .filter(move |(_, release)| normalize_name(&release.name) == name)
+            .map(|(id, _)| id)
+    }
+
+    /// Return the edges for the dependencies of a node
+    pub fn dependencies(&self, id: NodeId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+
+    /// Return the edges for the dependents of a node
+    pub fn dependents(&self, id: NodeId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.to == id)
+    }
+
+    /// Whether the graph has releases of a node's package other than the node's own, i.e. the
+    /// package is duplicated with semver-incompatible versions
+    pub fn is_duplicate(&self, id: NodeId) -> bool {
+        self.named(&self.release(id).name).nth(1).is_some()
+    }
+
+    /// Render the graph in Graphviz's DOT language, with [`default_dot_style`] for nodes
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(default_dot_style)
+    }
+
+    /// Render the graph in Graphviz's DOT language, using `style` for the attributes of each node
+    ///
+    /// Edges are dashed for dev dependencies and dotted for build dependencies, and labelled
+    /// with their version requirement
+    pub fn to_dot_with<F>(&self, style: F) -> String
+    where
+        F: Fn(&Self, NodeId) -> DotAttributes,
+    {
+        let mut dot = String::from("digraph dependencies {\n    node [shape=box];\n");
+
+        for (id, release) in self.nodes() {
+            let mut attributes = style(self, id);
+            attributes
+                .entry("label".to_owned())
+                .or_insert_with(|| format!("{} {}", release.name, release.vers));
+
+            dot.push_str(&format!(
+                "    n{} [{}];\n",
+                id.0,
+                dot_attributes(&attributes)
+            ));
+        }
+
+        for edge in &self.edges {
+            let mut attributes =
+                DotAttributes::from([("label".to_owned(), edge.dependency.req.to_string())]);
+            match edge.dependency.kind {
+                DependencyKind::Dev => {
+                    attributes.insert("style".to_owned(), "dashed".to_owned());
+                }
+                DependencyKind::Build => {
+                    attributes.insert("style".to_owned(), "dotted".to_owned());
+                }
+                _ => {}
+            }
+
+            dot.push_str(&format!(
+                "    n{} -> n{} [{}];\n",
+                edge.from.0,
+                edge.to.0,
+                dot_attributes(&attributes)
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the graph as a Mermaid flowchart, which GitHub renders in markdown
+    ///
+    /// Nodes are styled like [`default_dot_style`], with the `root`, `yanked`, and `duplicate`
+    /// classes. Edges are dotted for dev and build dependencies, and labelled with their version
+    /// requirement
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("graph TD\n");
+        let mut classes: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+        for (id, release) in self.nodes() {
+            let label = mermaid_text(&format!("{} {}", release.name, release.vers));
+            mermaid.push_str(&format!("    n{}[\"{label}\"]\n", id.0));
+
+            if self.roots.contains(&id) {
+                classes
+                    .entry("root")
+                    .or_default()
+                    .push(format!("n{}", id.0));
+            }
+            if release.yanked {
+                classes
+                    .entry("yanked")
+                    .or_default()
+                    .push(format!("n{}", id.0));
+            }
+            if self.is_duplicate(id) {
+                classes
+                    .entry("duplicate")
+                    .or_default()
+                    .push(format!("n{}", id.0));
+            }
+        }
+
+        for edge in &self.edges {
+            let arrow = match edge.dependency.kind {
+                DependencyKind::Dev | DependencyKind::Build => "-.->",
+                _ => "-->",
+            };
+            let label = mermaid_text(&edge.dependency.req.to_string());
+            mermaid.push_str(&format!(
+                "    n{} {arrow}|\"{label}\"| n{}\n",
+                edge.from.0, edge.to.0
+            ));
+        }
+
+        for (class, style) in [
+            ("root", "stroke-width:3px"),
+            ("yanked", "stroke:red,color:red"),
+            ("duplicate", "fill:lightyellow"),
+        ] {
+            if let Some(nodes) = classes.get(class) {
+                mermaid.push_str(&format!("    classDef {class} {style}\n"));
+                mermaid.push_str(&format!("    class {} {class}\n", nodes.join(",")));
+            }
+        }
+
+        mermaid
+    }
+
+    /// Render the graph as [GraphML](http://graphml.graphdrawing.org), which graph tools like
+    /// Gephi, yEd, and NetworkX can load
+    ///
+    /// Nodes have `name`, `version`, `yanked`, and `root` attributes, and edges have the `kind`,
+    /// `req`, and `optional` of their dependency
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+
+        for (key, scope, kind) in [
+            ("name", "node", "string"),
+            ("version", "node", "string"),
+            ("yanked", "node", "boolean"),
+            ("root", "node", "boolean"),
+            ("kind", "edge", "string"),
+            ("req", "edge", "string"),
+            ("optional", "edge", "boolean"),
+        ] {
+            graphml.push_str(&format!(
+                "  <key id=\"{key}\" for=\"{scope}\" attr.name=\"{key}\" attr.type=\"{kind}\"/>\n"
+            ));
+        }
+        graphml.push_str("  <graph id=\"dependencies\" edgedefault=\"directed\">\n");
+
+        for (id, release) in self.nodes() {
+            graphml.push_str(&format!("    <node id=\"n{}\">\n", id.0));
+            graphml.push_str(&graphml_data("name", &release.name));
+            graphml.push_str(&graphml_data("version", &release.vers.to_string()));
+            graphml.push_str(&graphml_data("yanked", &release.yanked.to_string()));
+            let root = self.roots.contains(&id);
+            graphml.push_str(&graphml_data("root", &root.to_string()));
+            graphml.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            graphml.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\">\n",
+                edge.from.0, edge.to.0
+            ));
+            graphml.push_str(&graphml_data("kind", edge.dependency.kind.as_str()));
+            graphml.push_str(&graphml_data("req", &edge.dependency.req.to_string()));
+            let optional = edge.dependency.optional.to_string();
+            graphml.push_str(&graphml_data("optional", &optional));
+            graphml.push_str("    </edge>\n");
+        }
+
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    /// Render the graph as a tree from each root, like `cargo tree` prints
+    ///
+    /// A release's normal dependencies are listed under it, followed by it's build and dev
+    /// dependencies under `[build-dependencies]` and `[dev-dependencies]` headings. Each release
+    /// is only expanded the first time it's listed, and is marked with `(*)` after that. Releases
+    /// deeper than `max_depth` levels below a root are left out
+    pub fn to_tree(&self, max_depth: Option<usize>, charset: Charset) -> String {
+        let mut tree = String::new();
+        let mut expanded = BTreeSet::new();
+
+        for root in &self.roots {
+            let release = self.release(*root);
+            tree.push_str(&format!("{} v{}\n", release.name, release.vers));
+            expanded.insert(*root);
+
+            let mut level = TreeLevel {
+                tree: &mut tree,
+                expanded: &mut expanded,
+                max_depth,
+                charset,
+            };
+            self.write_subtree(&mut level, *root, "", 1);
+        }
+
+        tree
+    }
+
+    /// Write the dependencies of a node below it, each line starting with `prefix`
+    fn write_subtree(&self, level: &mut TreeLevel, id: NodeId, prefix: &str, depth: usize) {
+        if level.max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return;
+        }
+
+        let [branch, last_branch, indent, last_indent] = level.charset.branches();
+        for kind in [
+            DependencyKind::Normal,
+            DependencyKind::Build,
+            DependencyKind::Dev,
+        ] {
+            let mut children = self
+                .dependencies(id)
+                .filter(|edge| edge.dependency.kind == kind)
+                .map(|edge| edge.to)
+                .collect::<Vec<NodeId>>();
+            children.sort_by(|a, b| self.release(*a).cmp(self.release(*b)));
+            children.dedup();
+
+            if children.is_empty() {
+                continue;
+            }
+            if kind != DependencyKind::Normal {
+                level
+                    .tree
+                    .push_str(&format!("{prefix}[{kind}-dependencies]\n"));
+            }
+
+            for (index, child) in children.iter().enumerate() {
+                let (branch, indent) = if index + 1 == children.len() {
+                    (last_branch, last_indent)
+                } else {
+                    (branch, indent)
+                };
+
+                let release = self.release(*child);
+                let first = level.expanded.insert(*child);
+                let marker = if !first && self.dependencies(*child).next().is_some() {
+                    " (*)"
+                } else {
+                    ""
+                };
+                level.tree.push_str(&format!(
+                    "{prefix}{branch}{} v{}{marker}\n",
+                    release.name, release.vers
+                ));
+
+                if first {
+                    self.write_subtree(level, *child, &format!("{prefix}{indent}"), depth + 1);
+                }
+            }
+        }
+    }
+
+    /// Write the graph as a version 3 `Cargo.lock` file, for releases from the index at
+    /// `index_url`. See [`Lockfile::from_graph`]
+    pub fn to_lockfile_toml(&self, index_url: &str) -> String {
+        Lockfile::from_graph(self, index_url).to_toml()
+    }
+
+    /// Return the dependency paths from the roots to the releases of a package, each ending at
+    /// the release
+    ///
+    /// This answers why a package is in the graph, like `cargo tree --invert`. Like `cargo
+    /// tree`, the dependents of a release are only followed once: a later path reaching the same
+    /// release stops there, so it starts at that release instead of a root. This keeps the
+    /// number of paths to at most the number of edges, rather than every distinct path through
+    /// the graph. Paths don't visit a node twice, so dependency cycles are cut short
+    pub fn paths_to(&self, name: &str) -> Vec<Vec<NodeId>> {
+        let mut paths = Vec::new();
+        let mut expanded = BTreeSet::new();
+
+        for target in self.named(name) {
+            self.collect_paths(&mut vec![target], &mut expanded, &mut paths);
+        }
+
+        paths
+    }
+
+    /// Extend a path, which runs from a release back towards the roots, through every dependent
+    /// of it's last node, collecting the paths that reach a root or a node already `expanded`
+    fn collect_paths(
+        &self,
+        path: &mut Vec<NodeId>,
+        expanded: &mut BTreeSet<NodeId>,
+        paths: &mut Vec<Vec<NodeId>>,
+    ) {
+        let last = path[path.len() - 1];
+        let first_visit = expanded.insert(last);
+
+        if self.roots.contains(&last) || !first_visit {
+            paths.push(path.iter().rev().copied().collect());
+        }
+        if !first_visit {
+            return;
+        }
+
+        let dependents = self
+            .dependents(last)
+            .map(|edge| edge.from)
+            .collect::<BTreeSet<NodeId>>();
+
+        for dependent in dependents {
+            if !path.contains(&dependent) {
+                path.push(dependent);
+                self.collect_paths(path, expanded, paths);
+                path.pop();
+            }
+        }
+    }
+
+    /// Return an iterator over the nodes in an order they can be built in, with every node
+    /// after the nodes it depends on
+    ///
+    /// Dev dependencies aren't needed to build a release, so they're ignored. If the remaining
+    /// nodes depend on each other in a cycle, an [`Error::Cycle`] naming the releases in it is
+    /// returned and the iteration ends
+    pub fn build_order(&self) -> BuildOrder<'_> {
+        let mut waiting = vec![BTreeSet::new(); self.releases.len()];
+        let mut dependents = vec![BTreeSet::new(); self.releases.len()];
+
+        for edge in &self.edges {
+            if edge.dependency.kind != DependencyKind::Dev {
+                waiting[edge.from.0].insert(edge.to);
+                dependents[edge.to.0].insert(edge.from);
+            }
+        }
+
+        let ready = self
+            .nodes()
+            .map(|(id, _)| id)
+            .filter(|id| waiting[id.0].is_empty())
+            .collect();
+
+        BuildOrder {
+            graph: self,
+            waiting,
+            dependents,
+            ready,
+            remaining: self.releases.len(),
+        }
+    }
+
+    /// Return an iterator over every node reachable from a node, including itself, nearest first
+    pub fn walk(&self, start: NodeId) -> Walk<'_> {
+        Walk {
+            graph: self,
+            queue: VecDeque::from([start]),
+            seen: BTreeSet::from([start]),
+        }
+    }
+}
+
+/// The default style of nodes in [`DependencyGraph::to_dot`]
+///
+/// Roots are drawn with a bold outline, yanked releases in red, and packages with more than
+/// one version in the graph are filled yellow. Useful as a starting point for custom styles with
+/// [`DependencyGraph::to_dot_with`]
+pub fn default_dot_style(graph: &DependencyGraph, id: NodeId) -> DotAttributes {
+    let mut attributes = DotAttributes::new();
+
+    if graph.roots.contains(&id) {
+        attributes.insert("penwidth".to_owned(), "2".to_owned());
+    }
+    if graph.release(id).yanked {
+        attributes.insert("color".to_owned(), "red".to_owned());
+        attributes.insert("fontcolor".to_owned(), "red".to_owned());
+    }
+    if graph.is_duplicate(id) {
+        attributes.insert("style".to_owned(), "filled".to_owned());
+        attributes.insert("fillcolor".to_owned(), "lightyellow".to_owned());
+    }
+
+    attributes
+}
+
+/// Format attributes as a DOT attribute list, quoting every value
+fn dot_attributes(attributes: &DotAttributes) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Escape text for a quoted Mermaid label
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Format a GraphML `data` element of a node or edge, escaping the value
+fn graphml_data(key: &str, value: &str) -> String {
+    let value = value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+
+    format!("      <data key=\"{key}\">{value}</data>\n")
+}
+
+/// An iterator over the nodes of a [`DependencyGraph`] in build order, see
+/// [`DependencyGraph::build_order`]
+#[derive(Debug, Clone)]
+pub struct BuildOrder<'a> {
+    graph: &'a DependencyGraph,
+    /// The dependencies of each node that haven't been built yet
+    waiting: Vec<BTreeSet<NodeId>>,
+    dependents: Vec<BTreeSet<NodeId>>,
+    ready: BTreeSet<NodeId>,
+    remaining: usize,
+}
+
+impl BuildOrder<'_> {
+    /// Find a cycle among the nodes that are still waiting, each of which waits on another
+    fn find_cycle(&self) -> Vec<NodeId> {
+        let Some(start) = self.waiting.iter().position(|waiting| !waiting.is_empty()) else {
+            return Vec::new();
+        };
+
+        let mut path = vec![NodeId(start)];
+        loop {
+            let last = path[path.len() - 1];
+            let Some(next) = self.waiting[last.0].first().copied() else {
+                return path;
+            };
+
+            if let Some(position) = path.iter().position(|id| *id == next) {
+                let mut cycle = path.split_off(position);
+                cycle.push(next);
+                return cycle;
+            }
+            path.push(next);
+        }
+    }
+}
+
+impl Iterator for BuildOrder<'_> {
+    type Item = Result<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let Some(id) = self.ready.pop_first() else {
+            let cycle = self
+                .find_cycle()
+                .into_iter()
+                .map(|id| {
+                    let release = self.graph.release(id);
+                    format!("{} {}", release.name, release.vers)
+                })
+                .collect();
+            self.remaining = 0;
+            return Some(Err(Error::Cycle(cycle)));
+        };
+
+        self.remaining -= 1;
+        for dependent in std::mem::take(&mut self.dependents[id.0]) {
+            let waiting = &mut self.waiting[dependent.0];
+            waiting.remove(&id);
+            if waiting.is_empty() {
+                self.ready.insert(dependent);
+            }
+        }
+
+        Some(Ok(id))
+    }
+}
+
+/// A breadth-first traversal over the nodes of a [`DependencyGraph`], visiting each node once
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    graph: &'a DependencyGraph,
+    queue: VecDeque<NodeId>,
+    seen: BTreeSet<NodeId>,
+}
+
+impl Iterator for Walk<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.queue.pop_front()?;
+
+        for edge in self.graph.dependencies(id) {
+            if self.seen.insert(edge.to) {
+                self.queue.push_back(edge.to);
+            }
+        }
+
+        Some(id)
+    }
+}
+
+/// The output and settings of [`DependencyGraph::to_tree`], shared by every level of the tree
+struct TreeLevel<'a> {
+    tree: &'a mut String,
+    expanded: &'a mut BTreeSet<NodeId>,
+    max_depth: Option<usize>,
+    charset: Charset,
+}
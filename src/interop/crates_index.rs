@@ -0,0 +1,63 @@
+//! Conversions to and from [`crates_index`] types
+//!
+//! Both crates read the same index format, so conversions go through it's JSON representation
+
+use crate::{error::Error, Package, Release};
+
+impl TryFrom<&crates_index::Version> for Release {
+    type Error = Error;
+
+    fn try_from(version: &crates_index::Version) -> Result<Self, Self::Error> {
+        let json = serde_json::to_value(version).map_err(Error::Serialize)?;
+
+        serde_json::from_value(json).map_err(Error::Deserialize)
+    }
+}
+
+impl TryFrom<&Release> for crates_index::Version {
+    type Error = Error;
+
+    fn try_from(release: &Release) -> Result<Self, Self::Error> {
+        let mut json = serde_json::to_value(release).map_err(Error::Serialize)?;
+
+        // The index stores the minimum supported rust version without an operator
+        if let Some(rust_version) = &release.rust_version {
+            let rust_version = rust_version.to_string();
+            json["rust_version"] = rust_version.trim_start_matches('^').into();
+        }
+
+        serde_json::from_value(json).map_err(Error::Deserialize)
+    }
+}
+
+/// Fails with [`Error::FromIndexFile`] if the crate has no versions
+impl TryFrom<&crates_index::Crate> for Package {
+    type Error = Error;
+
+    fn try_from(krate: &crates_index::Crate) -> Result<Self, Self::Error> {
+        let releases = krate
+            .versions()
+            .iter()
+            .map(Release::try_from)
+            .collect::<Result<Vec<Release>, Error>>()?;
+
+        Package::from_releases(releases, Vec::new())
+    }
+}
+
+impl TryFrom<&Package> for crates_index::Crate {
+    type Error = Error;
+
+    fn try_from(package: &Package) -> Result<Self, Self::Error> {
+        let lines = package
+            .releases()
+            .iter()
+            .map(|release| {
+                let version = crates_index::Version::try_from(release)?;
+                serde_json::to_string(&version).map_err(Error::Serialize)
+            })
+            .collect::<Result<Vec<String>, Error>>()?;
+
+        crates_index::Crate::from_slice(lines.join("\n").as_bytes()).map_err(Error::Io)
+    }
+}
@@ -0,0 +1,99 @@
+//! Conversions to and from [`cargo_metadata`] types
+
+use crate::{error::Error, Dependency, DependencyKind, Features, Release};
+use semver::VersionReq;
+
+impl From<&cargo_metadata::Dependency> for Dependency {
+    fn from(dep: &cargo_metadata::Dependency) -> Self {
+        // `cargo metadata` names dependencies by package, and gives the manifest name as a rename
+        let (name, package) = match &dep.rename {
+            Some(rename) => (rename.clone(), Some(dep.name.clone())),
+            None => (dep.name.clone(), None),
+        };
+
+        Dependency {
+            name,
+            req: dep.req.clone(),
+            features: dep.features.clone(),
+            optional: dep.optional,
+            default_features: dep.uses_default_features,
+            target: dep.target.as_ref().map(ToString::to_string),
+            kind: DependencyKind::from(dep.kind),
+            registry: dep.registry.clone(),
+            package,
+        }
+    }
+}
+
+/// `cargo_metadata::Dependency` can't be constructed directly, so this goes through it's JSON
+/// representation. Fails with [`Error::Deserialize`] if the target isn't a valid platform
+impl TryFrom<&Dependency> for cargo_metadata::Dependency {
+    type Error = Error;
+
+    fn try_from(dep: &Dependency) -> Result<Self, Self::Error> {
+        let json = serde_json::json!({
+            "name": dep.package_name(),
+            "source": null,
+            "req": dep.req,
+            "kind": dep.kind.as_str(),
+            "optional": dep.optional,
+            "uses_default_features": dep.default_features,
+            "features": dep.features,
+            "target": dep.target,
+            "rename": dep.package.as_ref().map(|_| &dep.name),
+            "registry": dep.registry,
+            "path": null,
+        });
+
+        serde_json::from_value(json).map_err(Error::Deserialize)
+    }
+}
+
+impl From<cargo_metadata::DependencyKind> for DependencyKind {
+    fn from(kind: cargo_metadata::DependencyKind) -> Self {
+        match kind {
+            cargo_metadata::DependencyKind::Normal => DependencyKind::Normal,
+            cargo_metadata::DependencyKind::Development => DependencyKind::Dev,
+            cargo_metadata::DependencyKind::Build => DependencyKind::Build,
+            other => DependencyKind::Unknown(other.to_string()),
+        }
+    }
+}
+
+/// Build a release from a package's manifest as reported by `cargo metadata`
+///
+/// Features using the extended syntax are placed in `features2`, as the registry does when a
+/// package is published. The checksum is left empty, since it's only known once a package is
+/// packaged, and the release is never yanked
+impl From<&cargo_metadata::Package> for Release {
+    fn from(package: &cargo_metadata::Package) -> Self {
+        let (features2, features): (Features, Features) = package
+            .features
+            .clone()
+            .into_iter()
+            .partition(|(_, values)| {
+                values
+                    .iter()
+                    .any(|value| value.starts_with("dep:") || value.contains("?/"))
+            });
+
+        let rust_version = package.rust_version.as_ref().and_then(|version| {
+            VersionReq::parse(&format!("{}.{}", version.major, version.minor)).ok()
+        });
+
+        Release {
+            name: package.name.clone(),
+            vers: package.version.clone(),
+            deps: package.dependencies.iter().map(Dependency::from).collect(),
+            cksum: String::new(),
+            features,
+            yanked: false,
+            links: package.links.clone(),
+            v: if features2.is_empty() { 1 } else { 2 },
+            features2: (!features2.is_empty()).then_some(features2),
+            rust_version,
+            published: None,
+            license: package.license.clone(),
+        }
+    }
+}
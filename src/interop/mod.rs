@@ -0,0 +1,12 @@
+//! Conversions between this crate's types and those of other crates, each behind a cargo feature
+//!
+//! - `cargo_metadata`: [`Dependency`](crate::Dependency) and [`Release`](crate::Release) from the
+//!   types returned by `cargo metadata`
+//! - `crates-index`: [`Release`](crate::Release) and [`Package`](crate::Package) to and from the
+//!   types of the `crates-index` crate, which can read cargo's local index cache
+
+#[cfg(feature = "cargo_metadata")]
+mod metadata;
+
+#[cfg(feature = "crates-index")]
+mod crates_index;
@@ -36,19 +36,58 @@
 
 #![deny(clippy::all)]
 
+#[cfg(feature = "api")]
+pub mod advisories;
+#[cfg(feature = "api")]
+pub mod api;
+pub mod availability;
+pub mod checksum;
+pub mod compat;
+pub mod diff;
+#[cfg(feature = "api")]
+pub mod docs_rs;
+pub mod download;
 pub mod error;
+pub mod extensions;
+pub mod features;
+pub mod graph;
+pub mod interop;
+pub mod lockfile;
+pub mod manifest;
+pub mod msrv;
+pub mod outdated;
+pub mod resolve;
+pub mod sbom;
+pub mod suggest;
+pub mod target;
 #[cfg(test)]
 mod tests;
 
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Read,
+    str::FromStr,
+    sync::OnceLock,
+};
 
-use error::Error;
+use error::{Error, NameError};
+use target::Target;
 
 /// The default crates.io index URL
 pub const CRATES_IO_INDEX_URL: &str = "https://index.crates.io";
 
+/// The crates.io web API URL, used for information that isn't in the index
+pub const CRATES_IO_API_URL: &str = "https://crates.io/api/v1";
+
+/// The user agent sent with every request. crates.io asks API users to identify themselves
+const USER_AGENT: &str = concat!(
+    "cargo-lookup/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/collinoc/cargo-lookup)"
+);
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// A query for a specific rust package based on the packages name, an option version requirement,
@@ -58,15 +97,27 @@ pub struct Query {
     name: String,
     version_req: Option<VersionReq>,
     custom_index: Option<String>,
+    policy: SelectionPolicy,
+    lenient: bool,
+    mirror: Option<String>,
+    features: Vec<String>,
+    separator_fallback: bool,
 }
 
+/// Queries are written as `name`, optionally followed by `@` and a version requirement, and then
+/// a list of features as either `+rt,macros` or `?features=rt,macros`, e.g. `tokio@^1+rt,macros`
+///
+/// Build metadata isn't meaningful in version requirements, so a `+` always starts the feature list
 impl FromStr for Query {
     type Err = Error;
 
-    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+    fn from_str(spec: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, features) = split_features(spec.trim())?;
         let (name, version_req) = match name.split_once('@') {
-            Some((name, version)) if !version.is_empty() => (name, Some(version)),
-            _ => (name, None),
+            // `cargo add` accepts a bare `name@`, meaning any version
+            Some((name, version)) if version.trim().is_empty() => (name.trim(), None),
+            Some((name, version)) => (name.trim(), Some(version.trim())),
+            None => (name, None),
         };
 
         let version_req = version_req
@@ -77,12 +128,62 @@ impl FromStr for Query {
             name: name.to_owned(),
             version_req,
             custom_index: None,
+            policy: SelectionPolicy::default(),
+            lenient: false,
+            mirror: None,
+            features,
+            separator_fallback: false,
         })
     }
 }
 
+/// Split the feature list from the end of a query string
+fn split_features(spec: &str) -> Result<(&str, Vec<String>)> {
+    let (spec, list) = if let Some((spec, params)) = spec.split_once('?') {
+        let mut list = Vec::new();
+
+        for param in params.split('&').filter(|param| !param.is_empty()) {
+            match param.split_once('=') {
+                Some(("features", features)) => list.push(features),
+                _ => return Err(Error::InvalidQuery(param.to_owned())),
+            }
+        }
+
+        (spec, list)
+    } else if let Some((spec, features)) = spec.split_once('+') {
+        (spec, vec![features])
+    } else {
+        (spec, Vec::new())
+    };
+
+    let features = list
+        .into_iter()
+        .flat_map(|features| features.split(','))
+        .map(str::trim)
+        .filter(|feature| !feature.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    Ok((spec.trim(), features))
+}
+
 impl Query {
+    /// Return the package name as it was given in this query
+    ///
+    /// The canonical name of the package is available from [`Package::name`] once it has been
+    /// fetched, since registries treat names case-insensitively
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Return the version requirement of this query, if one was given
+    pub fn version_req(&self) -> Option<&VersionReq> {
+        self.version_req.as_ref()
+    }
+
     /// USe a custom crate index for this query
+    ///
+    /// Sparse index URLs may be given as they are written in cargo's config, with a `sparse+` prefix
     pub fn with_index<T>(mut self, custom_index: T) -> Self
     where
         String: From<T>,
@@ -91,48 +192,301 @@ impl Query {
         self
     }
 
+    /// Return the URL of the index this query looks packages up in, crates.io unless
+    /// [`Query::with_index`] was used
+    pub fn index_url(&self) -> &str {
+        self.custom_index.as_deref().unwrap_or(CRATES_IO_INDEX_URL)
+    }
+
+    /// Return the features selected by this query, e.g. `rt` and `macros` for `tokio@^1+rt,macros`
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Select features for this query, in addition to any given in the query string
+    pub fn with_features<I, T>(mut self, features: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        String: From<T>,
+    {
+        self.features.extend(features.into_iter().map(String::from));
+        self
+    }
+
+    /// Use a custom selection policy when picking a release in [`Query::submit`]
+    pub fn with_policy(mut self, policy: SelectionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set whether [`Query::submit`] may select yanked releases, which are skipped by default
+    pub fn include_yanked(mut self, include_yanked: bool) -> Self {
+        self.policy = self.policy.include_yanked(include_yanked);
+        self
+    }
+
+    /// Set how [`Query::submit`] treats yanked releases, e.g. to only find yanked releases when
+    /// auditing
+    pub fn with_yank_policy(mut self, yank_policy: YankPolicy) -> Self {
+        self.policy = self.policy.yank_policy(yank_policy);
+        self
+    }
+
+    /// Set whether [`Query::submit`] may select pre-release versions, which are skipped by default
+    ///
+    /// See [`SelectionPolicy::include_prereleases`] for how this affects version requirements
+    pub fn include_prereleases(mut self, include_prereleases: bool) -> Self {
+        self.policy = self.policy.include_prereleases(include_prereleases);
+        self
+    }
+
+    /// Only let [`Query::submit`] select releases supporting a rust toolchain version
+    ///
+    /// See [`Release::supports_rust_version`]
+    pub fn with_msrv(mut self, rust_version: Version) -> Self {
+        self.policy = self.policy.rust_version(Some(rust_version));
+        self
+    }
+
+    /// Skip index lines that fail to parse instead of failing the whole query
+    ///
+    /// Skipped lines are reported by [`Package::warnings`]
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Cross-check the package against a mirror of the index
+    ///
+    /// The same index file is fetched from the mirror, and [`Query::package`] fails with
+    /// [`Error::CrossCheck`] if the releases found in each don't match
+    pub fn with_cross_check<T>(mut self, mirror: T) -> Self
+    where
+        String: From<T>,
+    {
+        self.mirror = Some(String::from(mirror));
+        self
+    }
+
     /// Return the raw contents of the index file found by this query
     pub fn raw_index(&self) -> Result<String> {
-        let index_url = self.custom_index.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
-        let index_path = get_index_path(&self.name);
-        let response = ureq::get(&format!("{index_url}/{index_path}"))
-            .call()
-            .map_err(|err| Error::Request(Box::new(err)))?
-            .into_string()
-            .map_err(Error::Io)?;
+        self.raw_index_from(self.index_url())
+    }
+
+    fn raw_index_from(&self, index_url: &str) -> Result<String> {
+        // Accept index URLs as they are written in cargo's config, e.g. `sparse+https://...`
+        let index_url = index_url
+            .trim_start_matches("sparse+")
+            .trim_end_matches('/');
+        let fetch = |name: &str| get(&format!("{index_url}/{}", get_index_path(name)?));
+
+        let result = fetch(&self.name);
 
-        Ok(response)
+        if !self.separator_fallback || !matches!(&result, Err(error) if error.is_not_found()) {
+            return result;
+        }
+
+        for candidate in self.name_candidates().iter().skip(1) {
+            match fetch(candidate) {
+                Err(error) if error.is_not_found() => continue,
+                other => return other,
+            }
+        }
+
+        // Report the name as it was given if none of the candidates exist either
+        result
+    }
+
+    /// Return the names this query may refer to, starting with the name as it was given
+    ///
+    /// Registries treat `-` and `_` as equivalent when checking for conflicting names, but index
+    /// files are stored under the name as it was published, so `serde-json` has to be looked up as
+    /// `serde_json`. The other candidates swap every separator for `_`, then for `-`
+    pub fn name_candidates(&self) -> Vec<String> {
+        let mut candidates = vec![self.name.clone()];
+
+        for candidate in [self.name.replace('-', "_"), self.name.replace('_', "-")] {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+
+    /// Retry with the other [name candidates](Query::name_candidates) if the index file for the
+    /// name as given doesn't exist
+    pub fn separator_fallback(mut self, separator_fallback: bool) -> Self {
+        self.separator_fallback = separator_fallback;
+        self
     }
 
     /// Return all of the info for the package found by this query
     pub fn package(&self) -> Result<Package> {
-        Package::from_index(self.raw_index()?)
+        let package = self.parse_index(self.raw_index()?)?;
+
+        if let Some(mirror) = self.mirror.as_deref() {
+            let mirrored = self.parse_index(self.raw_index_from(mirror)?)?;
+            package.cross_check(&mirrored)?;
+        }
+
+        Ok(package)
+    }
+
+    fn parse_index(&self, content: String) -> Result<Package> {
+        if self.lenient {
+            Package::from_index_lenient(content)
+        } else {
+            Package::from_index(content)
+        }
     }
 
     /// Return a specific release of a package found by this query
     ///
     /// If no version requirement ws specified, the latest version of the found package
     /// will be returned
+    ///
+    /// Only releases allowed by this query's [`SelectionPolicy`] are considered, so yanked
+    /// releases and pre-release versions are skipped unless opted into with
+    /// [`Query::include_yanked`] and [`Query::include_prereleases`]
     pub fn submit(&self) -> Result<Option<Release>> {
-        let package = self.package()?;
+        Ok(self.select(self.package()?))
+    }
+
+    /// Select the release matching this query from an already fetched package
+    pub fn select(&self, package: Package) -> Option<Release> {
+        package.into_selected(self.version_req.as_ref(), &self.policy)
+    }
+}
+
+/// Rules deciding which releases of a package are eligible for selection
+///
+/// The same policy is used by [`Query::submit`], the [`Package`] selection methods, and the
+/// command-line tool, so a release is picked the same way everywhere.
+///
+/// The default policy matches cargo, skipping yanked releases and pre-release versions unless a
+/// version requirement explicitly names a pre-release. See [`SelectionPolicy::permissive`] for a
+/// policy allowing every release
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionPolicy {
+    yank_policy: YankPolicy,
+    include_prereleases: bool,
+    rust_version: Option<Version>,
+}
+
+/// How yanked releases are treated when selecting a release
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum YankPolicy {
+    /// Skip yanked releases
+    #[default]
+    Exclude,
+    /// Allow yanked releases alongside releases that haven't been yanked
+    Include,
+    /// Only allow yanked releases
+    Only,
+}
 
-        match self.version_req {
-            Some(ref version_req) => Ok(package.into_version(version_req)),
-            None => Ok(package.into_latest()),
+impl YankPolicy {
+    /// Check if a release is allowed under this policy
+    pub fn allows(self, release: &Release) -> bool {
+        match self {
+            YankPolicy::Exclude => !release.yanked,
+            YankPolicy::Include => true,
+            YankPolicy::Only => release.yanked,
+        }
+    }
+}
+
+impl SelectionPolicy {
+    /// A policy allowing every release, including yanked and pre-release versions
+    pub fn permissive() -> Self {
+        Self {
+            yank_policy: YankPolicy::Include,
+            include_prereleases: true,
+            ..Self::default()
+        }
+    }
+
+    /// Set whether yanked releases may be selected
+    ///
+    /// This is shorthand for [`YankPolicy::Include`] or [`YankPolicy::Exclude`]
+    pub fn include_yanked(mut self, include_yanked: bool) -> Self {
+        self.yank_policy = if include_yanked {
+            YankPolicy::Include
+        } else {
+            YankPolicy::Exclude
+        };
+        self
+    }
+
+    /// Set how yanked releases are treated
+    pub fn yank_policy(mut self, yank_policy: YankPolicy) -> Self {
+        self.yank_policy = yank_policy;
+        self
+    }
+
+    /// Set whether pre-release versions may be selected
+    ///
+    /// When included, a pre-release may be selected without a version requirement, and a
+    /// requirement matches the pre-releases of versions it would otherwise match (e.g. `^2`
+    /// matches `2.1.0-rc.1`). A version requirement that explicitly names a pre-release (e.g.
+    /// `=1.0.0-beta.1`) can always select it, following the usual semver matching rules
+    pub fn include_prereleases(mut self, include_prereleases: bool) -> Self {
+        self.include_prereleases = include_prereleases;
+        self
+    }
+
+    /// Only allow releases whose minimum supported rust version is satisfied by `rust_version`
+    pub fn rust_version(mut self, rust_version: Option<Version>) -> Self {
+        self.rust_version = rust_version;
+        self
+    }
+
+    /// Check if a release may be selected under this policy and an optional version requirement
+    pub fn allows(&self, release: &Release, version_req: Option<&VersionReq>) -> bool {
+        if !self.yank_policy.allows(release) {
+            return false;
+        }
+
+        let matches_req = match version_req {
+            Some(version_req) => {
+                let Version {
+                    major,
+                    minor,
+                    patch,
+                    ..
+                } = release.vers;
+
+                version_req.matches(&release.vers)
+                    || (self.include_prereleases
+                        && version_req.matches(&Version::new(major, minor, patch)))
+            }
+            None => self.include_prereleases || release.vers.pre.is_empty(),
+        };
+
+        if !matches_req {
+            return false;
+        }
+
+        match &self.rust_version {
+            Some(rust_version) => release.supports_rust_version(rust_version),
+            None => true,
         }
     }
 }
 
 /// All info on a package from it's index file, including all of it's releases
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Package {
     name: String,
     index_path: String,
     releases: Vec<Release>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<IndexWarning>,
 }
 
 impl Package {
-    /// Return this package's name
+    /// Return this package's canonical name, as published in the index
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
@@ -142,19 +496,113 @@ impl Package {
         self.index_path.as_str()
     }
 
-    /// Return a list of releases for this package
+    /// Return a list of releases for this package, ordered from oldest to latest
     pub fn releases(&self) -> &Vec<Release> {
         &self.releases
     }
 
+    /// Return the index lines that were skipped when parsing with [`Package::from_index_lenient`]
+    pub fn warnings(&self) -> &[IndexWarning] {
+        &self.warnings
+    }
+
     /// Convert into a packages latest release
-    pub fn into_latest(mut self) -> Option<Release> {
-        self.releases.pop()
+    ///
+    /// This may be a yanked release, see [`Package::into_latest_unyanked`] to skip those
+    pub fn into_latest(self) -> Option<Release> {
+        self.into_selected(None, &SelectionPolicy::permissive())
     }
 
     /// Get a packages latest release
+    ///
+    /// This may be a yanked release, see [`Package::latest_unyanked`] to skip those
     pub fn latest(&self) -> Option<&Release> {
-        self.releases.last()
+        self.select(None, &SelectionPolicy::permissive())
+    }
+
+    /// Convert into a packages latest release that hasn't been yanked
+    pub fn into_latest_unyanked(self) -> Option<Release> {
+        self.into_selected(None, &SelectionPolicy::permissive().include_yanked(false))
+    }
+
+    /// Get a packages latest release that hasn't been yanked
+    pub fn latest_unyanked(&self) -> Option<&Release> {
+        self.select(None, &SelectionPolicy::permissive().include_yanked(false))
+    }
+
+    /// Get the latest release supporting a rust toolchain version
+    ///
+    /// Yanked releases and pre-release versions are skipped, as with [`SelectionPolicy::default`].
+    /// See [`Release::supports_rust_version`]
+    pub fn latest_for_msrv(&self, rust_version: &Version) -> Option<&Release> {
+        let policy = SelectionPolicy::default().rust_version(Some(rust_version.clone()));
+
+        self.select(None, &policy)
+    }
+
+    /// Return an iterator over this package's releases that aren't pre-release versions
+    pub fn stable_releases(&self) -> impl Iterator<Item = &Release> {
+        self.releases
+            .iter()
+            .filter(|release| release.vers.pre.is_empty())
+    }
+
+    /// Convert into a packages latest release that isn't a pre-release version
+    ///
+    /// Like [`Package::into_latest`], this may be a yanked release
+    pub fn into_latest_stable(self) -> Option<Release> {
+        self.into_selected(
+            None,
+            &SelectionPolicy::permissive().include_prereleases(false),
+        )
+    }
+
+    /// Get a packages latest release that isn't a pre-release version
+    ///
+    /// Like [`Package::latest`], this may be a yanked release
+    pub fn latest_stable(&self) -> Option<&Release> {
+        self.select(
+            None,
+            &SelectionPolicy::permissive().include_prereleases(false),
+        )
+    }
+
+    /// Convert to the latest release allowed by a selection policy and optional version requirement
+    pub fn into_selected(
+        self,
+        version_req: Option<&VersionReq>,
+        policy: &SelectionPolicy,
+    ) -> Option<Release> {
+        self.releases
+            .into_iter()
+            .rev()
+            .find(|release| policy.allows(release, version_req))
+    }
+
+    /// Find the latest release allowed by a selection policy and optional version requirement
+    pub fn select(
+        &self,
+        version_req: Option<&VersionReq>,
+        policy: &SelectionPolicy,
+    ) -> Option<&Release> {
+        self.releases
+            .iter()
+            .rev()
+            .find(|release| policy.allows(release, version_req))
+    }
+
+    /// Return every release matching a version requirement, newest first
+    ///
+    /// Unlike [`Package::version`], this doesn't stop at the newest match, and yanked releases
+    /// are included
+    pub fn versions_matching<'a>(
+        &'a self,
+        version_req: &'a VersionReq,
+    ) -> impl Iterator<Item = &'a Release> {
+        self.releases
+            .iter()
+            .rev()
+            .filter(move |release| version_req.matches(&release.vers))
     }
 
     /// Convert to a package release from a given version requirement
@@ -163,11 +611,14 @@ impl Package {
     ///
     /// For example, with a version requirement of `^0.1.0`, this will return `0.1.9` before it
     /// will return `0.1.8`
+    ///
+    /// This may be a yanked release, see [`Package::into_selected`] with a [`YankPolicy`] to
+    /// control that
     pub fn into_version(self, version_req: &semver::VersionReq) -> Option<Release> {
-        self.releases
-            .into_iter()
-            .rev()
-            .find(|release| version_req.matches(&release.vers))
+        self.into_selected(
+            Some(version_req),
+            &SelectionPolicy::permissive().include_prereleases(false),
+        )
     }
 
     /// Find a package release from a given version requirement
@@ -176,25 +627,92 @@ impl Package {
     ///
     /// For example, with a version requirement of `^0.1.0`, this will return `0.1.9` before it
     /// will return `0.1.8`
+    ///
+    /// This may be a yanked release, see [`Package::select`] with a [`YankPolicy`] to control that
     pub fn version(&self, version_req: &semver::VersionReq) -> Option<&Release> {
-        self.releases
-            .iter()
-            .rev()
-            .find(|release| version_req.matches(&release.vers))
+        self.select(
+            Some(version_req),
+            &SelectionPolicy::permissive().include_prereleases(false),
+        )
+    }
+
+    /// Check that another copy of this package, e.g. from a mirror, has exactly the same releases
+    ///
+    /// Releases are compared regardless of the order they appear in either index file
+    pub fn cross_check(&self, other: &Package) -> Result<()> {
+        let ours = self.releases.iter().collect::<BTreeSet<&Release>>();
+        let theirs = other.releases.iter().collect::<BTreeSet<&Release>>();
+
+        if let Some(release) = ours.difference(&theirs).next() {
+            let Release { name, vers, .. } = release;
+            return Err(Error::CrossCheck(format!(
+                "{name} {vers} does not match any release in the mirror"
+            )));
+        }
+
+        if let Some(release) = theirs.difference(&ours).next() {
+            let Release { name, vers, .. } = release;
+            return Err(Error::CrossCheck(format!(
+                "{name} {vers} from the mirror does not match any release in the index"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Convert the package, including all of it's releases, to it's json representation
+    pub fn as_json_string(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(Error::Serialize)
     }
 
     /// Parse a package from it's index file
+    ///
+    /// Releases are sorted from oldest to latest by version, regardless of the order they
+    /// appear in the index file
     pub fn from_index<T>(content: T) -> Result<Self>
     where
         T: AsRef<str>,
     {
-        let content = content.as_ref();
-
         let releases: Result<Vec<Release>> = content
+            .as_ref()
             .lines()
             .map(|release| serde_json::from_str(release).map_err(Error::Deserialize))
             .collect();
-        let releases = releases?;
+
+        Self::from_releases(releases?, Vec::new())
+    }
+
+    /// Parse a package from it's index file, skipping any lines that fail to parse
+    ///
+    /// Each skipped line is recorded in [`Package::warnings`], so a single malformed historical
+    /// release doesn't make the whole package unusable
+    pub fn from_index_lenient<T>(content: T) -> Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let mut releases = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (index, line) in content.as_ref().lines().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(release) => releases.push(release),
+                Err(error) => warnings.push(IndexWarning {
+                    line: index + 1,
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        Self::from_releases(releases, warnings)
+    }
+
+    pub(crate) fn from_releases(
+        mut releases: Vec<Release>,
+        warnings: Vec<IndexWarning>,
+    ) -> Result<Self> {
+        // Not every registry writes its index lines in order, so sort releases by version to
+        // keep `latest` and `version` correct
+        releases.sort_by(|a, b| a.vers.cmp(&b.vers));
 
         let name = releases
             .last()
@@ -202,16 +720,57 @@ impl Package {
             .name
             .clone();
 
-        let index_path = get_index_path(&name);
+        let index_path = get_index_path(&name)?;
 
         Ok(Package {
             name,
             index_path,
             releases,
+            warnings,
         })
     }
 }
 
+/// Displays a multi-line summary of a package and it's latest release
+impl std::fmt::Display for Package {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            name, index_path, ..
+        } = self;
+        let releases = plural(self.releases.len(), "release");
+        let yanked = self
+            .releases
+            .iter()
+            .filter(|release| release.yanked)
+            .count();
+
+        writeln!(f, "{name}")?;
+        writeln!(f, "  index path: {index_path}")?;
+        writeln!(f, "  releases: {releases} ({yanked} yanked)")?;
+
+        match self.latest() {
+            Some(latest) => write!(f, "  latest: {latest}"),
+            None => write!(f, "  latest: none"),
+        }
+    }
+}
+
+/// An index file line that was skipped while leniently parsing a package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexWarning {
+    /// The line number of the skipped entry, starting at `1`
+    pub line: usize,
+    /// Why the line could not be parsed
+    pub message: String,
+}
+
+impl std::fmt::Display for IndexWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { line, message } = self;
+        write!(f, "skipped index line {line}: {message}")
+    }
+}
+
 // See: https://github.com/serde-rs/serde/issues/368
 const fn one() -> u32 {
     1
@@ -222,7 +781,7 @@ const fn one() -> u32 {
 /// A package index file contains one line for each release of a package in json format, from oldest to latest.
 ///
 /// More info on the schema can be found in [The Cargo Book](https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Release {
     /// The name of the package
     pub name: String,
@@ -248,6 +807,18 @@ pub struct Release {
     pub features2: Option<Features>,
     /// The minimum supported rust version requirement without operator
     pub rust_version: Option<VersionReq>,
+    /// When this release was published, as an RFC 3339 timestamp
+    ///
+    /// This isn't part of the index, and is only set after fetching dates from the registry's
+    /// API with `Package::with_publish_dates`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    /// The SPDX license expression from this release's manifest, e.g. `MIT OR Apache-2.0`
+    ///
+    /// This isn't part of the index, and is only set after fetching licenses from the registry's
+    /// API with `Package::with_licenses`, or when converted from another source that has it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
 }
 
 impl Release {
@@ -255,6 +826,155 @@ impl Release {
     pub fn as_json_string(&self) -> Result<String> {
         serde_json::to_string(self).map_err(Error::Serialize)
     }
+
+    /// Return an iterator over this release's normal dependencies
+    pub fn normal_deps(&self) -> impl Iterator<Item = &Dependency> {
+        self.deps_of_kind(DependencyKind::Normal)
+    }
+
+    /// Return an iterator over this release's dev dependencies
+    pub fn dev_deps(&self) -> impl Iterator<Item = &Dependency> {
+        self.deps_of_kind(DependencyKind::Dev)
+    }
+
+    /// Return an iterator over this release's build dependencies
+    pub fn build_deps(&self) -> impl Iterator<Item = &Dependency> {
+        self.deps_of_kind(DependencyKind::Build)
+    }
+
+    /// Return an iterator over this release's dependencies of a given kind
+    pub fn deps_of_kind(&self, kind: DependencyKind) -> impl Iterator<Item = &Dependency> {
+        self.deps.iter().filter(move |dep| dep.kind == kind)
+    }
+
+    /// Return an iterator over this release's dependencies that apply to a target triple
+    ///
+    /// See [`Dependency::applies_to`]
+    pub fn deps_for_target<'a>(&'a self, triple: &'a str) -> impl Iterator<Item = &'a Dependency> {
+        self.deps.iter().filter(move |dep| dep.applies_to(triple))
+    }
+
+    /// Return every feature of this release, merging `features` and `features2`
+    ///
+    /// Entries with schema version `2` or later store features using the extended syntax
+    /// (`dep:name` and `name?/feature`) in `features2` so older versions of cargo ignore them.
+    /// Reading `features` alone misses those, so this should be preferred in most cases
+    pub fn all_features(&self) -> Features {
+        let mut features = self.features.clone();
+
+        for (name, enables) in self.features2.iter().flatten() {
+            let merged = features.entry(name.clone()).or_default();
+
+            for enable in enables {
+                if !merged.contains(enable) {
+                    merged.push(enable.clone());
+                }
+            }
+        }
+
+        features
+    }
+
+    /// Return a copy of this release with the semantics of older schema versions made explicit
+    ///
+    /// `features2` is merged into `features`, see [`Release::all_features`], and optional
+    /// dependencies that have an implicit feature get an explicit `name = ["dep:name"]` feature,
+    /// as cargo does when reading the entry. The result is marked as schema version `2`, so
+    /// entries can be treated uniformly regardless of the schema they were published with
+    pub fn normalized(&self) -> Release {
+        let mut features = self.all_features();
+
+        for dep in self.deps.iter().filter(|dep| dep.optional) {
+            if self.has_implicit_feature(&dep.name) {
+                features.insert(dep.name.clone(), vec![format!("dep:{}", dep.name)]);
+            }
+        }
+
+        Release {
+            features,
+            features2: None,
+            v: self.v.max(2),
+            ..self.clone()
+        }
+    }
+
+    /// Check if a rust toolchain version satisfies this release's minimum supported rust version
+    ///
+    /// Releases without a `rust_version` are unconstrained and support every toolchain. The
+    /// index stores the version without an operator (e.g. `1.60`), which is treated as a minimum
+    /// rather than a caret requirement, so newer major versions are supported too. Pre-release
+    /// toolchains (e.g. `1.70.0-nightly`) count as the version they precede
+    pub fn supports_rust_version(&self, toolchain: &Version) -> bool {
+        let Some(minimum) = self.minimum_rust_version() else {
+            return true;
+        };
+        let toolchain = Version::new(toolchain.major, toolchain.minor, toolchain.patch);
+
+        toolchain >= minimum
+    }
+
+    /// Return the oldest rust toolchain this release supports, from it's `rust_version`
+    ///
+    /// Missing minor and patch versions are taken as `0`, so a `rust_version` of `1.60` is
+    /// `1.60.0`. See [`Release::supports_rust_version`]
+    pub fn minimum_rust_version(&self) -> Option<Version> {
+        let comparator = self.rust_version.as_ref()?.comparators.first()?;
+
+        Some(Version::new(
+            comparator.major,
+            comparator.minor.unwrap_or(0),
+            comparator.patch.unwrap_or(0),
+        ))
+    }
+}
+
+/// Releases are ordered by name, then by version, then by every other field so the order agrees
+/// with `Eq`
+impl Ord for Release {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.vers.cmp(&other.vers))
+            .then_with(|| self.cksum.cmp(&other.cksum))
+            .then_with(|| self.yanked.cmp(&other.yanked))
+            .then_with(|| self.deps.cmp(&other.deps))
+            .then_with(|| self.features.cmp(&other.features))
+            .then_with(|| self.features2.cmp(&other.features2))
+            .then_with(|| self.links.cmp(&other.links))
+            .then_with(|| self.v.cmp(&other.v))
+            .then_with(|| {
+                let rust_version =
+                    |release: &Self| release.rust_version.as_ref().map(ToString::to_string);
+                rust_version(self).cmp(&rust_version(other))
+            })
+            .then_with(|| self.published.cmp(&other.published))
+            .then_with(|| self.license.cmp(&other.license))
+    }
+}
+
+impl PartialOrd for Release {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Displays a one line summary of a release, e.g. `serde 1.0.210 (4 deps, 7 features)`
+impl std::fmt::Display for Release {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { name, vers, .. } = self;
+        let deps = plural(self.deps.len(), "dep");
+        let features = plural(self.all_features().len(), "feature");
+        let yanked = if self.yanked { ", yanked" } else { "" };
+
+        write!(f, "{name} {vers} ({deps}, {features}{yanked})")
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("{count} {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
 }
 
 pub type Features = BTreeMap<String, Vec<String>>;
@@ -262,7 +982,7 @@ pub type Features = BTreeMap<String, Vec<String>>;
 /// A dependency of a package
 ///
 /// The structure can be found in [The Cargo Book](https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Dependency {
     /// The name of the dependency
     pub name: String,
@@ -277,42 +997,278 @@ pub struct Dependency {
     /// Target platform for the dependency
     pub target: Option<String>,
     /// The dependency kind (dev, build, or normal)
-    pub kind: Option<String>,
+    ///
+    /// Defaults to [`DependencyKind::Normal`] when missing from the index
+    #[serde(default)]
+    pub kind: DependencyKind,
     /// The URL of the index where this dependency is from. Defaults to current registry
     pub registry: Option<String>,
     /// If dependency is renamed, this is the name of the actual dependend upon package
     pub package: Option<String>,
 }
 
+impl Dependency {
+    /// Return the name of the package this dependency refers to, accounting for renames
+    pub fn package_name(&self) -> &str {
+        self.package.as_deref().unwrap_or(self.name.as_str())
+    }
+
+    /// Return a query string for the package this dependency refers to, like
+    /// `serde@^1.0.219?features=derive`, which can be parsed into a [`Query`]
+    pub fn query_spec(&self) -> String {
+        let mut spec = format!("{}@{}", self.package_name(), self.req);
+
+        if !self.features.is_empty() {
+            spec.push_str("?features=");
+            spec.push_str(&self.features.join(","));
+        }
+
+        spec
+    }
+
+    /// Check if this dependency applies when building for a target triple
+    ///
+    /// Dependencies without a target apply everywhere. A target that fails to parse is treated as
+    /// applying, so that dependencies are never silently dropped
+    pub fn applies_to(&self, triple: &str) -> bool {
+        match self.target.as_deref().map(str::parse::<Target>) {
+            Some(Ok(target)) => target.applies_to(triple),
+            Some(Err(_)) | None => true,
+        }
+    }
+}
+
+/// The kind of a dependency, determining when it is used
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "Option<String>", into = "String")]
+pub enum DependencyKind {
+    /// A dependency used to build and run the package
+    #[default]
+    Normal,
+    /// A dependency only used for tests, examples, and benchmarks
+    Dev,
+    /// A dependency only used by the package's build script
+    Build,
+    /// A dependency kind this library doesn't know about
+    Unknown(String),
+}
+
+impl DependencyKind {
+    /// Return the kind as it is written in the index
+    pub fn as_str(&self) -> &str {
+        match self {
+            DependencyKind::Normal => "normal",
+            DependencyKind::Dev => "dev",
+            DependencyKind::Build => "build",
+            DependencyKind::Unknown(kind) => kind.as_str(),
+        }
+    }
+}
+
+impl From<Option<String>> for DependencyKind {
+    fn from(kind: Option<String>) -> Self {
+        match kind.as_deref() {
+            None | Some("normal") => DependencyKind::Normal,
+            Some("dev") => DependencyKind::Dev,
+            Some("build") => DependencyKind::Build,
+            Some(_) => DependencyKind::Unknown(kind.unwrap_or_default()),
+        }
+    }
+}
+
+impl From<DependencyKind> for String {
+    fn from(kind: DependencyKind) -> Self {
+        match kind {
+            DependencyKind::Unknown(kind) => kind,
+            known => known.as_str().to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Dependencies are ordered by name, then by kind and target
+impl Ord for Dependency {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| self.target.cmp(&other.target))
+            .then_with(|| self.req.to_string().cmp(&other.req.to_string()))
+            .then_with(|| self.features.cmp(&other.features))
+            .then_with(|| self.optional.cmp(&other.optional))
+            .then_with(|| self.default_features.cmp(&other.default_features))
+            .then_with(|| self.registry.cmp(&other.registry))
+            .then_with(|| self.package.cmp(&other.package))
+    }
+}
+
+impl PartialOrd for Dependency {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The agent every request is made with, so connections to a registry are reused
+fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(ureq::Agent::new)
+}
+
+/// Fetch the body of a URL as a string
+pub(crate) fn get(url: &str) -> Result<String> {
+    agent()
+        .get(url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|err| Error::Request(Box::new(err)))?
+        .into_string()
+        .map_err(Error::Io)
+}
+
+/// Fetch the body of a URL as bytes, for binary content like `.crate` files
+pub(crate) fn get_bytes(url: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    agent()
+        .get(url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|err| Error::Request(Box::new(err)))?
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(Error::Io)?;
+
+    Ok(bytes)
+}
+
+/// Find the length of a URL's content with a `HEAD` request, if the server reports it
+pub(crate) fn content_length(url: &str) -> Result<Option<u64>> {
+    let response = agent()
+        .head(url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|err| Error::Request(Box::new(err)))?;
+
+    Ok(response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok()))
+}
+
+/// Post a json body to a URL, returning the body of the response as a string
+#[cfg(feature = "api")]
+pub(crate) fn post(url: &str, body: &serde_json::Value) -> Result<String> {
+    agent()
+        .post(url)
+        .set("User-Agent", USER_AGENT)
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|err| Error::Request(Box::new(err)))?
+        .into_string()
+        .map_err(Error::Io)
+}
+
 /// Get the index path for a package
 ///
+/// Fails with [`Error::InvalidName`] if the name isn't a valid crate name, see
+/// [`validate_crate_name`]
+///
 /// ## Examples
 ///
 /// ```
 /// use cargo_lookup::get_index_path;
 ///
-/// assert_eq!(get_index_path("cargo"), "ca/rg/cargo");
-/// assert_eq!(get_index_path("ice"), "3/i/ice");
+/// assert_eq!(get_index_path("cargo").unwrap(), "ca/rg/cargo");
+/// assert_eq!(get_index_path("ice").unwrap(), "3/i/ice");
+/// assert!(get_index_path("").is_err());
 /// ```
-pub fn get_index_path<T>(package: T) -> String
+pub fn get_index_path<T>(package: T) -> Result<String>
 where
     T: AsRef<str>,
 {
-    let package = package.as_ref();
-
-    let path = match package.len() {
-        1 => format!("1/{package}"),
-        2 => format!("2/{package}"),
-        3 => {
-            let first_char = &package[..1];
-            format!("3/{first_char}/{package}")
-        }
-        _ => {
-            let first_two_chars = &package[..2];
-            let next_two_chars = &package[2..4];
-            format!("{first_two_chars}/{next_two_chars}/{package}")
-        }
+    validate_crate_name(package.as_ref()).map_err(Error::InvalidName)?;
+
+    // Index paths are always lowercase
+    let package = package.as_ref().to_ascii_lowercase();
+
+    Ok(format!("{}/{package}", index_prefix(&package)))
+}
+
+/// Get the directories a package's index file is in, e.g. `ca/rg` for `cargo`
+///
+/// The case of the name is kept, since registries also use this for download URLs
+pub(crate) fn index_prefix(package: &str) -> String {
+    let chars = |range: std::ops::Range<usize>| -> String {
+        package
+            .chars()
+            .skip(range.start)
+            .take(range.len())
+            .collect()
     };
 
-    path.to_ascii_lowercase()
+    match package.chars().count() {
+        1 => "1".to_owned(),
+        2 => "2".to_owned(),
+        3 => format!("3/{}", chars(0..1)),
+        _ => format!("{}/{}", chars(0..2), chars(2..4)),
+    }
+}
+
+/// The maximum length of a crate name on crates.io
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// Check that a name follows the rules crates.io enforces for crate names
+///
+/// Names must be non-empty, at most [`MAX_NAME_LENGTH`] characters, start with an ASCII letter,
+/// and only contain ASCII letters, digits, `-`, and `_`
+///
+/// ## Examples
+///
+/// ```
+/// use cargo_lookup::{error::NameError, validate_crate_name};
+///
+/// assert!(validate_crate_name("serde_json").is_ok());
+/// assert_eq!(validate_crate_name("1password"), Err(NameError::InvalidStart('1')));
+/// ```
+pub fn validate_crate_name(name: &str) -> std::result::Result<(), NameError> {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        None => return Err(NameError::Empty),
+        Some(first) if !first.is_ascii_alphabetic() => return Err(NameError::InvalidStart(first)),
+        Some(_) => {}
+    }
+
+    if let Some(invalid) = chars.find(|char| !char.is_ascii_alphanumeric() && !"-_".contains(*char))
+    {
+        return Err(NameError::InvalidChar(invalid));
+    }
+
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong(name.len()));
+    }
+
+    Ok(())
+}
+
+/// Normalize a package name the way crates.io does when checking if two names refer to the same
+/// package
+///
+/// Names are compared case-insensitively, and `-` and `_` are treated as equivalent
+///
+/// ## Examples
+///
+/// ```
+/// use cargo_lookup::normalize_name;
+///
+/// assert_eq!(normalize_name("Serde-JSON"), normalize_name("serde_json"));
+/// ```
+pub fn normalize_name<T>(name: T) -> String
+where
+    T: AsRef<str>,
+{
+    name.as_ref().trim().to_lowercase().replace('-', "_")
 }
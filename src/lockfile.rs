@@ -0,0 +1,463 @@
+//! `Cargo.lock` files, and how they differ from a fresh resolution
+//!
+//! A [`Lockfile`] can be deserialized from the TOML of a `Cargo.lock` with a crate like `toml`,
+//! or built from a [`DependencyGraph`] with [`Lockfile::from_graph`]. Comparing a lockfile to a
+//! graph resolved from it's roots, see [`Lockfile::compare`], shows what `cargo update` would
+//! change before running it.
+//!
+//! Locked packages can also be checked against the index, for releases that have since been
+//! yanked or checksums that don't match, see [`LockedPackage::audit`]. Only packages from a
+//! registry are compared or audited, since path and git packages aren't in an index
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{lockfile::Lockfile, resolve::resolve, Query, Result};
+//!
+//! fn compare(lockfile: &Lockfile) -> Result<()> {
+//!     let roots = lockfile
+//!         .roots()
+//!         .map(|root| format!("{}@^{}", root.name, root.version).parse::<Query>())
+//!         .collect::<Result<Vec<Query>>>()?;
+//!     let resolution = resolve(&roots)?;
+//!
+//!     for change in lockfile.compare(resolution.graph()).changed {
+//!         println!("{}: {} -> {}", change.name, change.version.old, change.version.new);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    compat::{compat, Bump},
+    diff::Change,
+    graph::{DependencyGraph, NodeId},
+    normalize_name, Package, Release, CRATES_IO_INDEX_URL,
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The `source` cargo writes in lockfiles for packages from crates.io
+const CRATES_IO_SOURCE: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+/// The contents of a `Cargo.lock` file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// The lockfile format version. Missing from the oldest lockfiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// The locked packages
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A package pinned in a lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The name of the package
+    pub name: String,
+    /// The locked version
+    pub version: Version,
+    /// Where the package comes from, e.g. `registry+https://github.com/rust-lang/crates.io-index`.
+    /// Missing for path packages, like the members of a workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The SHA256 checksum of the package's `.crate` file, for registry packages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// The packages this one depends on, as `name`, `name version`, or `name version (source)`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from a registry index, rather than a path or git repository
+    pub fn is_registry(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+    }
+
+    /// Return the URL of the sparse index the package is locked from, if it's from a registry
+    ///
+    /// crates.io packages are locked with the URL of it's git index, which is mapped to
+    /// [`CRATES_IO_INDEX_URL`]. Other registries are only supported with sparse indexes, so
+    /// packages from a git index return `None`
+    pub fn index_url(&self) -> Option<String> {
+        let source = self.source.as_deref()?;
+        if source == CRATES_IO_SOURCE {
+            return Some(CRATES_IO_INDEX_URL.to_owned());
+        }
+
+        source
+            .strip_prefix("sparse+")
+            .map(|index_url| index_url.trim_end_matches('/').to_owned())
+    }
+
+    /// Check the locked release against the index file of it's package, returning any problems
+    /// with it
+    pub fn audit(&self, package: &Package) -> Vec<LockIssue> {
+        let Some(release) = package
+            .releases()
+            .iter()
+            .find(|release| release.vers == self.version)
+        else {
+            return vec![LockIssue::Missing];
+        };
+
+        let mut issues = Vec::new();
+        if let Some(checksum) = &self.checksum {
+            if !checksum.eq_ignore_ascii_case(&release.cksum) {
+                issues.push(LockIssue::ChecksumMismatch {
+                    locked: checksum.clone(),
+                    index: release.cksum.clone(),
+                });
+            }
+        }
+        if release.yanked {
+            issues.push(LockIssue::Yanked);
+        }
+
+        issues
+    }
+}
+
+/// A problem with a locked package, found by looking it up in the index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum LockIssue {
+    /// The locked release has been yanked
+    Yanked,
+    /// The index doesn't have the locked release, or the package at all
+    Missing,
+    /// The locked checksum isn't the one in the index. Either the lockfile was edited, or the
+    /// release in the index was replaced, which could be a sign of tampering
+    ChecksumMismatch {
+        /// The checksum in the lockfile
+        locked: String,
+        /// The checksum in the index
+        index: String,
+    },
+}
+
+impl std::fmt::Display for LockIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockIssue::Yanked => write!(f, "yanked"),
+            LockIssue::Missing => write!(f, "not in the index"),
+            LockIssue::ChecksumMismatch { locked, index } => {
+                write!(
+                    f,
+                    "locked checksum `{locked}` doesn't match the index's `{index}`"
+                )
+            }
+        }
+    }
+}
+
+/// A package at one version
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PackageVersion {
+    /// The name of the package
+    pub name: String,
+    /// The version of the package
+    pub version: Version,
+}
+
+/// A package that resolves to a different version than the one locked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionChange {
+    /// The name of the package
+    pub name: String,
+    /// The locked and resolved versions
+    pub version: Change<Version>,
+    /// How breaking the version change is under cargo's caret semantics
+    pub bump: Bump,
+}
+
+/// A package whose locked checksum differs from the one in the index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChecksumChange {
+    /// The name of the package
+    pub name: String,
+    /// The version of the package
+    pub version: Version,
+    /// The locked and indexed checksums
+    pub checksum: Change<String>,
+}
+
+/// The differences between a lockfile and a resolution, see [`Lockfile::compare`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LockDiff {
+    /// Packages resolved to a different version than the locked one
+    pub changed: Vec<VersionChange>,
+    /// Packages in the resolution but not the lockfile
+    pub added: Vec<PackageVersion>,
+    /// Packages in the lockfile but not the resolution
+    pub removed: Vec<PackageVersion>,
+    /// Packages resolved to the locked version, but with a different checksum
+    pub checksums: Vec<ChecksumChange>,
+}
+
+impl LockDiff {
+    /// Whether the resolution matches the lockfile
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.checksums.is_empty()
+    }
+}
+
+impl Lockfile {
+    /// Build a version 3 lockfile of the releases in a graph, which come from the index at
+    /// `index_url`
+    ///
+    /// Packages are sorted by name and version. Like cargo, a dependency is named along with
+    /// it's version only when the lockfile has more than one version of it
+    pub fn from_graph(graph: &DependencyGraph, index_url: &str) -> Self {
+        let source = lockfile_source(index_url);
+
+        let mut nodes = graph.nodes().collect::<Vec<(NodeId, &Release)>>();
+        nodes.sort_by(|(_, a), (_, b)| (&a.name, &a.vers).cmp(&(&b.name, &b.vers)));
+
+        let packages = nodes
+            .into_iter()
+            .map(|(id, release)| {
+                let mut dependencies = graph
+                    .dependencies(id)
+                    .map(|edge| graph.release(edge.to))
+                    .collect::<Vec<&Release>>();
+                dependencies.sort_by(|a, b| (&a.name, &a.vers).cmp(&(&b.name, &b.vers)));
+                dependencies.dedup_by(|a, b| a.name == b.name && a.vers == b.vers);
+
+                let dependencies = dependencies
+                    .into_iter()
+                    .map(|dependency| {
+                        if graph.named(&dependency.name).nth(1).is_some() {
+                            format!("{} {}", dependency.name, dependency.vers)
+                        } else {
+                            dependency.name.clone()
+                        }
+                    })
+                    .collect();
+
+                LockedPackage {
+                    name: release.name.clone(),
+                    version: release.vers.clone(),
+                    source: Some(source.clone()),
+                    checksum: Some(release.cksum.clone()),
+                    dependencies,
+                }
+            })
+            .collect();
+
+        Self {
+            version: Some(3),
+            packages,
+        }
+    }
+
+    /// Write the lockfile as TOML, in the layout cargo uses
+    pub fn to_toml(&self) -> String {
+        let mut lockfile = String::from(
+            "# This file is automatically @generated by cargo-lookup.\n\
+             # It is not intended for manual editing.\n",
+        );
+        if let Some(version) = self.version {
+            lockfile.push_str(&format!("version = {version}\n"));
+        }
+
+        for package in &self.packages {
+            lockfile.push_str(&format!(
+                "\n[[package]]\nname = \"{}\"\nversion = \"{}\"\n",
+                package.name, package.version
+            ));
+            if let Some(source) = &package.source {
+                lockfile.push_str(&format!("source = \"{source}\"\n"));
+            }
+            if let Some(checksum) = &package.checksum {
+                lockfile.push_str(&format!("checksum = \"{checksum}\"\n"));
+            }
+
+            if package.dependencies.is_empty() {
+                continue;
+            }
+
+            lockfile.push_str("dependencies = [\n");
+            for dependency in &package.dependencies {
+                lockfile.push_str(&format!(" \"{dependency}\",\n"));
+            }
+            lockfile.push_str("]\n");
+        }
+
+        lockfile
+    }
+
+    /// Find the locked package a dependency entry of another package refers to
+    pub fn dependency(&self, entry: &str) -> Option<&LockedPackage> {
+        let mut parts = entry.split_whitespace();
+        let name = parts.next()?;
+        let version = parts
+            .next()
+            .and_then(|version| version.parse::<Version>().ok());
+
+        self.packages.iter().find(|package| {
+            package.name == name
+                && version
+                    .as_ref()
+                    .map_or(true, |vers| package.version == *vers)
+        })
+    }
+
+    /// Return the registry packages the lockfile was resolved from: those depended on directly
+    /// by a path or git package, like a workspace member, and those nothing depends on
+    pub fn roots(&self) -> impl Iterator<Item = &LockedPackage> {
+        let mut depended_on = BTreeSet::new();
+        let mut roots = BTreeSet::new();
+
+        for package in &self.packages {
+            for dependency in package
+                .dependencies
+                .iter()
+                .filter_map(|entry| self.dependency(entry))
+            {
+                let id = (&dependency.name, &dependency.version);
+
+                depended_on.insert(id);
+                if !package.is_registry() {
+                    roots.insert(id);
+                }
+            }
+        }
+
+        self.packages.iter().filter(move |package| {
+            let id = (&package.name, &package.version);
+            package.is_registry() && (roots.contains(&id) || !depended_on.contains(&id))
+        })
+    }
+
+    /// Compare the locked registry packages with the releases of a graph, usually one resolved
+    /// from the lockfile's [roots](Lockfile::roots)
+    ///
+    /// Versions of a package are paired up when they're semver compatible, so a package locked
+    /// at more than one version is compared version by version. When one version of a package
+    /// is locked and one is resolved, they're paired even if the change is breaking
+    pub fn compare(&self, graph: &DependencyGraph) -> LockDiff {
+        let mut locked = BTreeMap::<String, BTreeMap<Version, Option<&str>>>::new();
+        for package in self.packages.iter().filter(|package| package.is_registry()) {
+            locked
+                .entry(normalize_name(&package.name))
+                .or_default()
+                .insert(package.version.clone(), package.checksum.as_deref());
+        }
+
+        let mut resolved = BTreeMap::<String, BTreeMap<Version, &Release>>::new();
+        for release in graph.releases() {
+            resolved
+                .entry(normalize_name(&release.name))
+                .or_default()
+                .insert(release.vers.clone(), release);
+        }
+
+        let names = locked
+            .keys()
+            .chain(resolved.keys())
+            .cloned()
+            .collect::<BTreeSet<String>>();
+        let mut diff = LockDiff::default();
+
+        for name in names {
+            let mut old = locked.remove(&name).unwrap_or_default();
+            let mut new = resolved.remove(&name).unwrap_or_default();
+            let display_name = new
+                .values()
+                .next()
+                .map(|release| release.name.clone())
+                .or_else(|| {
+                    self.packages
+                        .iter()
+                        .find(|package| normalize_name(&package.name) == name)
+                        .map(|package| package.name.clone())
+                })
+                .unwrap_or(name);
+
+            for (version, checksum) in &old {
+                let (Some(checksum), Some(release)) = (checksum, new.get(version)) else {
+                    continue;
+                };
+                if !checksum.eq_ignore_ascii_case(&release.cksum) {
+                    diff.checksums.push(ChecksumChange {
+                        name: display_name.clone(),
+                        version: version.clone(),
+                        checksum: Change {
+                            old: checksum.to_string(),
+                            new: release.cksum.clone(),
+                        },
+                    });
+                }
+            }
+            let unchanged = old
+                .keys()
+                .filter(|version| new.contains_key(*version))
+                .cloned()
+                .collect::<Vec<Version>>();
+            for version in &unchanged {
+                old.remove(version);
+                new.remove(version);
+            }
+
+            let mut pairs = Vec::new();
+            for from in old.keys() {
+                let to = new.keys().find(|to| {
+                    !compat(from, to).is_breaking()
+                        && !pairs.iter().any(|(_, paired)| paired == *to)
+                });
+                if let Some(to) = to {
+                    pairs.push((from.clone(), to.clone()));
+                }
+            }
+            if pairs.is_empty() && old.len() == 1 && new.len() == 1 {
+                pairs.extend(old.keys().cloned().zip(new.keys().cloned()));
+            }
+
+            for (from, to) in pairs {
+                old.remove(&from);
+                new.remove(&to);
+                diff.changed.push(VersionChange {
+                    name: display_name.clone(),
+                    bump: compat(&from, &to),
+                    version: Change { old: from, new: to },
+                });
+            }
+
+            diff.removed
+                .extend(old.into_keys().map(|version| PackageVersion {
+                    name: display_name.clone(),
+                    version,
+                }));
+            diff.added
+                .extend(new.into_keys().map(|version| PackageVersion {
+                    name: display_name.clone(),
+                    version,
+                }));
+        }
+
+        diff
+    }
+}
+
+/// Return the `source` cargo writes in lockfiles for packages from an index
+fn lockfile_source(index_url: &str) -> String {
+    let index_url = index_url
+        .trim_start_matches("sparse+")
+        .trim_end_matches('/');
+
+    if index_url == CRATES_IO_INDEX_URL {
+        CRATES_IO_SOURCE.to_owned()
+    } else {
+        format!("sparse+{index_url}/")
+    }
+}
@@ -1,67 +1,271 @@
 #![deny(clippy::all)]
 
-use anyhow::{anyhow, bail, Result};
-use cargo_lookup::{Query, Release};
+use anyhow::{anyhow, Result};
+use cargo_lookup::{
+    api,
+    diff::feature_history,
+    download::RegistryConfig,
+    graph::{DependencyGraph, NodeId},
+    normalize_name,
+    resolve::{Fetch, Resolver, Unifier},
+    suggest, Dependency, DependencyKind, Package, Query, Release, SelectionPolicy, YankPolicy,
+    CRATES_IO_INDEX_URL,
+};
 use clap::Parser;
-use std::ops::Deref;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
+mod cache;
 mod cli;
+mod commands;
+mod enrich;
+mod jobs;
+mod project;
+mod registry;
+mod stream;
+mod table;
+mod template;
+mod yaml;
 
-use cli::{Cli, Format, Options, Type};
+use cli::{Cli, Command, Format, Options, Type};
+use enrich::{Enrichment, Entry};
+use stream::Stream;
+use table::Table;
+use template::{Field, Template};
 
 fn main() -> Result<()> {
-    let Cli::Lookup(options) = Cli::parse();
-    let packages = options.packages.as_slice();
+    let Cli::Lookup(mut options) = Cli::parse();
+    let policy = options.policy();
 
-    let mut resolved = Vec::new();
-    let resolve_depth = options
-        .max_depth
-        .map(Depth::Restricted)
-        .unwrap_or(Depth::Infinite);
+    if options.capabilities {
+        return commands::capabilities::run();
+    }
 
-    for package in packages {
-        resolve(
-            package,
-            options.index_url.as_deref(),
-            resolve_depth,
-            &options,
-            &mut resolved,
-        )?;
-    }
-
-    if options.kind == Some(Type::Json) {
-        // Print all resolved items in one JSON list
-        let json = if options.format == Format::Pretty {
-            serde_json::to_string_pretty(&resolved)?
-        } else {
-            serde_json::to_string(&resolved)?
-        };
+    if let Some(name) = &options.registry {
+        options.index_url = Some(registry::index_url(name)?);
+    }
+
+    match options.command {
+        Some(Command::Pin(ref pin)) => commands::pin::run(pin, &options, &policy),
+        Some(Command::Diff(ref diff)) => commands::diff::run(diff, &options, &policy),
+        Some(Command::Exists(ref exists)) => commands::exists::run(exists, &options, &policy),
+        Some(Command::IndexPath(ref index_path)) => commands::index_path::run(index_path, &options),
+        Some(Command::Raw(ref raw)) => commands::raw::run(raw, &options, &policy),
+        Some(Command::Download(ref download)) => {
+            commands::download::run(download, &options, &policy)
+        }
+        Some(Command::Verify(ref verify)) => commands::verify::run(verify, &options, &policy),
+        Some(Command::Available(ref available)) => {
+            commands::available::run(available, &options, &policy)
+        }
+        Some(Command::Owners(ref owners)) => commands::owners::run(owners, &options),
+        Some(Command::Downloads(ref downloads)) => commands::downloads::run(downloads, &options),
+        Some(Command::Rdeps(ref rdeps)) => commands::rdeps::run(rdeps, &options),
+        Some(Command::Search(ref search)) => commands::search::run(search, &options),
+        Some(Command::Info(ref info)) => commands::info::run(info, &options, &policy),
+        Some(Command::AuditLock(ref audit)) => commands::audit_lock::run(audit, &options, &policy),
+        Some(Command::LockDiff(ref lock)) => commands::lock_diff::run(lock, &options, &policy),
+        Some(Command::Msrv(ref msrv)) => commands::msrv::run(msrv, &options, &policy),
+        Some(Command::Sbom(ref sbom)) => commands::sbom::run(sbom, &options, &policy),
+        Some(Command::Outdated(ref outdated)) => {
+            commands::outdated::run(outdated, &options, &policy)
+        }
+        Some(Command::Graph(ref graph)) => commands::graph::run(graph, &options, &policy),
+        Some(Command::Tree(ref tree)) => commands::tree::run(tree, &options, &policy),
+        Some(Command::TreeSize(ref tree)) => commands::tree_size::run(tree, &options, &policy),
+        Some(Command::Why(ref why)) => commands::why::run(why, &options, &policy),
+        None => lookup(&options, &policy),
+    }
+}
+
+fn lookup(options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    let mut packages = options.packages.clone();
+    if let Some(manifest_path) = &options.manifest_path {
+        packages.extend(project::manifest_queries(manifest_path, options)?);
+    }
+    let packages = packages.as_slice();
+
+    match options.kind {
+        Some(Type::Versions) => return print_versions(packages, options, policy),
+        Some(Type::FeatureHistory) => return print_feature_history(packages, options, policy),
+        _ => {}
+    }
 
-        println!("{json}");
+    let stream = match options.format {
+        Format::Ndjson => Some(Stream::new(options)),
+        _ => None,
+    };
+
+    let (root_releases, resolved) = if options.resolve {
+        resolve_consistent(packages, policy, options)?
     } else {
-        for release in resolved {
-            let use_prefix = !matches!(options.format, Format::CargoAddAll | Format::NoPrefix);
+        resolve_each(packages, policy, options, stream.as_ref())?
+    };
+
+    match options.format {
+        Format::Ndjson => {
+            // Releases are printed as they're found, except when resolving consistently, where
+            // a release isn't settled until the whole resolution is
+            if let Some(stream) = &stream {
+                for release in resolved.releases() {
+                    stream.emit(release, options)?;
+                }
+            }
+            return Ok(());
+        }
+        Format::Dot => {
+            print!("{}", resolved.to_dot());
+            return Ok(());
+        }
+        Format::Mermaid => {
+            print!("{}", resolved.to_mermaid());
+            return Ok(());
+        }
+        Format::Lockfile => {
+            let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+            print!("{}", resolved.to_lockfile_toml(index_url));
+            return Ok(());
+        }
+        Format::Toml => return print_toml(packages, &root_releases, &resolved, options),
+        Format::BuildOrder => {
+            for id in resolved.build_order() {
+                let Release { name, vers, .. } = resolved.release(id?);
+                println!("{name} {vers}");
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // Queried packages are shown along with any features selected in their query string
+    let mut roots = HashMap::new();
+    for (package, root) in packages.iter().zip(root_releases) {
+        if let Some(root) = root {
+            let features = package.parse::<Query>()?.features().to_vec();
+            roots.entry(root).or_insert_with(Vec::new).extend(features);
+        }
+    }
+
+    let enrichment = Enrichment::load(options);
+    let mut entries = resolved
+        .into_releases()
+        .into_iter()
+        .map(|release| enrichment.entry(release))
+        .collect::<Vec<Entry>>();
+
+    if options.check_advisories {
+        Enrichment::check_advisories(&mut entries, options.jobs());
+    }
+    enrichment.check_sizes(&mut entries, options.jobs());
+    // Download URLs need the registry's `config.json` too, which is reused if it was fetched
+    // for sizes. Unlike sizes, they can't be left out, so failing to fetch it is an error
+    let registry_config = match (&options.kind, enrichment.registry_config()) {
+        (Some(Type::DlUrl), Some(config)) => Some(config.clone()),
+        (Some(Type::DlUrl), None) => {
+            let index_url = options.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+            Some(RegistryConfig::fetch(index_url)?)
+        }
+        _ => None,
+    };
+
+    // Download counts are per package, so they're fetched once for all of a package's releases
+    let downloads_client = match options.kind {
+        Some(Type::Downloads) => Some(api_client(options)?),
+        _ => None,
+    };
+    let mut download_counts = HashMap::new();
+
+    if options.format.is_table() {
+        let mut table = Table::new(options.columns());
+        for entry in &entries {
+            let release = &entry.release;
+            let root_features = roots
+                .get(&(release.name.clone(), release.vers.clone()))
+                .map(Vec::as_slice);
+            table.push(table_row(
+                release,
+                entry.crate_size,
+                root_features,
+                options,
+            )?);
+        }
+
+        print_table(&table, options);
+        return Ok(());
+    }
+
+    // Structured output prints all resolved items in one list
+    if options.format_str.is_some() || !print_structured(&entries, options)? {
+        for entry in entries {
+            if let Some(lifecycle) = &entry.lifecycle {
+                let Release { name, vers, .. } = &entry.release;
+                eprintln!("note: {name} {vers}: {lifecycle}");
+            }
+            for advisory in &entry.advisories {
+                let Release { name, vers, .. } = &entry.release;
+                eprintln!("warning: {name} {vers}: {advisory}");
+            }
+
+            let release = &entry.release;
+            let root_features = roots
+                .get(&(release.name.clone(), release.vers.clone()))
+                .map(Vec::as_slice);
+
+            if let Some(template) = &options.format_str {
+                let rendered = render(template, release, entry.crate_size, root_features, options)?;
+                println!("{rendered}");
+                continue;
+            }
+
             let (kind, delim) = match options.format {
                 Format::CargoAddAll => (Some(Type::Features).as_ref(), ","),
                 _ => (options.kind.as_ref(), options.delim.as_str()),
             };
+            // Checksum lines already start with the name, like `sha256sum` lines with the file,
+            // and download URLs are printed bare so they can be piped to `curl`
+            let use_prefix = !matches!(options.format, Format::CargoAddAll | Format::NoPrefix)
+                && !matches!(kind, Some(Type::Cksum | Type::DlUrl));
 
             let info_string = match kind {
                 Some(Type::Features) => release
-                    .features
-                    .keys()
-                    .map(Deref::deref)
-                    .collect::<Vec<&str>>()
-                    .join(delim),
-                Some(Type::Deps) => release
-                    .deps
-                    .iter()
-                    .map(|dep| dep.name.as_str())
-                    .collect::<Vec<&str>>()
+                    .all_features()
+                    .into_keys()
+                    .collect::<Vec<String>>()
                     .join(delim),
-                Some(Type::Json) | None => release.as_json_string()?,
+                Some(Type::Deps) => shown_deps(release, root_features, options)?.join(delim),
+                Some(Type::Downloads) => {
+                    let client = downloads_client
+                        .as_ref()
+                        .expect("the API client is created for download counts");
+                    downloads(release, client, &mut download_counts)?.join(delim)
+                }
+                Some(Type::License) => release
+                    .license
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_owned()),
+                Some(Type::Cksum) => format!("{} {} {}", release.name, release.vers, release.cksum),
+                Some(Type::DlUrl) => registry_config
+                    .as_ref()
+                    .expect("the registry's config is fetched for download URLs")
+                    .download_url(release),
+                Some(Type::Versions | Type::FeatureHistory) => {
+                    unreachable!("releases are listed before resolving")
+                }
+                Some(Type::Json | Type::Yaml) | None => serde_json::to_string(&entry)?,
             };
 
+            if options.dates {
+                print!("{} ", published_date(release));
+            }
+            if options.size {
+                let size = entry.crate_size.map_or("unknown".to_owned(), human_size);
+                print!("{size:>10} ");
+            }
+
             if use_prefix {
                 let package = &release.name;
                 println!("{package}:{info_string}");
@@ -74,55 +278,792 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn resolve(
-    package: &str,
-    index: Option<&str>,
-    depth: Depth,
+/// Return the names of a release's dependencies shown by `--type=deps`, which are those enabled
+/// by the selected features and allowed by `--dep-kind` and `--target`
+fn shown_deps<'a>(
+    release: &'a Release,
+    root_features: Option<&[String]>,
     options: &Options,
-    resolved: &mut Vec<Release>,
+) -> Result<Vec<&'a str>> {
+    Ok(enabled_deps(release, root_features, options)?
+        .into_iter()
+        .filter(|dep| options.includes_dep_kind(&dep.kind))
+        .filter(|dep| options.includes_dep_target(dep))
+        .map(|dep| dep.name.as_str())
+        .collect())
+}
+
+/// Fill in a `--format-str` template for a release
+fn render(
+    template: &Template,
+    release: &Release,
+    crate_size: Option<u64>,
+    root_features: Option<&[String]>,
+    options: &Options,
+) -> Result<String> {
+    template.render(|field| field_value(field, release, crate_size, root_features, options))
+}
+
+/// Return the value of a `--format-str` placeholder or `--columns` field for a release
+fn field_value(
+    field: Field,
+    release: &Release,
+    crate_size: Option<u64>,
+    root_features: Option<&[String]>,
+    options: &Options,
+) -> Result<String> {
+    let delim = options.delim.as_str();
+
+    Ok(match field {
+        Field::Name => release.name.clone(),
+        Field::Version => release.vers.to_string(),
+        Field::Yanked => release.yanked.to_string(),
+        Field::Msrv => release
+            .rust_version
+            .as_ref()
+            .map_or("none".to_owned(), |rust_version| {
+                rust_version.to_string().trim_start_matches('^').to_owned()
+            }),
+        Field::Features => release
+            .all_features()
+            .into_keys()
+            .collect::<Vec<String>>()
+            .join(delim),
+        Field::Deps => shown_deps(release, root_features, options)?.join(delim),
+        Field::DepCount => shown_deps(release, root_features, options)?
+            .len()
+            .to_string(),
+        Field::FeatureCount => release.all_features().len().to_string(),
+        Field::Checksum => release.cksum.clone(),
+        Field::License => release
+            .license
+            .clone()
+            .unwrap_or_else(|| "unknown".to_owned()),
+        Field::Published => published_date(release).to_owned(),
+        Field::Size => crate_size.map_or("unknown".to_owned(), human_size),
+    })
+}
+
+/// Return a `--columns` row for a release
+fn table_row(
+    release: &Release,
+    crate_size: Option<u64>,
+    root_features: Option<&[String]>,
+    options: &Options,
+) -> Result<Vec<String>> {
+    options
+        .columns()
+        .iter()
+        .map(|field| field_value(*field, release, crate_size, root_features, options))
+        .collect()
+}
+
+/// Print a table in the format chosen by `--format`
+fn print_table(table: &Table, options: &Options) {
+    match options.format {
+        Format::Tsv => print!("{}", table.to_tsv()),
+        Format::Table => print!("{}", table.to_aligned()),
+        _ => print!("{}", table.to_csv()),
+    }
+}
+
+/// Print every release of each queried package, newest first, limited to those matching the
+/// query's version requirement if it has one and by the yank flags
+fn print_versions(packages: &[String], options: &Options, policy: &SelectionPolicy) -> Result<()> {
+    // Rows of every package go in one table, printed once they're all listed
+    let mut table = options
+        .format
+        .is_table()
+        .then(|| Table::new(options.columns()));
+
+    for (query, package) in fetch_listed(packages, options, policy)? {
+        for release in listed_releases(&query, &package, options) {
+            if let Some(table) = &mut table {
+                table.push(table_row(release, None, None, options)?);
+                continue;
+            }
+            if let Some(template) = &options.format_str {
+                println!("{}", render(template, release, None, None, options)?);
+                continue;
+            }
+
+            if options.dates {
+                print!("{} ", published_date(release));
+            }
+            if options.format != Format::NoPrefix {
+                print!("{}:", release.name);
+            }
+
+            let yanked = if release.yanked { " (yanked)" } else { "" };
+            println!("{}{yanked}", release.vers);
+        }
+    }
+
+    if let Some(table) = &table {
+        print_table(table, options);
+    }
+
+    Ok(())
+}
+
+/// Print the releases of each queried package that added or removed features, oldest first,
+/// from the releases `--type=versions` would list. Pre-releases are skipped unless
+/// `--include-prerelease` is given
+fn print_feature_history(
+    packages: &[String],
+    options: &Options,
+    policy: &SelectionPolicy,
 ) -> Result<()> {
-    let query: Query = match index {
-        Some(custom) => package.parse::<Query>()?.with_index(custom),
-        None => package.parse()?,
+    for (query, package) in fetch_listed(packages, options, policy)? {
+        let prefix = match options.format {
+            Format::NoPrefix => String::new(),
+            _ => format!("{}:", package.name()),
+        };
+
+        let releases = listed_releases(&query, &package, options)
+            .filter(|release| options.include_prerelease || release.vers.pre.is_empty());
+        for (index, changes) in feature_history(releases).iter().enumerate() {
+            let version = &changes.version;
+
+            // Features of the oldest release may have been added before a limited range
+            let added = if index == 0 && query.version_req().is_some() {
+                "present"
+            } else {
+                "added"
+            };
+            for feature in &changes.added {
+                println!("{prefix}{feature} {added} in {version}");
+            }
+            for feature in &changes.removed {
+                println!("{prefix}{feature} removed in {version}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch the package of each query for listing it's releases, skipping missing packages with
+/// `--ignore-missing`
+fn fetch_listed(
+    packages: &[String],
+    options: &Options,
+    policy: &SelectionPolicy,
+) -> Result<Vec<(Query, Arc<Package>)>> {
+    let queries = packages
+        .iter()
+        .map(|package| query(package, options, policy))
+        .collect::<Result<Vec<Query>>>()?;
+    let cache = cache::Packages::default();
+    let fetched = jobs::parallel_map(&queries, options.jobs(), |query| cache.fetch(query));
+
+    // Publish dates and licenses are added afterwards, one package at a time
+    let enrichment = Enrichment::load(options);
+    let mut listed = Vec::new();
+    for (query, fetched) in queries.into_iter().zip(fetched) {
+        match fetched {
+            Ok(package) => listed.push((query, enrichment.package(package))),
+            Err(_) if options.ignore_missing => continue,
+            Err(other) if other.is_not_found() && options.index_url.is_none() => {
+                return Err(not_found(query.name(), other))
+            }
+            Err(other) => return Err(anyhow!(other)),
+        }
+    }
+
+    Ok(listed)
+}
+
+/// Return the releases of a package to list, newest first, limited to those matching the
+/// query's version requirement if it has one and by the yank flags
+fn listed_releases<'a>(
+    query: &'a Query,
+    package: &'a Package,
+    options: &Options,
+) -> impl Iterator<Item = &'a Release> {
+    let releases: Box<dyn Iterator<Item = &Release>> = match query.version_req() {
+        Some(version_req) => Box::new(package.versions_matching(version_req)),
+        None => Box::new(package.releases().iter().rev()),
     };
 
-    let result = match query.submit() {
-        Ok(Some(result)) => result,
-        _ if options.ignore_missing => return Ok(()),
-        Ok(None) => bail!("failed to find a matching release of `{package}`"),
-        Err(other) => return Err(anyhow!(other)),
+    // Unlike selection, listings include yanked releases unless asked not to
+    let yank_policy = options.yank_policy().unwrap_or(YankPolicy::Include);
+    releases.filter(move |release| yank_policy.allows(release))
+}
+
+/// Return the date a release was published, e.g. `2024-09-06`, or `unknown` without `--dates`
+fn published_date(release: &Release) -> &str {
+    // Keep just the date of the timestamp
+    release.published.as_deref().map_or("unknown", |published| {
+        published
+            .split_once('T')
+            .map_or(published, |(date, _)| date)
+    })
+}
+
+/// Print a `Cargo.toml` dependency line for the release selected for each queried package
+fn print_toml(
+    packages: &[String],
+    root_releases: &[Option<(String, Version)>],
+    resolved: &DependencyGraph,
+    options: &Options,
+) -> Result<()> {
+    let mut lines = Vec::new();
+
+    for (package, root) in packages.iter().zip(root_releases) {
+        let Some(release) = root
+            .as_ref()
+            .and_then(|(name, version)| resolved.find(name, version))
+            .map(|id| resolved.release(id))
+        else {
+            continue;
+        };
+
+        let mut features = package.parse::<Query>()?.features().to_vec();
+        features.extend(options.features.iter().cloned());
+        features.sort();
+        features.dedup();
+
+        // Fails for features the release doesn't have, rather than writing a line cargo rejects
+        let selected = features.iter().map(String::as_str).collect::<Vec<&str>>();
+        release.activated_deps(&selected, !options.no_default_features)?;
+
+        let line = Dependency {
+            features,
+            default_features: !options.no_default_features,
+            ..release.to_dependency()
+        }
+        .to_manifest_line();
+
+        if !lines.contains(&line) {
+            println!("{line}");
+            lines.push(line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a number of bytes for people to read, e.g. `78.3 KiB`
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+/// Return the total, recent, and per-version download counts of a release as `key=count` pairs
+///
+/// Each package's counts are only requested from the registry once, and kept in `fetched`
+fn downloads(
+    release: &Release,
+    client: &api::Client,
+    fetched: &mut HashMap<String, api::Downloads>,
+) -> Result<Vec<String>> {
+    if !fetched.contains_key(&release.name) {
+        let downloads = client.downloads(&release.name)?;
+        fetched.insert(release.name.clone(), downloads);
+    }
+    let downloads = &fetched[&release.name];
+
+    let mut counts = vec![format!("total={}", downloads.total)];
+    if let Some(recent) = downloads.recent {
+        counts.push(format!("recent={recent}"));
+    }
+    if let Some(version) = downloads.version(&release.vers) {
+        counts.push(format!("{}={version}", release.vers));
+    }
+
+    Ok(counts)
+}
+
+/// The name and version of the release selected for each queried package, if one was found,
+/// and the graph of every release resolved
+type Resolved = (Vec<Option<(String, Version)>>, DependencyGraph);
+
+/// Resolve each queried package independently, picking the newest match for every requirement
+///
+/// Each release is printed to `stream` as soon as it's selected, if there is one
+fn resolve_each(
+    packages: &[String],
+    policy: &SelectionPolicy,
+    options: &Options,
+    stream: Option<&Stream>,
+) -> Result<Resolved> {
+    let resolve_depth = options
+        .max_depth
+        .map(Depth::Restricted)
+        .unwrap_or(Depth::Infinite);
+
+    // Packages are fetched once for every queried package's tree. Trees are resolved in
+    // parallel themselves when recursing, so only one is resolved at a time then
+    let cache = cache::Packages::default();
+    let jobs = if options.recursive { 1 } else { options.jobs() };
+    let per_package = jobs::parallel_map(packages, jobs, |package| {
+        resolve(package, resolve_depth, policy, options, &cache, stream)
+    });
+
+    let per_package = per_package.into_iter().collect::<Result<Vec<_>>>()?;
+
+    let roots = per_package
+        .iter()
+        .map(|graph| {
+            let root = graph.release(*graph.roots().first()?);
+            Some((root.name.clone(), root.vers.clone()))
+        })
+        .collect();
+
+    // Packages shared between the trees of different queried packages are only reported once
+    let mut resolved = DependencyGraph::new();
+    for graph in per_package {
+        resolved.merge(graph);
+    }
+
+    Ok((roots, resolved))
+}
+
+/// Resolve the queried packages together into one consistent set of releases, as cargo would
+fn resolve_consistent(
+    packages: &[String],
+    policy: &SelectionPolicy,
+    options: &Options,
+) -> Result<Resolved> {
+    let dep_kinds = [
+        DependencyKind::Normal,
+        DependencyKind::Build,
+        DependencyKind::Dev,
+    ]
+    .into_iter()
+    .filter(|kind| options.includes_dep_kind(kind));
+
+    resolve_with_dep_kinds(packages, dep_kinds, policy, options)
+}
+
+/// Resolve the queried packages together like [`resolve_consistent`], only following
+/// dependencies of the given kinds
+fn resolve_with_dep_kinds(
+    packages: &[String],
+    dep_kinds: impl IntoIterator<Item = DependencyKind>,
+    policy: &SelectionPolicy,
+    options: &Options,
+) -> Result<Resolved> {
+    let queries = packages
+        .iter()
+        .map(|package| Ok(query(package, options, policy)?.with_features(&options.features)))
+        .collect::<Result<Vec<Query>>>()?;
+    let Some(template) = queries.first() else {
+        return Ok((Vec::new(), DependencyGraph::new()));
+    };
+
+    let fetcher = |name: &str| {
+        let package = template.fetch(name)?;
+        report_warnings(&package);
+        Ok(package)
+    };
+    let mut resolver = Resolver::new(fetcher)
+        .with_policy(policy.clone())
+        .default_features(!options.no_default_features)
+        .with_dep_kinds(dep_kinds);
+    if let Some(target) = &options.target {
+        resolver = resolver.with_target(target.as_str());
+    }
+    let resolution = resolver.resolve(&queries)?;
+
+    let roots = resolution
+        .roots()
+        .map(|root| Some((root.name.clone(), root.vers.clone())))
+        .collect();
+
+    Ok((roots, resolution.into_graph()))
+}
+
+/// Print a value as JSON or YAML if `--type` asks for it, returning whether it was printed
+///
+/// Commands print their own text output otherwise
+fn print_structured<T: Serialize>(value: &T, options: &Options) -> Result<bool> {
+    match (&options.kind, &options.format) {
+        (Some(Type::Json), Format::Pretty) => println!("{}", serde_json::to_string_pretty(value)?),
+        (Some(Type::Json), _) => println!("{}", serde_json::to_string(value)?),
+        (Some(Type::Yaml), _) => print!("{}", yaml::to_string(value)?),
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Return a client for the web API of the registry given with `--index-url`, or crates.io
+fn api_client(options: &Options) -> cargo_lookup::Result<api::Client> {
+    match options.index_url.as_deref() {
+        Some(index_url) => api::Client::from_index(index_url),
+        None => Ok(api::Client::default()),
+    }
+}
+
+/// Return the dependencies of a release, limited to those enabled by `--features`,
+/// `--no-default-features`, and features in the query string for queried packages
+fn enabled_deps<'a>(
+    release: &'a Release,
+    root_features: Option<&[String]>,
+    options: &Options,
+) -> Result<Vec<&'a Dependency>> {
+    let Some(root_features) = root_features else {
+        return Ok(release.deps.iter().collect());
     };
 
-    let deps = result.deps.clone();
+    if !options.feature_aware() && root_features.is_empty() {
+        return Ok(release.deps.iter().collect());
+    }
+
+    let selected = options
+        .features
+        .iter()
+        .chain(root_features)
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+
+    Ok(release
+        .activated_deps(&selected, !options.no_default_features)?
+        .into_iter()
+        .map(|activated| activated.dependency)
+        .collect())
+}
+
+/// Resolve a queried package, and it's dependency tree when recursing
+///
+/// The tree is resolved one level at a time, fetching every package on a level in parallel
+fn resolve(
+    package: &str,
+    depth: Depth,
+    policy: &SelectionPolicy,
+    options: &Options,
+    cache: &cache::Packages,
+    stream: Option<&Stream>,
+) -> Result<DependencyGraph> {
+    let mut tree = Tree::default();
+    let mut level = vec![Pending {
+        package: package.to_owned(),
+        features: options.features.iter().cloned().collect(),
+        default_features: !options.no_default_features,
+    }];
+    let mut remaining = depth;
+    // The first release visited is the queried package itself
+    let mut first_visit = true;
+
+    while !level.is_empty() {
+        let queries = level
+            .iter()
+            .map(|pending| query(&pending.package, options, policy))
+            .collect::<Result<Vec<Query>>>()?;
+        let fetched = jobs::parallel_map(&queries, options.jobs(), |query| cache.fetch(query));
+
+        let follow = options.recursive
+            && (remaining == Depth::Infinite
+                || matches!(remaining, Depth::Restricted(max) if max > 1));
+        let mut next: Vec<Pending> = Vec::new();
+
+        for ((pending, query), fetched) in level.into_iter().zip(&queries).zip(fetched) {
+            let selected = fetched.map(|package| {
+                let selected = tree.unifier.select(&package, query.version_req(), policy);
+                (selected, package)
+            });
+            let selection = match selected {
+                Ok((Some(selection), _)) => selection,
+                _ if options.ignore_missing => continue,
+                Ok((None, package)) => {
+                    return Err(no_match(&pending.package, query, &package, policy, options))
+                }
+                Err(other) if other.is_not_found() && options.index_url.is_none() => {
+                    return Err(not_found(query.name(), other))
+                }
+                Err(other) => return Err(anyhow!(other)),
+            };
+            if let Some(replaced) = &selection.replaced {
+                for pending in tree.replace(replaced, &selection.release) {
+                    if follow && !next.contains(&pending) {
+                        next.push(pending);
+                    }
+                }
+            }
+            let result = selection.release;
 
-    resolved.push(result);
+            let features = pending
+                .features
+                .into_iter()
+                .chain(query.features().iter().cloned())
+                .collect::<BTreeSet<String>>();
+            let visit = Visit {
+                name: normalize_name(&result.name),
+                vers: result.vers.clone(),
+                features,
+                default_features: pending.default_features,
+            };
+            if !tree.visited.insert(visit.clone()) {
+                continue;
+            }
+            if let Some(stream) = stream {
+                stream.emit(&result, options)?;
+            }
+
+            let selected = visit
+                .features
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<&str>>();
+            // Features a dependent asks for may not exist in the release that was selected, but
+            // features asked for on the command line should
+            let root = std::mem::take(&mut first_visit);
+            let activated = match result.activated_deps(&selected, visit.default_features) {
+                Ok(activated) => activated,
+                Err(err) if root => return Err(err.into()),
+                Err(_) => result.activated_deps(&[], visit.default_features)?,
+            };
 
-    if options.recursive
-        && (depth == Depth::Infinite || matches!(depth, Depth::Restricted(max) if max > 1))
-    {
-        let depth = match depth {
+            if follow {
+                for activated in activated {
+                    let sub = activated.dependency;
+                    if !options.follows_dep_kind(&sub.kind, root)
+                        || !options.includes_dep_target(sub)
+                    {
+                        continue;
+                    }
+
+                    tree.edges
+                        .push((result.name.clone(), result.vers.clone(), sub.clone()));
+
+                    let name = sub.package_name();
+                    let version_req = &sub.req;
+
+                    // Stop cyclic dependencies from being infinitely resolved, and skip releases
+                    // that were already visited with every feature this dependency enables
+                    if tree.covers(name, version_req, &activated.features, sub.default_features) {
+                        tree.unifier.require(name, version_req);
+                        continue;
+                    }
+
+                    let pending = Pending {
+                        package: format!("{name}@{version_req}"),
+                        features: activated.features,
+                        default_features: sub.default_features,
+                    };
+                    if !next.contains(&pending) {
+                        next.push(pending);
+                    }
+                }
+            }
+        }
+
+        level = next;
+        remaining = match remaining {
             Depth::Infinite => Depth::Infinite,
-            Depth::Restricted(max) => Depth::Restricted(max - 1),
+            Depth::Restricted(max) => Depth::Restricted(max.saturating_sub(1)),
         };
+    }
 
-        for sub in deps {
-            let name = sub.package.as_deref().unwrap_or(sub.name.as_str());
-            let version_req = sub.req;
-            let sub_query = format!("{name}@{version_req}");
+    // The first release resolved is the queried package itself
+    let mut graph = DependencyGraph::new();
+    let ids = tree
+        .unifier
+        .into_releases()
+        .into_iter()
+        .map(|release| graph.add_release(release))
+        .collect::<Vec<NodeId>>();
+    if let Some(root) = ids.first() {
+        graph.add_root(*root);
+    }
+    for (name, vers, dependency) in tree.edges {
+        if let Some(from) = graph.find(&name, &vers) {
+            graph.connect(from, &dependency);
+        }
+    }
 
-            // Stop cyclic dependencies from being infinitely resolved
-            if resolved
-                .iter()
-                .any(|res| name == res.name && version_req.matches(&res.vers))
-            {
-                continue;
+    // Releases only a replaced release depended on aren't part of the tree anymore
+    if tree.replaced {
+        graph = reachable(&graph);
+    }
+
+    Ok(graph)
+}
+
+/// Return the part of a graph reachable from it's first root
+fn reachable(graph: &DependencyGraph) -> DependencyGraph {
+    let mut reachable = DependencyGraph::new();
+    let Some(root) = graph.roots().first() else {
+        return reachable;
+    };
+
+    let ids = graph
+        .walk(*root)
+        .collect::<BTreeSet<NodeId>>()
+        .into_iter()
+        .map(|id| (id, reachable.add_release(graph.release(id).clone())))
+        .collect::<HashMap<NodeId, NodeId>>();
+    reachable.add_root(ids[root]);
+    for edge in graph.edges() {
+        if let (Some(from), Some(to)) = (ids.get(&edge.from), ids.get(&edge.to)) {
+            reachable.add_edge(*from, *to, edge.dependency.clone());
+        }
+    }
+
+    reachable
+}
+
+/// A package waiting to be resolved, with the features and default features it's dependent
+/// enables
+#[derive(Debug, PartialEq)]
+struct Pending {
+    package: String,
+    features: BTreeSet<String>,
+    default_features: bool,
+}
+
+/// The releases found while resolving a queried package's dependency tree
+#[derive(Debug, Default)]
+struct Tree {
+    /// The releases selected, unifying semver-compatible requirements like cargo
+    unifier: Unifier,
+    visited: HashSet<Visit>,
+    /// Every dependency followed, by the name and version of it's dependent
+    edges: Vec<(String, Version, Dependency)>,
+    /// Whether unifying replaced a release that was already visited
+    replaced: bool,
+}
+
+/// A release visited while resolving a tree, with the features it was visited with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Visit {
+    name: String,
+    vers: Version,
+    features: BTreeSet<String>,
+    default_features: bool,
+}
+
+impl Tree {
+    /// Whether a release matching a requirement was already visited with at least the given
+    /// features enabled
+    fn covers(
+        &self,
+        name: &str,
+        req: &VersionReq,
+        features: &BTreeSet<String>,
+        default_features: bool,
+    ) -> bool {
+        let name = normalize_name(name);
+
+        self.visited.iter().any(|visit| {
+            visit.name == name
+                && req.matches(&visit.vers)
+                && visit.features.is_superset(features)
+                && (visit.default_features || !default_features)
+        })
+    }
+
+    /// Forget a release that unifying replaced, returning the packages to resolve so the
+    /// replacement is visited with every feature the replaced release was
+    ///
+    /// The replacement may declare different dependencies, so the ones followed from the
+    /// replaced release are dropped, to be followed again from the replacement
+    fn replace(&mut self, replaced: &Release, replacement: &Release) -> Vec<Pending> {
+        self.replaced = true;
+        let name = normalize_name(&replaced.name);
+        self.edges
+            .retain(|(from, vers, _)| normalize_name(from) != name || *vers != replaced.vers);
+
+        let mut pending = Vec::new();
+        self.visited.retain(|visit| {
+            if visit.name != name || visit.vers != replaced.vers {
+                return true;
             }
 
-            resolve(&sub_query, index, depth, options, resolved)?;
+            pending.push(Pending {
+                package: format!("{}@={}", replacement.name, replacement.vers),
+                features: visit.features.clone(),
+                default_features: visit.default_features,
+            });
+            false
+        });
+
+        pending
+    }
+}
+
+/// Build a query for a package spec using the index and selection policy given on the command line
+fn query(package: &str, options: &Options, policy: &SelectionPolicy) -> Result<Query> {
+    let query: Query = match options.index_url.as_deref() {
+        Some(custom) => package.parse::<Query>()?.with_index(custom),
+        None => package.parse()?,
+    };
+
+    let query = query
+        .with_policy(policy.clone())
+        .lenient(options.lenient)
+        .separator_fallback(true);
+
+    Ok(match options.cross_check.as_deref() {
+        Some(mirror) => query.with_cross_check(mirror),
+        None => query,
+    })
+}
+
+/// The error for a query that no release matches, pointing out `--include-prerelease` when a
+/// pre-release would have matched
+fn no_match(
+    spec: &str,
+    query: &Query,
+    package: &Package,
+    policy: &SelectionPolicy,
+    options: &Options,
+) -> anyhow::Error {
+    let with_prereleases = policy.clone().include_prereleases(true);
+    match package.select(query.version_req(), &with_prereleases) {
+        Some(release) if !options.include_prerelease => anyhow!(
+            "failed to find a matching release of `{spec}`, but pre-release {} matches, \
+             pass `--include-prerelease` to select it",
+            release.vers
+        ),
+        _ => anyhow!("failed to find a matching release of `{spec}`"),
+    }
+}
+
+/// Add "did you mean" suggestions from crates.io to an error for a package that doesn't exist
+fn not_found(name: &str, error: cargo_lookup::error::Error) -> anyhow::Error {
+    // Suggestions are best-effort, the original error is still reported if they can't be found
+    let suggestions = suggest::suggestions(name, 3).unwrap_or_default();
+
+    match suggestions.as_slice() {
+        [] => anyhow!(error),
+        [suggestion] => anyhow!("{error}\n\ndid you mean `{suggestion}`?"),
+        suggestions => {
+            let suggestions = suggestions
+                .iter()
+                .map(|suggestion| format!("`{suggestion}`"))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            anyhow!("{error}\n\ndid you mean one of {suggestions}?")
         }
     }
+}
 
-    Ok(())
+/// Print any index lines that were skipped while leniently parsing a package
+fn report_warnings(package: &Package) {
+    let name = package.name();
+
+    for warning in package.warnings() {
+        eprintln!("warning: {name}: {warning}");
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -0,0 +1,495 @@
+//! Dependencies declared in a `Cargo.toml`
+//!
+//! A [`Manifest`] can be deserialized from the TOML of a `Cargo.toml` with a crate like `toml`.
+//! Its registry dependencies, from every dependency table including target-specific ones, are
+//! available as [`Dependency`]s from [`Manifest::dependencies`], ready to be looked up with
+//! [`Dependency::query_spec`].
+//!
+//! Path and git dependencies aren't in an index, so they're left out. Dependencies inherited with
+//! `workspace = true` are filled in from the workspace root's `[workspace.dependencies]` by
+//! [`Manifest::dependencies_in`], and left out by [`Manifest::dependencies`]
+//!
+//! Dependencies from another registry are kept, with the registry's name as written in the
+//! manifest in [`Dependency::registry`], so they can be told apart from crates.io ones
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{manifest::Manifest, Query, Result};
+//!
+//! fn latest(manifest: &Manifest) -> Result<()> {
+//!     for dependency in manifest.dependencies()? {
+//!         let query = dependency.query_spec().parse::<Query>()?;
+//!
+//!         if let Some(release) = query.submit()? {
+//!             println!("{} = \"{}\"", dependency.name, release.vers);
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{error::Error, Dependency, DependencyKind, Release, Result};
+use semver::{Comparator, Op, Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The dependency tables of a `Cargo.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The `[dependencies]` table
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+    /// The `[dev-dependencies]` table
+    #[serde(
+        default,
+        rename = "dev-dependencies",
+        alias = "dev_dependencies",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub dev_dependencies: BTreeMap<String, DependencySpec>,
+    /// The `[build-dependencies]` table
+    #[serde(
+        default,
+        rename = "build-dependencies",
+        alias = "build_dependencies",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub build_dependencies: BTreeMap<String, DependencySpec>,
+    /// The dependency tables under `[target.<cfg>]`, by target
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target: BTreeMap<String, TargetDependencies>,
+    /// The `[workspace]` table, if this is the root of a workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+}
+
+/// The `[workspace]` table of a workspace root's `Cargo.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Paths of the member packages, relative to the workspace root. May contain globs, like
+    /// `crates/*`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+    /// Paths to leave out of the members, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// The `[workspace.dependencies]` table, which members inherit from with `workspace = true`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+}
+
+/// The dependency tables of one `[target.<cfg>]` table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetDependencies {
+    /// The `[target.<cfg>.dependencies]` table
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+    /// The `[target.<cfg>.dev-dependencies]` table
+    #[serde(
+        default,
+        rename = "dev-dependencies",
+        alias = "dev_dependencies",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub dev_dependencies: BTreeMap<String, DependencySpec>,
+    /// The `[target.<cfg>.build-dependencies]` table
+    #[serde(
+        default,
+        rename = "build-dependencies",
+        alias = "build_dependencies",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub build_dependencies: BTreeMap<String, DependencySpec>,
+}
+
+/// A dependency as written in a manifest, either just a version requirement or a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DependencySpec {
+    /// A version requirement, like `serde = "1.0"`
+    Version(String),
+    /// A table, like `serde = { version = "1.0", features = ["derive"] }`
+    Detailed(DetailedDependency),
+}
+
+impl DependencySpec {
+    /// Return the dependency as a table, so both forms can be handled alike
+    pub fn to_detailed(&self) -> DetailedDependency {
+        match self {
+            DependencySpec::Version(version) => DetailedDependency {
+                version: Some(version.clone()),
+                ..DetailedDependency::default()
+            },
+            DependencySpec::Detailed(detailed) => detailed.clone(),
+        }
+    }
+}
+
+/// A dependency written as a table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DetailedDependency {
+    /// The version requirement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The name of the package, when the dependency is renamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// Features enabled on the dependency
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Whether the dependency's default features are enabled. Defaults to `true`
+    #[serde(
+        default,
+        alias = "default_features",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_features: Option<bool>,
+    /// Whether the dependency is optional
+    #[serde(default)]
+    pub optional: bool,
+    /// The path of a local dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The repository of a git dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The name of the registry the dependency is from, if it isn't crates.io
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// Whether the dependency is inherited from the workspace's `[workspace.dependencies]`
+    #[serde(default)]
+    pub workspace: bool,
+}
+
+impl Manifest {
+    /// Return the registry dependencies declared in the manifest, from the top-level tables
+    /// first and then each target's tables
+    ///
+    /// Fails if a dependency has a version requirement that doesn't parse
+    pub fn dependencies(&self) -> Result<Vec<Dependency>> {
+        self.collect_dependencies(None)
+    }
+
+    /// Return the registry dependencies declared in the manifest of a workspace member, like
+    /// [`Manifest::dependencies`], filling in those inherited from the workspace
+    ///
+    /// Inherited dependencies take their requirement, package, and default features from the
+    /// workspace, and features from both. Those the workspace doesn't declare are left out
+    pub fn dependencies_in(&self, workspace: &Workspace) -> Result<Vec<Dependency>> {
+        self.collect_dependencies(Some(workspace))
+    }
+
+    /// Return the names of the dependencies inherited from the workspace with
+    /// `workspace = true`, as they're written in the manifest
+    pub fn inherited(&self) -> Vec<&str> {
+        self.tables()
+            .into_iter()
+            .flat_map(|(_, table, _)| table)
+            .filter(|(_, spec)| spec.to_detailed().workspace)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    fn collect_dependencies(&self, workspace: Option<&Workspace>) -> Result<Vec<Dependency>> {
+        let mut dependencies = Vec::new();
+        for (target, table, kind) in self.tables() {
+            collect(table, kind, target, workspace, &mut dependencies)?;
+        }
+
+        Ok(dependencies)
+    }
+
+    /// Return every dependency table, with it's target and kind, the top-level tables first
+    fn tables(&self) -> Vec<Table<'_>> {
+        let mut tables = vec![
+            (None, &self.dependencies, DependencyKind::Normal),
+            (None, &self.build_dependencies, DependencyKind::Build),
+            (None, &self.dev_dependencies, DependencyKind::Dev),
+        ];
+        for (target, deps) in &self.target {
+            let target = Some(target.as_str());
+            tables.extend([
+                (target, &deps.dependencies, DependencyKind::Normal),
+                (target, &deps.build_dependencies, DependencyKind::Build),
+                (target, &deps.dev_dependencies, DependencyKind::Dev),
+            ]);
+        }
+
+        tables
+    }
+}
+
+/// A dependency table of a manifest, with the target it's for and the kind of it's dependencies
+type Table<'a> = (
+    Option<&'a str>,
+    &'a BTreeMap<String, DependencySpec>,
+    DependencyKind,
+);
+
+/// A new version requirement for a dependency, see [`update_requirements`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequirementUpdate {
+    /// The name of the dependency as written in the manifest, which differs from the package's
+    /// name when it's renamed
+    pub name: String,
+    /// The requirement to replace. Declarations of the dependency with other requirements are
+    /// left as they are
+    pub from: VersionReq,
+    /// The new requirement, as it should be written
+    pub to: String,
+}
+
+impl Dependency {
+    /// Write the dependency as it would be declared in a `Cargo.toml` dependency table, like
+    /// `serde = { version = "1.0.219", features = ["derive"] }`
+    ///
+    /// Caret requirements are written without the `^`, as `cargo add` does. The dependency's
+    /// kind and target decide which table it belongs in, so they aren't part of the line
+    pub fn to_manifest_line(&self) -> String {
+        let version = manifest_requirement(&self.req);
+        let mut fields = vec![format!("version = \"{version}\"")];
+
+        if let Some(package) = &self.package {
+            fields.push(format!("package = \"{package}\""));
+        }
+        if !self.default_features {
+            fields.push("default-features = false".to_owned());
+        }
+        if !self.features.is_empty() {
+            let features = self
+                .features
+                .iter()
+                .map(|feature| format!("\"{feature}\""))
+                .collect::<Vec<String>>();
+            fields.push(format!("features = [{}]", features.join(", ")));
+        }
+        if self.optional {
+            fields.push("optional = true".to_owned());
+        }
+
+        match fields.as_slice() {
+            [_] => format!("{} = \"{version}\"", self.name),
+            fields => format!("{} = {{ {} }}", self.name, fields.join(", ")),
+        }
+    }
+}
+
+impl Release {
+    /// Return a dependency on this release, as `cargo add` would declare it
+    ///
+    /// The dependency has a caret requirement on the release's version and no features beyond
+    /// the default ones. See [`Dependency::to_manifest_line`]
+    pub fn to_dependency(&self) -> Dependency {
+        Dependency {
+            name: self.name.clone(),
+            req: caret_requirement(&self.vers),
+            features: Vec::new(),
+            optional: false,
+            default_features: true,
+            target: None,
+            kind: DependencyKind::Normal,
+            registry: None,
+            package: None,
+        }
+    }
+}
+
+/// Return a caret requirement on a version, e.g. `^1.0.219` for `1.0.219`
+pub fn caret_requirement(version: &Version) -> VersionReq {
+    VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Caret,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+            pre: version.pre.clone(),
+        }],
+    }
+}
+
+/// Write a requirement as it's usually written in a manifest, leaving the `^` off a single caret
+/// requirement
+pub fn manifest_requirement(req: &VersionReq) -> String {
+    let req = req.to_string();
+
+    match req.strip_prefix('^') {
+        Some(version) if !version.contains(',') => version.to_owned(),
+        _ => req,
+    }
+}
+
+/// Replace the version requirements of dependencies in the text of a `Cargo.toml`, keeping the
+/// rest of it exactly as written
+///
+/// Requirements are updated in every dependency table, including `[workspace.dependencies]` and
+/// those under `[target.<cfg>]`, whether written as `name = "1.0"`, as an inline table, or as a
+/// `[dependencies.name]` table. Requirements that don't parse are left alone
+pub fn update_requirements(contents: &str, updates: &[RequirementUpdate]) -> String {
+    // The dependency whose table is being read, for `[dependencies.name]` tables
+    let mut section: Option<String> = None;
+    let mut in_table = false;
+    let mut updated = String::with_capacity(contents.len());
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') {
+            let header = trimmed
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim();
+            in_table = header.ends_with("dependencies");
+            section = header
+                .rsplit_once("dependencies.")
+                .map(|(_, name)| unquote(name).to_owned());
+            updated.push_str(line);
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            updated.push_str(line);
+            continue;
+        };
+        let key = unquote(key.trim());
+
+        let (name, value) = match &section {
+            Some(name) if key == "version" => (name.as_str(), value),
+            None if in_table => match value.trim_start().strip_prefix('{') {
+                Some(table) => (key, inline_version(table).unwrap_or("")),
+                None => (key, value),
+            },
+            _ => ("", ""),
+        };
+
+        let replaced = updates
+            .iter()
+            .filter(|update| update.name == name)
+            .find_map(|update| replace_requirement(line, value, update));
+        updated.push_str(replaced.as_deref().unwrap_or(line));
+    }
+
+    updated
+}
+
+/// Replace the quoted requirement at the start of `value`, a suffix of `line`, if it's the one
+/// being updated
+fn replace_requirement(line: &str, value: &str, update: &RequirementUpdate) -> Option<String> {
+    let quoted = value.trim_start();
+    let quote = quoted
+        .chars()
+        .next()
+        .filter(|quote| matches!(quote, '"' | '\''))?;
+    let req = quoted[1..].split(quote).next()?;
+
+    if VersionReq::parse(req).ok()? != update.from {
+        return None;
+    }
+
+    let start = line.len() - quoted.len() + 1;
+    let end = start + req.len();
+    Some(format!("{}{}{}", &line[..start], update.to, &line[end..]))
+}
+
+/// Return the value of the `version` key of an inline table, as a suffix of `table`, which
+/// starts after the table's opening `{`
+///
+/// The table is split into it's entries on the commas outside of strings, arrays, and nested
+/// tables, so only a key that is exactly `version` matches, not a feature or package name
+/// containing it
+fn inline_version(table: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, char) in table.char_indices() {
+        match (quote, char) {
+            (Some('"'), _) if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (Some(open), _) if char == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(char),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') if depth > 0 => depth -= 1,
+            (None, ',' | '}') if depth == 0 => {
+                if let Some(value) = entry_value(table, start, index, "version") {
+                    return Some(value);
+                }
+                if char == '}' {
+                    return None;
+                }
+                start = index + 1;
+            }
+            (None, _) => {}
+        }
+    }
+
+    None
+}
+
+/// Return the value of an inline table entry spanning `start..end` of `table` if it's key is
+/// `key`, as a suffix of `table`
+fn entry_value<'a>(table: &'a str, start: usize, end: usize, key: &str) -> Option<&'a str> {
+    let (entry_key, _) = table[start..end].split_once('=')?;
+
+    (unquote(entry_key.trim()) == key).then(|| &table[start + entry_key.len() + 1..])
+}
+
+/// Remove the quotes around a TOML key, if it has them
+fn unquote(key: &str) -> &str {
+    key.trim_matches(|char| char == '"' || char == '\'')
+}
+
+/// Add the registry dependencies of one dependency table
+fn collect(
+    table: &BTreeMap<String, DependencySpec>,
+    kind: DependencyKind,
+    target: Option<&str>,
+    workspace: Option<&Workspace>,
+    dependencies: &mut Vec<Dependency>,
+) -> Result<()> {
+    for (name, spec) in table {
+        let mut detailed = spec.to_detailed();
+
+        if detailed.workspace {
+            let Some(inherited) = workspace.and_then(|workspace| workspace.dependencies.get(name))
+            else {
+                continue;
+            };
+
+            let mut inherited = inherited.to_detailed();
+            inherited.features.extend(detailed.features);
+            inherited.optional = detailed.optional;
+            detailed = inherited;
+        }
+
+        if detailed.path.is_some() || detailed.git.is_some() {
+            continue;
+        }
+
+        // Like cargo, a missing requirement allows any version
+        let req = match detailed.version.as_deref() {
+            Some(version) => VersionReq::parse(version).map_err(Error::InvalidVersion)?,
+            None => VersionReq::STAR,
+        };
+
+        dependencies.push(Dependency {
+            name: name.clone(),
+            req,
+            features: detailed.features,
+            optional: detailed.optional,
+            default_features: detailed.default_features.unwrap_or(true),
+            target: target.map(ToOwned::to_owned),
+            kind: kind.clone(),
+            registry: detailed.registry,
+            package: detailed.package,
+        });
+    }
+
+    Ok(())
+}
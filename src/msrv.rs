@@ -0,0 +1,81 @@
+//! The minimum supported rust version of a whole dependency tree
+//!
+//! A package can only be built with a toolchain every release in it's dependency tree supports,
+//! so the tree's MSRV is the highest `rust_version` of any of them. See [`DependencyGraph::msrv`]
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{resolve::resolve, Query, Result};
+//! use semver::Version;
+//!
+//! fn main() -> Result<()> {
+//!     let resolution = resolve(&["tokio@^1".parse::<Query>()?])?;
+//!     let graph = resolution.graph();
+//!     let msrv = graph.msrv();
+//!
+//!     if !msrv.supports(&Version::new(1, 70, 0)) {
+//!         for id in &msrv.imposed_by {
+//!             let release = graph.release(*id);
+//!             println!("{} {} needs a newer rust", release.name, release.vers);
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::graph::{DependencyGraph, NodeId};
+use semver::Version;
+
+/// The minimum supported rust version of a dependency graph, see [`DependencyGraph::msrv`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Msrv {
+    /// The highest `rust_version` of any release in the graph, or `None` if none declare one
+    pub rust_version: Option<Version>,
+    /// The releases declaring that `rust_version`
+    pub imposed_by: Vec<NodeId>,
+    /// The releases without a `rust_version`, which may need a newer toolchain than they say
+    pub unspecified: Vec<NodeId>,
+}
+
+impl Msrv {
+    /// Whether a rust toolchain version can build every release in the graph, as far as their
+    /// `rust_version`s tell. Pre-release toolchains count as the version they precede
+    pub fn supports(&self, toolchain: &Version) -> bool {
+        let toolchain = Version::new(toolchain.major, toolchain.minor, toolchain.patch);
+
+        self.rust_version
+            .as_ref()
+            .map_or(true, |rust_version| &toolchain >= rust_version)
+    }
+}
+
+impl DependencyGraph {
+    /// Return the minimum supported rust version of every release in the graph together, and
+    /// the releases that impose it
+    ///
+    /// Releases without a `rust_version` don't constrain the toolchain, and are listed in
+    /// [`Msrv::unspecified`]. See [`Release::minimum_rust_version`](crate::Release::minimum_rust_version)
+    pub fn msrv(&self) -> Msrv {
+        let mut msrv = Msrv::default();
+
+        for (id, release) in self.nodes() {
+            let Some(rust_version) = release.minimum_rust_version() else {
+                msrv.unspecified.push(id);
+                continue;
+            };
+
+            match &msrv.rust_version {
+                Some(highest) if *highest > rust_version => {}
+                Some(highest) if *highest == rust_version => msrv.imposed_by.push(id),
+                _ => {
+                    msrv.rust_version = Some(rust_version);
+                    msrv.imposed_by = vec![id];
+                }
+            }
+        }
+
+        msrv
+    }
+}
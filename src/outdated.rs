@@ -0,0 +1,93 @@
+//! How far a dependency is behind the releases of it's package
+//!
+//! Like `cargo outdated`, three versions are compared: the one in use, the newest one the
+//! dependency's requirement allows, and the newest one overall. See [`Package::outdated`]
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{Query, Result, SelectionPolicy};
+//!
+//! fn main() -> Result<()> {
+//!     let query = "serde@^1.0.100".parse::<Query>()?;
+//!     let req = query.version_req().cloned().unwrap_or_default();
+//!     let outdated = query.package()?.outdated(&req, None, &SelectionPolicy::default());
+//!
+//!     if let (true, Some(latest)) = (outdated.is_outdated(), &outdated.latest) {
+//!         println!("serde can be updated to {latest}");
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{manifest::caret_requirement, Package, SelectionPolicy};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+
+/// The versions of a package a dependency could use, see [`Package::outdated`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Outdated {
+    /// The name of the package
+    pub name: String,
+    /// The dependency's version requirement
+    pub req: VersionReq,
+    /// The version in use: the locked version if there is one, otherwise the newest release
+    /// matching the requirement
+    pub current: Option<Version>,
+    /// The newest release matching the requirement
+    pub compatible: Option<Version>,
+    /// The newest release
+    pub latest: Option<Version>,
+}
+
+impl Outdated {
+    /// Whether there's a newer release than the one in use
+    pub fn is_outdated(&self) -> bool {
+        match (&self.current, &self.latest) {
+            (Some(current), Some(latest)) => latest > current,
+            (None, latest) => latest.is_some(),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Return a requirement on the newest release, for dependencies with a newer release than
+    /// the one in use
+    ///
+    /// This is a caret requirement, which `cargo add` would write without the `^`, see
+    /// [`manifest_requirement`](crate::manifest::manifest_requirement)
+    pub fn suggested_req(&self) -> Option<VersionReq> {
+        let latest = self.latest.as_ref().filter(|_| self.is_outdated())?;
+
+        Some(caret_requirement(latest))
+    }
+}
+
+impl Package {
+    /// Compare a requirement on this package, and the version locked for it if there is one,
+    /// with the package's releases
+    ///
+    /// Releases are selected with `policy`, so yanked and pre-release versions are skipped unless
+    /// it allows them. A locked version is taken as is, even if it's been yanked since
+    pub fn outdated(
+        &self,
+        req: &VersionReq,
+        locked: Option<&Version>,
+        policy: &SelectionPolicy,
+    ) -> Outdated {
+        let compatible = self
+            .select(Some(req), policy)
+            .map(|release| release.vers.clone());
+        let latest = self
+            .select(None, policy)
+            .map(|release| release.vers.clone());
+
+        Outdated {
+            name: self.name().to_owned(),
+            req: req.clone(),
+            current: locked.cloned().or_else(|| compatible.clone()),
+            compatible,
+            latest,
+        }
+    }
+}
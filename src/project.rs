@@ -0,0 +1,257 @@
+//! Loading the manifests and lockfiles of a local cargo project or workspace
+
+use crate::{cli::Options, registry};
+use anyhow::{bail, Context, Result};
+use cargo_lookup::{
+    lockfile::Lockfile,
+    manifest::{Manifest, Workspace},
+    Dependency,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Read and parse a `Cargo.toml`
+pub fn read_manifest(path: &Path) -> Result<Manifest> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    toml::from_str(&contents).with_context(|| format!("failed to parse `{}`", path.display()))
+}
+
+/// Read and parse a `Cargo.lock`
+pub fn read_lockfile(path: &Path) -> Result<Lockfile> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    toml::from_str(&contents).with_context(|| format!("failed to parse `{}`", path.display()))
+}
+
+/// Return a query string for each registry dependency declared in a `Cargo.toml`, see
+/// [`manifest_dependencies`]
+pub fn manifest_queries(path: &Path, options: &Options) -> Result<Vec<String>> {
+    let dependencies = manifest_dependencies(path, options)?;
+
+    Ok(dependencies.iter().map(Dependency::query_spec).collect())
+}
+
+/// Return the registry dependencies declared in a `Cargo.toml`, leaving out those excluded by
+/// `--dep-kind`, `--no-dev-deps`, or `--target`
+///
+/// When the manifest is a workspace root, the dependencies of every member are included too.
+/// A package declared more than once, e.g. by two members, is only included once for each
+/// distinct requirement and feature list
+pub fn manifest_dependencies(path: &Path, options: &Options) -> Result<Vec<Dependency>> {
+    let manifests = manifest_paths(path)?
+        .into_iter()
+        .map(|path| Ok((read_manifest(&path)?, path)))
+        .collect::<Result<Vec<(Manifest, PathBuf)>>>()?;
+    let workspace = match &manifests[0].0.workspace {
+        Some(workspace) => Some(workspace.clone()),
+        None => find_workspace(path),
+    };
+
+    let mut included = Vec::new();
+    let mut dependencies = Vec::new();
+    for (manifest, path) in manifests {
+        let declared = match &workspace {
+            Some(workspace) => manifest.dependencies_in(workspace),
+            None => manifest.dependencies(),
+        }
+        .with_context(|| format!("invalid dependency in `{}`", path.display()))?;
+
+        for dependency in declared {
+            if !options.includes_dep_kind(&dependency.kind)
+                || !options.includes_dep_target(&dependency)
+            {
+                continue;
+            }
+
+            if let Some(registry) = &dependency.registry {
+                if !is_queried_registry(registry, options) {
+                    eprintln!(
+                        "warning: skipping `{}` from registry `{registry}`, pass `--registry {registry}` to look it up",
+                        dependency.name
+                    );
+                    continue;
+                }
+            }
+
+            let query = dependency.query_spec();
+            if !included.contains(&query) {
+                included.push(query);
+                dependencies.push(dependency);
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Return whether a registry named in a manifest is the one being queried, either by name or
+/// by its configured index URL
+fn is_queried_registry(name: &str, options: &Options) -> bool {
+    if options.registry.as_deref() == Some(name) {
+        return true;
+    }
+
+    let normalize = |url: &str| {
+        url.trim_start_matches("sparse+")
+            .trim_end_matches('/')
+            .to_owned()
+    };
+    match (registry::index_url(name), &options.index_url) {
+        (Ok(index_url), Some(queried)) => normalize(&index_url) == normalize(queried),
+        _ => false,
+    }
+}
+
+/// Return the path of a `Cargo.toml`, followed by the manifests of the workspace's members if
+/// it's a workspace root
+pub fn manifest_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![path.to_owned()];
+
+    if let Some(workspace) = read_manifest(path)?.workspace {
+        let root = path.parent().unwrap_or(Path::new("."));
+        for member in workspace_members(root, &workspace)? {
+            if member != path {
+                paths.push(member);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Find the `Cargo.lock` of a package, which is next to it's manifest or, for a workspace
+/// member, next to the workspace root's
+pub fn find_lockfile(manifest_path: &Path) -> Option<PathBuf> {
+    let dir = manifest_path.canonicalize().ok()?;
+
+    dir.ancestors()
+        .skip(1)
+        .map(|ancestor| ancestor.join("Cargo.lock"))
+        .find(|path| path.is_file())
+}
+
+/// Return the manifest paths of a workspace's members, expanding globs in `members` and leaving
+/// out `exclude`d paths
+pub fn workspace_members(root: &Path, workspace: &Workspace) -> Result<Vec<PathBuf>> {
+    let excluded = workspace
+        .exclude
+        .iter()
+        .flat_map(|pattern| expand(root, pattern))
+        .collect::<Vec<PathBuf>>();
+
+    let mut members = Vec::new();
+    for pattern in &workspace.members {
+        for dir in expand(root, pattern) {
+            let manifest = dir.join("Cargo.toml");
+            if excluded.contains(&dir) || members.contains(&manifest) {
+                continue;
+            }
+
+            if !manifest.is_file() {
+                // Like cargo, directories matched by a glob are skipped if they aren't packages
+                if has_glob(pattern) {
+                    continue;
+                }
+                bail!("workspace member `{}` has no `Cargo.toml`", dir.display());
+            }
+            members.push(manifest);
+        }
+    }
+
+    Ok(members)
+}
+
+/// Find the workspace a package is a member of, by looking for a workspace root in the
+/// directories above it's manifest
+fn find_workspace(manifest_path: &Path) -> Option<Workspace> {
+    find_workspace_root(manifest_path).map(|(_, workspace)| workspace)
+}
+
+/// Return the path of the workspace root a member's manifest is in, along with the workspace
+fn find_workspace_root(manifest_path: &Path) -> Option<(PathBuf, Workspace)> {
+    let dir = manifest_path.canonicalize().ok()?;
+
+    dir.ancestors().skip(2).find_map(|ancestor| {
+        let path = ancestor.join("Cargo.toml");
+        if !path.is_file() {
+            return None;
+        }
+        Some((path.clone(), read_manifest(&path).ok()?.workspace?))
+    })
+}
+
+/// Return the path of the workspace root's manifest, if the manifest is a workspace member
+/// rather than the root itself
+pub fn workspace_root(manifest_path: &Path) -> Result<Option<PathBuf>> {
+    if read_manifest(manifest_path)?.workspace.is_some() {
+        return Ok(None);
+    }
+
+    Ok(find_workspace_root(manifest_path).map(|(path, _)| path))
+}
+
+/// Expand a path relative to a directory, where any component may contain `*` and `?`
+/// wildcards, returning the existing directories it matches
+fn expand(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![root.to_owned()];
+
+    for component in pattern
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+    {
+        if !has_glob(component) {
+            paths = paths.into_iter().map(|path| path.join(component)).collect();
+            continue;
+        }
+
+        let mut matched = Vec::new();
+        for path in paths {
+            let Ok(entries) = fs::read_dir(&path) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                if is_dir && matches_wildcard(component, &name.to_string_lossy()) {
+                    matched.push(entry.path());
+                }
+            }
+        }
+        matched.sort();
+        paths = matched;
+    }
+
+    paths
+}
+
+/// Whether a path pattern contains a wildcard
+fn has_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Whether a name matches a pattern where `*` matches any run of characters and `?` matches one
+fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+            }
+            (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+            (Some(expected), Some(actual)) if expected == actual => {
+                matches(&pattern[1..], &name[1..])
+            }
+            _ => false,
+        }
+    }
+
+    matches(&pattern, &name)
+}
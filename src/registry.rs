@@ -0,0 +1,71 @@
+//! Looking up the index URL of a named registry from cargo's configuration
+//!
+//! This follows the same precedence as cargo: the `CARGO_REGISTRIES_<NAME>_INDEX` environment
+//! variable first, then `.cargo/config.toml` files from the current directory upwards, and
+//! finally the config in `CARGO_HOME`
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::{env, fs, path::PathBuf};
+
+/// Return the index URL configured for a named registry, as given to `--registry`
+pub fn index_url(name: &str) -> Result<String> {
+    let var = format!(
+        "CARGO_REGISTRIES_{}_INDEX",
+        name.to_uppercase().replace('-', "_")
+    );
+
+    let index = match env::var(&var) {
+        Ok(index) => index,
+        Err(_) => from_config(name)?
+            .ok_or_else(|| anyhow!("no index is configured for registry `{name}`"))?,
+    };
+
+    if !index.starts_with("sparse+") {
+        bail!("registry `{name}` uses a git index, only sparse indexes are supported: {index}");
+    }
+
+    Ok(index)
+}
+
+fn from_config(name: &str) -> Result<Option<String>> {
+    for path in config_paths() {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let config: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse `{}`", path.display()))?;
+
+        let index = config
+            .get("registries")
+            .and_then(|registries| registries.get(name))
+            .and_then(|registry| registry.get("index"))
+            .and_then(|index| index.as_str());
+
+        if let Some(index) = index {
+            return Ok(Some(index.to_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Cargo config files in the order they take precedence
+fn config_paths() -> Vec<PathBuf> {
+    let mut dirs = env::current_dir()
+        .map(|cwd| {
+            cwd.ancestors()
+                .map(|dir| dir.join(".cargo"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")));
+    dirs.extend(cargo_home);
+
+    dirs.into_iter()
+        .flat_map(|dir| [dir.join("config.toml"), dir.join("config")])
+        .collect()
+}
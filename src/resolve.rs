@@ -0,0 +1,672 @@
+//! Dependency resolution, selecting one consistent set of releases for a set of root packages
+//!
+//! Like cargo, the resolver unifies semver-compatible requirements: every requirement on a
+//! package that can be satisfied by the same release is, so `serde ^1.0.100` and `serde ^1.0.50`
+//! both resolve to one `serde 1.x`. Incompatible versions, like `rand 0.7` and `rand 0.8`, may
+//! both be in a resolution. Releases are tried newest first, backtracking when a choice leaves
+//! another requirement unsatisfiable.
+//!
+//! Only normal and build dependencies are followed by default, see [`Resolver::with_dep_kinds`],
+//! and optional dependencies only when a feature
+//! enables them. Features are unified too: a release is built with every feature any of it's
+//! dependents enables, starting from the features selected by each root query along with it's
+//! default features, see [`Resolver::default_features`]. Dependencies for every target are
+//! followed, unless the resolution is for one target, see [`Resolver::with_target`]
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{resolve::resolve, Query, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let roots = ["tokio@^1".parse::<Query>()?, "hyper@^1".parse()?];
+//!
+//!     for release in resolve(&roots)?.releases() {
+//!         println!("{} {}", release.name, release.vers);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    compat::compat,
+    error::Error,
+    graph::{DependencyGraph, NodeId},
+    normalize_name, Dependency, DependencyKind, Package, Query, Release, Result, SelectionPolicy,
+    CRATES_IO_INDEX_URL,
+};
+use semver::{Version, VersionReq};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    rc::Rc,
+};
+
+/// A source of packages for the resolver
+///
+/// This is implemented for [`Query`], fetching packages from the same index as the query, and
+/// for closures, which is useful for resolving against packages that are already on hand
+pub trait Fetch {
+    /// Fetch a package by name
+    fn fetch(&self, name: &str) -> Result<Package>;
+}
+
+impl<F> Fetch for F
+where
+    F: Fn(&str) -> Result<Package>,
+{
+    fn fetch(&self, name: &str) -> Result<Package> {
+        self(name)
+    }
+}
+
+/// Fetches packages from the query's index, with the same mirror and leniency settings
+impl Fetch for Query {
+    fn fetch(&self, name: &str) -> Result<Package> {
+        Query {
+            name: name.to_owned(),
+            version_req: None,
+            features: Vec::new(),
+            ..self.clone()
+        }
+        .package()
+    }
+}
+
+/// Resolve the dependency trees of a set of root packages, see the [module docs](self)
+///
+/// Packages are fetched from the first root's index, and releases are selected with it's
+/// [`SelectionPolicy`]. See [`Resolver`] for more control
+pub fn resolve(roots: &[Query]) -> Result<Resolution> {
+    let Some(first) = roots.first() else {
+        return Ok(Resolution::default());
+    };
+
+    Resolver::new(first.clone())
+        .with_policy(first.policy.clone())
+        .resolve(roots)
+}
+
+/// A configurable dependency resolver
+pub struct Resolver<F> {
+    fetcher: F,
+    policy: SelectionPolicy,
+    target: Option<String>,
+    default_features: bool,
+    dep_kinds: Vec<DependencyKind>,
+    packages: RefCell<HashMap<String, Rc<Package>>>,
+}
+
+impl<F: Fetch> Resolver<F> {
+    /// Create a resolver fetching packages from `fetcher`, with the default [`SelectionPolicy`]
+    pub fn new(fetcher: F) -> Self {
+        Self {
+            fetcher,
+            policy: SelectionPolicy::default(),
+            target: None,
+            default_features: true,
+            dep_kinds: vec![DependencyKind::Normal, DependencyKind::Build],
+            packages: RefCell::default(),
+        }
+    }
+
+    /// Set the policy deciding which releases may be selected
+    pub fn with_policy(mut self, policy: SelectionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Only follow dependencies used when building for a target triple, e.g.
+    /// `x86_64-unknown-linux-gnu`, see [`Dependency::applies_to`](crate::Dependency::applies_to)
+    pub fn with_target<T>(mut self, triple: T) -> Self
+    where
+        String: From<T>,
+    {
+        self.target = Some(String::from(triple));
+        self
+    }
+
+    /// Set whether the `default` feature of the root packages is enabled, which it is unless
+    /// this is set to `false`
+    ///
+    /// Dependencies of the roots enable default features as their dependents declare
+    pub fn default_features(mut self, enabled: bool) -> Self {
+        self.default_features = enabled;
+        self
+    }
+
+    /// Only follow dependencies of these kinds, which are normal and build dependencies unless
+    /// this is set
+    ///
+    /// Like cargo, dev dependencies are only ever followed for the root packages, since the dev
+    /// dependencies of a dependency are never built
+    pub fn with_dep_kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = DependencyKind>,
+    {
+        self.dep_kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// Resolve the dependency trees of the root packages
+    ///
+    /// The version requirement and features of each root query are respected. Fails with
+    /// [`Error::UnknownFeature`] if no release a root query allows has a feature it asks for,
+    /// and with [`Error::Resolve`] if there's no set of releases satisfying every requirement
+    pub fn resolve(&self, roots: &[Query]) -> Result<Resolution> {
+        let index_url = roots.first().and_then(|root| root.custom_index.clone());
+        let root_requirements = roots
+            .iter()
+            .map(|root| Requirement {
+                name: root.name.clone(),
+                req: root.version_req.clone().unwrap_or(VersionReq::STAR),
+                features: root.features.iter().cloned().collect(),
+                default_features: self.default_features,
+                root: true,
+            })
+            .collect::<Vec<Requirement>>();
+
+        for root in &root_requirements {
+            self.check_features(root)?;
+        }
+        let state = self.solve(root_requirements.iter().cloned().collect())?;
+
+        let mut activations = state.activated.into_values().collect::<Vec<Activation>>();
+        activations.sort_by(|a, b| {
+            (&a.release.name, &a.release.vers).cmp(&(&b.release.name, &b.release.vers))
+        });
+
+        let mut graph = DependencyGraph::new();
+        let ids = activations
+            .iter()
+            .map(|activation| graph.add_release((*activation.release).clone()))
+            .collect::<Vec<NodeId>>();
+        for (from, activation) in ids.into_iter().zip(&activations) {
+            for (dep, _) in self.followed_deps(activation) {
+                graph.connect(from, dep);
+            }
+        }
+
+        let roots = root_requirements
+            .iter()
+            .filter_map(|root| {
+                graph
+                    .named(&root.name)
+                    .find(|id| self.allows(graph.release(*id), &root.req))
+            })
+            .collect::<Vec<NodeId>>();
+        for root in &roots {
+            graph.add_root(*root);
+        }
+
+        Ok(Resolution {
+            graph,
+            roots,
+            index_url,
+        })
+    }
+
+    /// Fail with the feature error of the newest release a root requirement allows, if none of
+    /// them has every feature it enables
+    ///
+    /// That's a mistake in the query rather than a conflict in the tree, so it's reported before
+    /// solving instead of as an unsatisfiable requirement
+    fn check_features(&self, root: &Requirement) -> Result<()> {
+        let package = self.package(&root.name)?;
+        let allowed = package
+            .releases()
+            .iter()
+            .filter(|release| self.allows(release, &root.req))
+            .collect::<Vec<&Release>>();
+
+        match allowed.last() {
+            Some(newest) if !allowed.iter().any(|release| has_features(release, root)) => {
+                let selected = root
+                    .features
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<&str>>();
+                newest.resolve_features(&selected, root.default_features)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Activate a release for every requirement, backtracking through the choices made so far
+    /// whenever a requirement can't be satisfied
+    fn solve(&self, mut pending: VecDeque<Requirement>) -> Result<State> {
+        let mut state = State::default();
+        let mut choices: Vec<Choice> = Vec::new();
+        let mut unsatisfied: Option<Requirement> = None;
+
+        loop {
+            let requirement = loop {
+                match pending.pop_front() {
+                    None => return Ok(state),
+                    Some(requirement) => match self.satisfying(&state, &requirement) {
+                        Some(key) => self.enable(&mut state, &mut pending, &key, &requirement),
+                        None => break requirement,
+                    },
+                }
+            };
+
+            let candidates = self.candidates(&state, &requirement)?;
+            if candidates.is_empty() {
+                unsatisfied = Some(requirement.clone());
+            }
+
+            choices.push(Choice {
+                state,
+                pending,
+                requirement,
+                candidates,
+            });
+
+            // Take the newest untried candidate, going back to earlier choices when none are left
+            loop {
+                let Some(choice) = choices.last_mut() else {
+                    return Err(Error::Resolve(unsatisfiable(unsatisfied)));
+                };
+
+                match choice.candidates.pop() {
+                    Some(candidate) => {
+                        state = choice.state.clone();
+                        pending = choice.pending.clone();
+                        self.activate(&mut state, &mut pending, candidate, &choice.requirement);
+                        break;
+                    }
+                    None => {
+                        choices.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the key of a release that's already activated and satisfies a requirement
+    fn satisfying(&self, state: &State, requirement: &Requirement) -> Option<(String, Compat)> {
+        state
+            .releases_of(&normalize_name(&requirement.name))
+            .find(|release| {
+                self.allows(release, &requirement.req) && has_features(release, requirement)
+            })
+            .map(|release| key(&normalize_name(&release.name), &release.vers))
+    }
+
+    /// Return the releases that could satisfy a requirement without conflicting with the
+    /// releases already activated, oldest first
+    fn candidates(&self, state: &State, requirement: &Requirement) -> Result<Vec<Rc<Release>>> {
+        let package = self.package(&requirement.name)?;
+        let name = normalize_name(package.name());
+
+        Ok(package
+            .releases()
+            .iter()
+            .filter(|release| self.allows(release, &requirement.req))
+            .filter(|release| has_features(release, requirement))
+            .filter(|release| !state.activated.contains_key(&key(&name, &release.vers)))
+            .map(|release| Rc::new(release.clone()))
+            .collect())
+    }
+
+    /// Activate a release with the features a requirement enables, queueing requirements for
+    /// the dependencies it uses
+    fn activate(
+        &self,
+        state: &mut State,
+        pending: &mut VecDeque<Requirement>,
+        release: Rc<Release>,
+        requirement: &Requirement,
+    ) {
+        let activation = Activation {
+            release,
+            features: requirement.features.clone(),
+            default_features: requirement.default_features,
+            root: requirement.root,
+        };
+        self.queue_deps(pending, &activation);
+
+        let name = normalize_name(&activation.release.name);
+        let key = key(&name, &activation.release.vers);
+        state.activated.insert(key, activation);
+    }
+
+    /// Enable the features of a requirement on an activated release, queueing requirements for
+    /// any dependencies they enable
+    fn enable(
+        &self,
+        state: &mut State,
+        pending: &mut VecDeque<Requirement>,
+        key: &(String, Compat),
+        requirement: &Requirement,
+    ) {
+        let Some(activation) = state.activated.get_mut(key) else {
+            return;
+        };
+
+        let default_features = activation.default_features || requirement.default_features;
+        let features = activation
+            .features
+            .union(&requirement.features)
+            .cloned()
+            .collect::<BTreeSet<String>>();
+
+        let root = activation.root || requirement.root;
+        if default_features == activation.default_features
+            && features == activation.features
+            && root == activation.root
+        {
+            return;
+        }
+
+        activation.features = features;
+        activation.default_features = default_features;
+        activation.root = root;
+        self.queue_deps(pending, activation);
+    }
+
+    /// Queue requirements for the dependencies an activated release uses with it's features
+    fn queue_deps(&self, pending: &mut VecDeque<Requirement>, activation: &Activation) {
+        for (dep, features) in self.followed_deps(activation) {
+            pending.push_back(Requirement {
+                name: dep.package_name().to_owned(),
+                req: dep.req.clone(),
+                features,
+                default_features: dep.default_features,
+                root: false,
+            });
+        }
+    }
+
+    /// Return the dependencies an activated release uses with it's features, along with the
+    /// features enabled on each
+    fn followed_deps<'a>(
+        &self,
+        activation: &'a Activation,
+    ) -> Vec<(&'a Dependency, BTreeSet<String>)> {
+        let selected = activation
+            .features
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+        // Candidates are checked for the features they're asked for, so this doesn't fail
+        activation
+            .release
+            .activated_deps(&selected, activation.default_features)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|activated| {
+                let dep = activated.dependency;
+                self.dep_kinds.contains(&dep.kind)
+                    && (dep.kind != DependencyKind::Dev || activation.root)
+                    && self
+                        .target
+                        .as_deref()
+                        .map_or(true, |target| dep.applies_to(target))
+            })
+            .map(|activated| (activated.dependency, activated.features))
+            .collect()
+    }
+
+    fn allows(&self, release: &Release, req: &VersionReq) -> bool {
+        self.policy.allows(release, Some(req))
+    }
+
+    /// Fetch a package, or return it from the packages fetched earlier in this resolution
+    fn package(&self, name: &str) -> Result<Rc<Package>> {
+        let normalized = normalize_name(name);
+
+        if let Some(package) = self.packages.borrow().get(&normalized) {
+            return Ok(Rc::clone(package));
+        }
+
+        let package = Rc::new(self.fetcher.fetch(name)?);
+        self.packages
+            .borrow_mut()
+            .insert(normalized, Rc::clone(&package));
+
+        Ok(package)
+    }
+}
+
+/// The releases selected by the resolver, see [`Resolver::resolve`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolution {
+    graph: DependencyGraph,
+    roots: Vec<NodeId>,
+    index_url: Option<String>,
+}
+
+impl Resolution {
+    /// Return every selected release, ordered by name and then version
+    pub fn releases(&self) -> &[Release] {
+        self.graph.releases()
+    }
+
+    /// Convert into every selected release, ordered by name and then version
+    pub fn into_releases(self) -> Vec<Release> {
+        self.graph.into_releases()
+    }
+
+    /// Return the dependency graph of the selected releases
+    ///
+    /// There's an edge for every dependency the resolver followed, see the [module docs](self)
+    pub fn graph(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
+    /// Convert into the dependency graph of the selected releases
+    pub fn into_graph(self) -> DependencyGraph {
+        self.graph
+    }
+
+    /// Write the selected releases as a version 3 `Cargo.lock` file
+    ///
+    /// Releases are assumed to be from the index of the first root query. See
+    /// [`DependencyGraph::to_lockfile_toml`]
+    pub fn to_lockfile_toml(&self) -> String {
+        let index_url = self.index_url.as_deref().unwrap_or(CRATES_IO_INDEX_URL);
+        self.graph.to_lockfile_toml(index_url)
+    }
+
+    /// Return the release selected for each root, in the order the roots were given
+    pub fn roots(&self) -> impl Iterator<Item = &Release> {
+        self.roots.iter().map(|id| self.graph.release(*id))
+    }
+
+    /// Return the selected releases of a package, which may be more than one if the tree
+    /// requires semver-incompatible versions of it
+    pub fn get<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Release> {
+        self.graph.named(name).map(|id| self.graph.release(id))
+    }
+}
+
+/// Releases selected for requirements one at a time, unifying semver-compatible ones
+///
+/// Each requirement gets the newest release it allows, unless a semver-compatible release of
+/// the package was already selected. Then the newest release fitting every requirement on that
+/// one is used instead, replacing it if needed, the way cargo unifies `^1.0.100` and `^1.0.50`.
+/// Both are kept if no release fits all of them. Unlike [`Resolver`], earlier choices are never
+/// revisited beyond that, so requirements can be selected for as a tree is walked
+#[derive(Debug, Clone, Default)]
+pub struct Unifier {
+    releases: Vec<Release>,
+    /// Every version requirement selected for so far, by normalized package name
+    requirements: HashMap<String, Vec<VersionReq>>,
+}
+
+/// A release picked by [`Unifier::select`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// The release selected for the requirement
+    pub release: Release,
+    /// The release selected for earlier requirements that this one replaced, if any
+    pub replaced: Option<Release>,
+}
+
+impl Unifier {
+    /// Create a unifier that hasn't selected any releases yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select a release of a package for a version requirement, see the [type docs](Unifier)
+    ///
+    /// Returns `None` if no release the policy allows matches the requirement
+    pub fn select(
+        &mut self,
+        package: &Package,
+        version_req: Option<&VersionReq>,
+        policy: &SelectionPolicy,
+    ) -> Option<Selection> {
+        let selected = package.select(version_req, policy)?.clone();
+        let name = normalize_name(&selected.name);
+        if let Some(version_req) = version_req {
+            self.require(&name, version_req);
+        }
+
+        let Some(index) = self.releases.iter().position(|release| {
+            normalize_name(&release.name) == name
+                && !compat(&release.vers, &selected.vers).is_breaking()
+        }) else {
+            self.releases.push(selected.clone());
+            return Some(Selection {
+                release: selected,
+                replaced: None,
+            });
+        };
+
+        let existing = &self.releases[index];
+        let shared = self.requirements[&name]
+            .iter()
+            .filter(|req| req.matches(&existing.vers))
+            .collect::<Vec<&VersionReq>>();
+        let unified = package.releases().iter().rev().find(|release| {
+            policy.allows(release, version_req)
+                && shared.iter().all(|req| req.matches(&release.vers))
+        });
+
+        match unified {
+            Some(unified) => {
+                let replaced = (unified.vers != existing.vers)
+                    .then(|| std::mem::replace(&mut self.releases[index], unified.clone()));
+                Some(Selection {
+                    release: unified.clone(),
+                    replaced,
+                })
+            }
+            None => {
+                if !self.releases.contains(&selected) {
+                    self.releases.push(selected.clone());
+                }
+                Some(Selection {
+                    release: selected,
+                    replaced: None,
+                })
+            }
+        }
+    }
+
+    /// Record a requirement on a package without selecting a release for it, so later
+    /// selections keep fitting it, e.g. for a dependency on a release that was already selected
+    pub fn require(&mut self, name: &str, version_req: &VersionReq) {
+        self.requirements
+            .entry(normalize_name(name))
+            .or_default()
+            .push(version_req.clone());
+    }
+
+    /// Return the selected releases, in the order they were first selected
+    pub fn releases(&self) -> &[Release] {
+        &self.releases
+    }
+
+    /// Convert into the selected releases, in the order they were first selected
+    pub fn into_releases(self) -> Vec<Release> {
+        self.releases
+    }
+}
+
+/// A requirement on a package that the resolution has to satisfy, with the features it enables
+#[derive(Debug, Clone)]
+struct Requirement {
+    name: String,
+    req: VersionReq,
+    features: BTreeSet<String>,
+    default_features: bool,
+    /// Whether this is the requirement on a root package itself
+    root: bool,
+}
+
+/// An activated release, with every feature enabled on it so far
+#[derive(Debug, Clone)]
+struct Activation {
+    release: Rc<Release>,
+    features: BTreeSet<String>,
+    default_features: bool,
+    root: bool,
+}
+
+/// Releases activated so far, keyed by normalized name and semver-compatible version range
+#[derive(Debug, Clone, Default)]
+struct State {
+    activated: BTreeMap<(String, Compat), Activation>,
+}
+
+impl State {
+    fn releases_of<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Release> {
+        let start = (name.to_owned(), (0, 0, 0));
+        let end = (name.to_owned(), (u64::MAX, u64::MAX, u64::MAX));
+
+        self.activated
+            .range(start..=end)
+            .map(|(_, activation)| activation.release.as_ref())
+    }
+}
+
+/// A point the resolver can go back to, with the candidates for a requirement that haven't been
+/// tried yet
+struct Choice {
+    state: State,
+    pending: VecDeque<Requirement>,
+    requirement: Requirement,
+    candidates: Vec<Rc<Release>>,
+}
+
+/// The semver-compatible range a version is in, e.g. `1.x`, `0.3.x`, or `0.0.4`
+type Compat = (u64, u64, u64);
+
+fn key(name: &str, version: &Version) -> (String, Compat) {
+    let compat = match (version.major, version.minor) {
+        (0, 0) => (0, 0, version.patch),
+        (0, minor) => (0, minor, u64::MAX),
+        (major, _) => (major, u64::MAX, u64::MAX),
+    };
+
+    (name.to_owned(), compat)
+}
+
+/// Whether a release has every feature a requirement enables on it
+fn has_features(release: &Release, requirement: &Requirement) -> bool {
+    let selected = requirement
+        .features
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+
+    release
+        .resolve_features(&selected, requirement.default_features)
+        .is_ok()
+}
+
+/// Describe the requirement that couldn't be satisfied
+fn unsatisfiable(requirement: Option<Requirement>) -> String {
+    match requirement {
+        Some(Requirement { name, req, .. }) => {
+            format!("no release of `{name}` matching `{req}` fits with the rest of the tree")
+        }
+        None => "no set of releases satisfies every requirement".to_owned(),
+    }
+}
@@ -0,0 +1,359 @@
+//! Software bills of materials for a dependency graph, as [CycloneDX](https://cyclonedx.org) or
+//! [SPDX](https://spdx.dev) JSON
+//!
+//! Every release of a [`DependencyGraph`] is a component, identified by a `pkg:cargo` package
+//! URL and hashed with it's SHA-256 checksum from the index. Licenses come from
+//! [`Release::license`], which is only set after fetching them from a registry's API, or can be
+//! added with [`Sbom::with_license`]. Suppliers can be added with [`Sbom::with_supplier`]
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use cargo_lookup::{resolve::resolve, sbom::Sbom, Query, Result, CRATES_IO_INDEX_URL};
+//!
+//! fn main() -> Result<()> {
+//!     let resolution = resolve(&["tokio@^1".parse::<Query>()?])?;
+//!     let sbom = Sbom::new(resolution.graph(), CRATES_IO_INDEX_URL);
+//!
+//!     println!("{}", sbom.to_cyclonedx());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{graph::DependencyGraph, DependencyKind, Release, CRATES_IO_INDEX_URL};
+use semver::Version;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The person or organization that supplies a package, e.g. it's owner on crates.io
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Supplier {
+    /// A person, like a crates.io user
+    Person(String),
+    /// An organization, like a crates.io team
+    Organization(String),
+}
+
+impl Supplier {
+    /// Return the supplier's name
+    pub fn name(&self) -> &str {
+        match self {
+            Supplier::Person(name) | Supplier::Organization(name) => name,
+        }
+    }
+}
+
+/// A bill of materials for the releases of a graph, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct Sbom<'a> {
+    graph: &'a DependencyGraph,
+    index_url: String,
+    licenses: BTreeMap<(String, Version), String>,
+    suppliers: BTreeMap<String, Supplier>,
+    created: String,
+}
+
+impl<'a> Sbom<'a> {
+    /// Create a bill of materials for a graph of releases from the index at `index_url`, created
+    /// now
+    pub fn new(graph: &'a DependencyGraph, index_url: &str) -> Self {
+        Self {
+            graph,
+            index_url: index_url.to_owned(),
+            licenses: BTreeMap::new(),
+            suppliers: BTreeMap::new(),
+            created: timestamp(SystemTime::now()),
+        }
+    }
+
+    /// Set the license of a release, in place of [`Release::license`]
+    pub fn with_license(mut self, name: &str, version: &Version, license: &str) -> Self {
+        self.licenses
+            .insert((name.to_owned(), version.clone()), license.to_owned());
+        self
+    }
+
+    /// Set the supplier of every release of a package
+    pub fn with_supplier(mut self, name: &str, supplier: Supplier) -> Self {
+        self.suppliers.insert(name.to_owned(), supplier);
+        self
+    }
+
+    /// Set when the bill of materials was created, as an RFC 3339 timestamp like
+    /// `2024-05-01T12:00:00Z`
+    pub fn with_created<T: Into<String>>(mut self, created: T) -> Self {
+        self.created = created.into();
+        self
+    }
+
+    /// Return the package URL of a release, like `pkg:cargo/serde@1.0.219`
+    ///
+    /// Releases from a registry other than crates.io have a `repository_url` qualifier with the
+    /// index URL
+    pub fn purl(&self, release: &Release) -> String {
+        let mut purl = format!(
+            "pkg:cargo/{}@{}",
+            percent_encode(&release.name, ""),
+            percent_encode(&release.vers.to_string(), "")
+        );
+
+        if self.index_url != CRATES_IO_INDEX_URL {
+            purl.push_str("?repository_url=");
+            purl.push_str(&percent_encode(&self.index_url, ":/"));
+        }
+
+        purl
+    }
+
+    /// Write the bill of materials as a CycloneDX 1.5 JSON document
+    ///
+    /// The `dependencies` list what each component depends on directly, of any kind
+    pub fn to_cyclonedx(&self) -> Value {
+        let components = self
+            .graph
+            .nodes()
+            .map(|(_, release)| {
+                let mut component = json!({
+                    "type": "library",
+                    "bom-ref": self.purl(release),
+                    "name": release.name,
+                    "version": release.vers.to_string(),
+                    "purl": self.purl(release),
+                    "hashes": [{ "alg": "SHA-256", "content": release.cksum }],
+                });
+                if let Some(license) = self.license(release) {
+                    component["licenses"] = json!([{ "expression": license_expression(license) }]);
+                }
+                if let Some(supplier) = self.suppliers.get(&release.name) {
+                    component["supplier"] = json!({ "name": supplier.name() });
+                }
+
+                component
+            })
+            .collect::<Vec<Value>>();
+
+        let dependencies = self
+            .graph
+            .nodes()
+            .map(|(id, release)| {
+                let mut depends_on = self
+                    .graph
+                    .dependencies(id)
+                    .map(|edge| self.purl(self.graph.release(edge.to)))
+                    .collect::<Vec<String>>();
+                depends_on.sort();
+                depends_on.dedup();
+
+                json!({ "ref": self.purl(release), "dependsOn": depends_on })
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": self.created,
+                "tools": {
+                    "components": [{
+                        "type": "application",
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    }],
+                },
+            },
+            "components": components,
+            "dependencies": dependencies,
+        })
+    }
+
+    /// Write the bill of materials as an SPDX 2.3 JSON document
+    ///
+    /// The document describes the graph's roots. Normal dependencies are `DEPENDS_ON`
+    /// relationships, and build and dev dependencies are `BUILD_DEPENDENCY_OF` and
+    /// `DEV_DEPENDENCY_OF` relationships from the dependency to the dependent
+    pub fn to_spdx(&self) -> Value {
+        let packages = self
+            .graph
+            .nodes()
+            .map(|(_, release)| {
+                let license = self
+                    .license(release)
+                    .map_or_else(|| "NOASSERTION".to_owned(), license_expression);
+                let supplier = match self.suppliers.get(&release.name) {
+                    Some(Supplier::Person(name)) => format!("Person: {name}"),
+                    Some(Supplier::Organization(name)) => format!("Organization: {name}"),
+                    None => "NOASSERTION".to_owned(),
+                };
+
+                json!({
+                    "name": release.name,
+                    "SPDXID": spdx_id(release),
+                    "versionInfo": release.vers.to_string(),
+                    "supplier": supplier,
+                    "downloadLocation": "NOASSERTION",
+                    "filesAnalyzed": false,
+                    "checksums": [{ "algorithm": "SHA256", "checksumValue": release.cksum }],
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": license,
+                    "copyrightText": "NOASSERTION",
+                    "externalRefs": [{
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": self.purl(release),
+                    }],
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        let mut relationships = self
+            .graph
+            .roots()
+            .iter()
+            .map(|root| {
+                json!({
+                    "spdxElementId": "SPDXRef-DOCUMENT",
+                    "relationshipType": "DESCRIBES",
+                    "relatedSpdxElement": spdx_id(self.graph.release(*root)),
+                })
+            })
+            .collect::<Vec<Value>>();
+        for edge in self.graph.edges() {
+            let from = spdx_id(self.graph.release(edge.from));
+            let to = spdx_id(self.graph.release(edge.to));
+            let (element, kind, related) = match edge.dependency.kind {
+                DependencyKind::Build => (to, "BUILD_DEPENDENCY_OF", from),
+                DependencyKind::Dev => (to, "DEV_DEPENDENCY_OF", from),
+                _ => (from, "DEPENDS_ON", to),
+            };
+
+            relationships.push(json!({
+                "spdxElementId": element,
+                "relationshipType": kind,
+                "relatedSpdxElement": related,
+            }));
+        }
+
+        let name = self
+            .graph
+            .roots()
+            .iter()
+            .map(|root| {
+                let release = self.graph.release(*root);
+                format!("{}-{}", release.name, release.vers)
+            })
+            .collect::<Vec<String>>()
+            .join("_");
+
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": name,
+            "documentNamespace": self.namespace(&name),
+            "creationInfo": {
+                "created": self.created,
+                "creators": [format!("Tool: {}-{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        })
+    }
+
+    /// Return the license of a release, if it's known
+    fn license(&self, release: &'a Release) -> Option<&str> {
+        self.licenses
+            .get(&(release.name.clone(), release.vers.clone()))
+            .or(release.license.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Return a URI unique to the document, made from a hash of it's releases and creation time
+    fn namespace(&self, name: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.created.as_bytes());
+        for (_, release) in self.graph.nodes() {
+            hasher.update(format!(
+                "{} {} {}\n",
+                release.name, release.vers, release.cksum
+            ));
+        }
+        let hash = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+
+        format!("https://spdx.org/spdxdocs/{name}-{hash}")
+    }
+}
+
+/// Return the SPDX identifier of a release's package, like `SPDXRef-serde-1.0.219`
+fn spdx_id(release: &Release) -> String {
+    let id = format!("{}-{}", release.name, release.vers).replace(
+        |char: char| !char.is_ascii_alphanumeric() && char != '.' && char != '-',
+        "-",
+    );
+
+    format!("SPDXRef-{id}")
+}
+
+/// Write a license from a manifest as an SPDX expression. Old manifests separate licenses with
+/// `/`, which cargo reads as `OR`
+fn license_expression(license: &str) -> String {
+    license
+        .split('/')
+        .map(str::trim)
+        .collect::<Vec<&str>>()
+        .join(" OR ")
+}
+
+/// Percent-encode everything but unreserved characters and those in `keep`
+fn percent_encode(text: &str, keep: &str) -> String {
+    text.bytes()
+        .map(|byte| {
+            let char = char::from(byte);
+            if char.is_ascii_alphanumeric() || "-._~".contains(char) || keep.contains(char) {
+                char.to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+/// Format a time as an RFC 3339 timestamp in UTC, to the second
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // Convert days since the epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
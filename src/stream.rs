@@ -0,0 +1,47 @@
+//! Printing releases as newline-delimited JSON as soon as they're resolved, for
+//! `--format=ndjson`
+
+use crate::{
+    cli::Options,
+    enrich::{Enrichment, Entry},
+};
+use anyhow::Result;
+use cargo_lookup::{normalize_name, Release};
+use semver::Version;
+use std::{collections::HashSet, sync::Mutex};
+
+/// Prints each release once, as a line of JSON with the same fields as the JSON output
+#[derive(Debug)]
+pub struct Stream {
+    enrichment: Enrichment,
+    printed: Mutex<HashSet<(String, Version)>>,
+}
+
+impl Stream {
+    /// Load the enrichment sources requested on the command line, so they're ready for the
+    /// first release
+    pub fn new(options: &Options) -> Self {
+        Self {
+            enrichment: Enrichment::load(options),
+            printed: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Print a release, unless it's been printed already
+    pub fn emit(&self, release: &Release, options: &Options) -> Result<()> {
+        let key = (normalize_name(&release.name), release.vers.clone());
+        if !self.printed.lock().expect("printed lock").insert(key) {
+            return Ok(());
+        }
+
+        let mut entries = [self.enrichment.entry(release.clone())];
+        if options.check_advisories {
+            Enrichment::check_advisories(&mut entries, 1);
+        }
+        self.enrichment.check_sizes(&mut entries, 1);
+
+        let [entry]: [Entry; 1] = entries;
+        println!("{}", serde_json::to_string(&entry)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+//! "Did you mean" suggestions for package names that couldn't be found
+//!
+//! Candidates come from the crates.io search API, and are ranked by how close they are to the
+//! name that was looked up
+
+use crate::{error::Error, get, normalize_name, Result, CRATES_IO_API_URL};
+use serde::Deserialize;
+
+/// How many search results to rank for each search
+const SEARCH_RESULTS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct Search {
+    crates: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    name: String,
+}
+
+/// Return up to `limit` names of crates on crates.io that are similar to `name`
+///
+/// This searches crates.io for the name itself and for it's first few characters, which catches
+/// most typos like `tokoi` for `tokio`
+pub fn suggestions(name: &str, limit: usize) -> Result<Vec<String>> {
+    // Only characters that can be in a crate name are searched, so nothing needs escaping
+    let name = name
+        .chars()
+        .filter(|char| char.is_ascii_alphanumeric() || "-_".contains(*char))
+        .collect::<String>();
+    let prefix = name.chars().take(3).collect::<String>();
+    let mut candidates = Vec::new();
+
+    for query in [name.as_str(), prefix.as_str()] {
+        let url = format!(
+            "{CRATES_IO_API_URL}/crates?q={query}&sort=downloads&per_page={SEARCH_RESULTS}"
+        );
+        let search: Search = serde_json::from_str(&get(&url)?).map_err(Error::Deserialize)?;
+
+        candidates.extend(search.crates.into_iter().map(|result| result.name));
+    }
+
+    Ok(rank(&name, candidates, limit))
+}
+
+/// Rank candidate names by how similar they are to `name`, keeping up to `limit` close matches
+///
+/// Candidates are kept if they are within an edit distance of a third of the name's length, at
+/// least one. Ties keep the order of `candidates`, so more popular crates should come first
+pub fn rank<I>(name: &str, candidates: I, limit: usize) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let target = normalize_name(name);
+    let max_distance = (target.chars().count() / 3).max(1);
+
+    let mut ranked: Vec<(usize, String)> = Vec::new();
+    for candidate in candidates {
+        let normalized = normalize_name(&candidate);
+        if normalized == target || ranked.iter().any(|(_, seen)| *seen == candidate) {
+            continue;
+        }
+
+        let distance = edit_distance(&target, &normalized);
+        if distance <= max_distance {
+            ranked.push((distance, candidate));
+        }
+    }
+
+    ranked.sort_by_key(|(distance, _)| *distance);
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// The number of single character insertions, deletions, substitutions, and adjacent
+/// transpositions needed to turn one string into the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+
+    // Rows for the previous two prefixes of `a`, needed to count transpositions
+    let mut before = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+
+        before = std::mem::replace(&mut previous, current);
+    }
+
+    previous[b.len()]
+}
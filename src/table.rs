@@ -0,0 +1,117 @@
+//! Writing results as rows of columns, for `--format=csv`, `--format=tsv`, and `--format=table`
+//!
+//! The columns are chosen with `--columns`, using the same fields as `--format-str`, see
+//! [`Field`]
+
+use crate::template::Field;
+
+/// Columns shown when `--columns` isn't given
+pub const DEFAULT_COLUMNS: [Field; 6] = [
+    Field::Name,
+    Field::Version,
+    Field::Yanked,
+    Field::Msrv,
+    Field::DepCount,
+    Field::Checksum,
+];
+
+/// Columns shown by `--format=table` when `--columns` isn't given
+pub const TABLE_COLUMNS: [Field; 6] = [
+    Field::Name,
+    Field::Version,
+    Field::Yanked,
+    Field::Msrv,
+    Field::DepCount,
+    Field::FeatureCount,
+];
+
+/// A header row naming each column, and a row of values for each result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create a table with no rows, headed by the names of the columns
+    pub fn new(columns: &[Field]) -> Self {
+        Self {
+            header: columns.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Create a table with no rows, headed by columns that aren't fields, like a command's own
+    /// columns
+    pub fn with_header(header: &[&str]) -> Self {
+        Self {
+            header: header.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row, with a value for each column
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Write the table as comma-separated values, quoting values as RFC 4180 describes
+    pub fn to_csv(&self) -> String {
+        self.write(",", |value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_owned()
+            }
+        })
+    }
+
+    /// Write the table as tab-separated values. Values can't be quoted, so tabs and line breaks
+    /// in them are replaced with spaces
+    pub fn to_tsv(&self) -> String {
+        self.write("\t", |value| value.replace(['\t', '\n', '\r'], " "))
+    }
+
+    /// Write the table with each column padded to the width of it's widest value, for reading
+    /// in a terminal
+    pub fn to_aligned(&self) -> String {
+        let mut widths = vec![0; self.header.len()];
+        for row in std::iter::once(&self.header).chain(&self.rows) {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+
+        let mut output = String::new();
+        for row in std::iter::once(&self.header).chain(&self.rows) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{value:width$}"))
+                .collect::<Vec<String>>()
+                .join("  ");
+            output.push_str(line.trim_end());
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn write<F>(&self, separator: &str, escape: F) -> String
+    where
+        F: Fn(&str) -> String,
+    {
+        let mut output = String::new();
+
+        for row in std::iter::once(&self.header).chain(&self.rows) {
+            let row = row
+                .iter()
+                .map(|value| escape(value))
+                .collect::<Vec<String>>();
+            output.push_str(&row.join(separator));
+            output.push('\n');
+        }
+
+        output
+    }
+}
@@ -0,0 +1,405 @@
+//! Platform targets for dependencies, as written in [`Dependency::target`](crate::Dependency::target)
+//!
+//! A target is either an exact target triple, like `x86_64-pc-windows-msvc`, or a `cfg`
+//! expression, like `cfg(all(unix, target_arch = "aarch64"))`
+//!
+//! ## Examples
+//!
+//! ```
+//! use cargo_lookup::target::Target;
+//!
+//! let target: Target = r#"cfg(any(windows, target_os = "macos"))"#.parse().unwrap();
+//!
+//! assert!(target.applies_to("x86_64-pc-windows-msvc"));
+//! assert!(target.applies_to("aarch64-apple-darwin"));
+//! assert!(!target.applies_to("x86_64-unknown-linux-gnu"));
+//! ```
+
+use crate::error::Error;
+use std::{fmt, iter::Peekable, str::FromStr};
+
+/// A dependency's target platform
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// An exact target triple
+    Triple(String),
+    /// A `cfg(...)` expression
+    Cfg(CfgExpr),
+}
+
+/// A `cfg` expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CfgExpr {
+    /// `not(expr)`
+    Not(Box<CfgExpr>),
+    /// `all(expr, ...)`
+    All(Vec<CfgExpr>),
+    /// `any(expr, ...)`
+    Any(Vec<CfgExpr>),
+    /// A single option, like `unix` or `target_os = "linux"`
+    Value(Cfg),
+}
+
+/// A single `cfg` option
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cfg {
+    /// A name on it's own, like `unix`
+    Name(String),
+    /// A key-value pair, like `target_os = "linux"`
+    KeyPair(String, String),
+}
+
+impl Target {
+    /// Check if this target applies when building for the given target triple
+    pub fn applies_to(&self, triple: &str) -> bool {
+        match self {
+            Target::Triple(target) => target == triple,
+            Target::Cfg(expr) => expr.matches(&TargetInfo::from_triple(triple)),
+        }
+    }
+}
+
+impl CfgExpr {
+    /// Evaluate this expression against the cfg values of a target
+    pub fn matches(&self, info: &TargetInfo) -> bool {
+        match self {
+            CfgExpr::Not(expr) => !expr.matches(info),
+            CfgExpr::All(exprs) => exprs.iter().all(|expr| expr.matches(info)),
+            CfgExpr::Any(exprs) => exprs.iter().any(|expr| expr.matches(info)),
+            CfgExpr::Value(cfg) => info.matches(cfg),
+        }
+    }
+}
+
+/// The cfg values of a target, derived from it's target triple
+///
+/// This covers the values dependency targets are commonly written against. Options that can't be
+/// known from a triple alone, such as `target_feature`, never match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetInfo {
+    /// `target_arch`, e.g. `x86_64`
+    pub arch: String,
+    /// `target_vendor`, e.g. `apple`
+    pub vendor: String,
+    /// `target_os`, e.g. `linux`
+    pub os: String,
+    /// `target_env`, e.g. `gnu`, or empty
+    pub env: String,
+    /// `target_abi`, e.g. `eabihf`, or empty
+    pub abi: String,
+    /// `target_family` values, e.g. `unix`
+    pub families: Vec<String>,
+    /// `target_pointer_width`, e.g. `64`
+    pub pointer_width: u32,
+    /// `target_endian`, either `little` or `big`
+    pub endian: &'static str,
+}
+
+impl TargetInfo {
+    /// Derive the cfg values of a target from it's target triple
+    pub fn from_triple(triple: &str) -> Self {
+        let parts = triple.split('-').collect::<Vec<&str>>();
+        let raw_arch = parts.first().copied().unwrap_or_default();
+
+        let arch = match raw_arch {
+            "i386" | "i586" | "i686" => "x86",
+            "x86_64h" => "x86_64",
+            "arm64" | "arm64e" => "aarch64",
+            "powerpc64le" => "powerpc64",
+            arch if arch.starts_with("armv") || arch.starts_with("thumbv") => "arm",
+            arch if arch.starts_with("riscv64") => "riscv64",
+            arch if arch.starts_with("riscv32") => "riscv32",
+            "mipsel" => "mips",
+            "mips64el" => "mips64",
+            arch => arch,
+        };
+
+        // Some triples, like `aarch64-linux-android`, leave out the vendor
+        let vendor = match parts.len() {
+            0..=2 => "unknown",
+            _ if parts[1] == "linux" => "unknown",
+            _ => parts[1],
+        };
+
+        let os_and_env = parts.iter().skip(1).copied().collect::<Vec<&str>>();
+        let has = |name: &str| os_and_env.contains(&name);
+
+        let os = if has("android") || has("androideabi") {
+            "android"
+        } else if has("linux") {
+            "linux"
+        } else if has("windows") {
+            "windows"
+        } else if has("darwin") || has("macos") {
+            "macos"
+        } else if has("ios") {
+            "ios"
+        } else if has("tvos") {
+            "tvos"
+        } else if has("watchos") {
+            "watchos"
+        } else if has("visionos") {
+            "visionos"
+        } else if has("emscripten") {
+            "emscripten"
+        } else if has("wasi") || has("wasip1") || has("wasip2") {
+            "wasi"
+        } else {
+            [
+                "freebsd",
+                "netbsd",
+                "openbsd",
+                "dragonfly",
+                "fuchsia",
+                "redox",
+                "solaris",
+                "illumos",
+                "haiku",
+                "hermit",
+                "uefi",
+                "aix",
+            ]
+            .into_iter()
+            .find(|os| has(os))
+            .unwrap_or(if has("none") { "none" } else { "unknown" })
+        };
+
+        let env_part = match os {
+            "windows" | "linux" | "android" | "uefi" | "wasi" => {
+                parts.last().copied().unwrap_or_default()
+            }
+            _ if parts.len() > 3 => parts[3],
+            _ => "",
+        };
+
+        // WASI targets carry their preview version as the environment, e.g. `wasm32-wasip2`
+        let env_part = match os {
+            "wasi" => env_part.strip_prefix("wasi").unwrap_or(env_part),
+            _ => env_part,
+        };
+
+        let (env, abi) = [
+            "gnu", "musl", "msvc", "uclibc", "sgx", "ohos", "newlib", "p1", "p2",
+        ]
+        .into_iter()
+        .find_map(|env| {
+            env_part
+                .strip_prefix(env)
+                .map(|abi| (env.to_owned(), abi.to_owned()))
+        })
+        .unwrap_or_else(|| match env_part {
+            abi @ ("eabi" | "eabihf" | "androideabi") => (String::new(), abi.to_owned()),
+            _ => (String::new(), String::new()),
+        });
+
+        let mut families = Vec::new();
+        match os {
+            "windows" => families.push("windows".to_owned()),
+            "none" | "unknown" | "uefi" | "wasi" => {}
+            _ => families.push("unix".to_owned()),
+        }
+        if arch.starts_with("wasm") {
+            families.push("wasm".to_owned());
+        }
+
+        let pointer_width = match arch {
+            "x86_64" | "aarch64" | "powerpc64" | "riscv64" | "mips64" | "s390x" | "sparc64"
+            | "loongarch64" | "wasm64" => 64,
+            "avr" | "msp430" => 16,
+            _ => 32,
+        };
+
+        let endian = match raw_arch {
+            "powerpc" | "powerpc64" | "s390x" | "sparc" | "sparc64" | "mips" | "mips64" => "big",
+            _ => "little",
+        };
+
+        Self {
+            arch: arch.to_owned(),
+            vendor: vendor.to_owned(),
+            os: os.to_owned(),
+            env,
+            abi,
+            families,
+            pointer_width,
+            endian,
+        }
+    }
+
+    /// Check if a single cfg option is set for this target
+    pub fn matches(&self, cfg: &Cfg) -> bool {
+        match cfg {
+            Cfg::Name(name) => self.families.iter().any(|family| family == name),
+            Cfg::KeyPair(key, value) => match key.as_str() {
+                "target_arch" => &self.arch == value,
+                "target_vendor" => &self.vendor == value,
+                "target_os" => &self.os == value,
+                "target_env" => &self.env == value,
+                "target_abi" => &self.abi == value,
+                "target_family" => self.families.iter().any(|family| family == value),
+                "target_pointer_width" => self.pointer_width.to_string() == *value,
+                "target_endian" => self.endian == value,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = Error;
+
+    fn from_str(target: &str) -> std::result::Result<Self, Self::Err> {
+        let target = target.trim();
+
+        match target
+            .strip_prefix("cfg(")
+            .and_then(|cfg| cfg.strip_suffix(')'))
+        {
+            Some(cfg) => cfg.parse().map(Target::Cfg),
+            None if target.is_empty() || target.contains(char::is_whitespace) => {
+                Err(Error::InvalidTarget(target.to_owned()))
+            }
+            None => Ok(Target::Triple(target.to_owned())),
+        }
+    }
+}
+
+impl FromStr for CfgExpr {
+    type Err = Error;
+
+    fn from_str(expr: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidTarget(format!("cfg({expr})"));
+        let mut tokens = tokenize(expr).ok_or_else(invalid)?.into_iter().peekable();
+
+        let parsed = parse_expr(&mut tokens).ok_or_else(invalid)?;
+
+        match tokens.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(parsed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    LeftParen,
+    RightParen,
+    Comma,
+    Equals,
+}
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::LeftParen),
+            ')' => tokens.push(Token::RightParen),
+            ',' => tokens.push(Token::Comma),
+            '=' => tokens.push(Token::Equals),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return None,
+        }
+    }
+
+    Some(tokens)
+}
+
+fn parse_expr<I>(tokens: &mut Peekable<I>) -> Option<CfgExpr>
+where
+    I: Iterator<Item = Token>,
+{
+    let Token::Ident(ident) = tokens.next()? else {
+        return None;
+    };
+
+    match tokens.peek() {
+        Some(Token::LeftParen) => {
+            tokens.next();
+            let mut exprs = Vec::new();
+
+            loop {
+                if tokens.peek() == Some(&Token::RightParen) {
+                    tokens.next();
+                    break;
+                }
+
+                exprs.push(parse_expr(tokens)?);
+
+                match tokens.next()? {
+                    Token::Comma => {}
+                    Token::RightParen => break,
+                    _ => return None,
+                }
+            }
+
+            match ident.as_str() {
+                "all" => Some(CfgExpr::All(exprs)),
+                "any" => Some(CfgExpr::Any(exprs)),
+                "not" if exprs.len() == 1 => Some(CfgExpr::Not(Box::new(exprs.remove(0)))),
+                _ => None,
+            }
+        }
+        Some(Token::Equals) => {
+            tokens.next();
+            match tokens.next()? {
+                Token::Str(value) => Some(CfgExpr::Value(Cfg::KeyPair(ident, value))),
+                _ => None,
+            }
+        }
+        _ => Some(CfgExpr::Value(Cfg::Name(ident))),
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Triple(triple) => write!(f, "{triple}"),
+            Target::Cfg(expr) => write!(f, "cfg({expr})"),
+        }
+    }
+}
+
+impl fmt::Display for CfgExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |exprs: &[CfgExpr]| {
+            exprs
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self {
+            CfgExpr::Not(expr) => write!(f, "not({expr})"),
+            CfgExpr::All(exprs) => write!(f, "all({})", join(exprs)),
+            CfgExpr::Any(exprs) => write!(f, "any({})", join(exprs)),
+            CfgExpr::Value(Cfg::Name(name)) => write!(f, "{name}"),
+            CfgExpr::Value(Cfg::KeyPair(key, value)) => write!(f, "{key} = \"{value}\""),
+        }
+    }
+}
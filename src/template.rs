@@ -0,0 +1,163 @@
+//! Output templates for `--format-str`, e.g. `{name} {version} {msrv}`
+//!
+//! Placeholders are field names in braces, see [`Field`]. Literal braces are written `{{` and
+//! `}}`. The same fields name the columns of `--columns`
+
+use anyhow::Result;
+use std::{fmt, str::FromStr};
+
+/// A value of a release that can be placed in a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// `{name}`: the package name
+    Name,
+    /// `{version}`: the release's version
+    Version,
+    /// `{yanked}`: `true` if the release is yanked, otherwise `false`
+    Yanked,
+    /// `{msrv}`: the release's `rust-version`, or `none`
+    Msrv,
+    /// `{features}`: the release's features, separated by `--delim`
+    Features,
+    /// `{deps}`: the release's dependencies, separated by `--delim` and limited like
+    /// `--type=deps`
+    Deps,
+    /// `{dep_count}`: the number of dependencies in `{deps}`
+    DepCount,
+    /// `{feature_count}`: the number of features in `{features}`
+    FeatureCount,
+    /// `{checksum}`: the SHA-256 checksum of the release's `.crate` file
+    Checksum,
+    /// `{license}`: the release's license from crates.io, or `unknown`
+    License,
+    /// `{published}`: the date the release was published from crates.io, or `unknown`
+    Published,
+    /// `{size}`: the size of the release's `.crate` file, or `unknown`
+    Size,
+}
+
+impl Field {
+    /// Every field with it's placeholder name
+    const ALL: [(&'static str, Field); 12] = [
+        ("name", Field::Name),
+        ("version", Field::Version),
+        ("yanked", Field::Yanked),
+        ("msrv", Field::Msrv),
+        ("features", Field::Features),
+        ("deps", Field::Deps),
+        ("dep_count", Field::DepCount),
+        ("feature_count", Field::FeatureCount),
+        ("checksum", Field::Checksum),
+        ("license", Field::License),
+        ("published", Field::Published),
+        ("size", Field::Size),
+    ];
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = Field::ALL
+            .iter()
+            .find(|(_, field)| field == self)
+            .expect("every field has a name");
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .iter()
+            .find(|(placeholder, _)| *placeholder == name)
+            .map(|(_, field)| *field)
+            .ok_or_else(|| {
+                let names = Field::ALL.map(|(placeholder, _)| placeholder).join(", ");
+                format!("unknown field `{name}`, expected one of: {names}")
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed `--format-str` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    /// Whether the template has a placeholder for a field
+    pub fn uses(&self, field: Field) -> bool {
+        self.pieces.contains(&Piece::Field(field))
+    }
+
+    /// Fill in the template, getting the value of each placeholder from `value`
+    pub fn render<F>(&self, mut value: F) -> Result<String>
+    where
+        F: FnMut(Field) -> Result<String>,
+    {
+        let mut rendered = String::new();
+
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => rendered.push_str(text),
+                Piece::Field(field) => rendered.push_str(&value(*field)?),
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let (name, rest) = chars
+                        .as_str()
+                        .split_once('}')
+                        .ok_or("unclosed `{` in template, write `{{` for a literal brace")?;
+                    let field = name.trim().parse::<Field>()?;
+                    chars = rest.chars();
+
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Field(field));
+                }
+                '}' => {
+                    return Err("unmatched `}` in template, write `}}` for a literal brace".into())
+                }
+                _ => text.push(char),
+            }
+        }
+
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+
+        Ok(Self { pieces })
+    }
+}
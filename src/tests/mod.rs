@@ -1,5 +1,29 @@
-use crate::{get_index_path, Package, Query};
-use std::path::PathBuf;
+use crate::{
+    availability::{is_reserved, separator_spellings, Availability},
+    checksum::parse_crate_file_name,
+    compat::{compat, Bump},
+    diff::feature_history,
+    download::RegistryConfig,
+    error::{Error, NameError},
+    extensions::Extensions,
+    features::FeatureValue,
+    get_index_path,
+    graph::{Charset, DependencyGraph, NodeId},
+    lockfile::{LockIssue, LockedPackage, Lockfile, PackageVersion},
+    manifest::{update_requirements, Manifest, RequirementUpdate, Workspace},
+    normalize_name,
+    resolve::{Resolver, Unifier},
+    sbom::{Sbom, Supplier},
+    suggest::{edit_distance, rank},
+    target::Target,
+    validate_crate_name, Dependency, DependencyKind, Package, Query, Release, SelectionPolicy,
+    YankPolicy, CRATES_IO_INDEX_URL,
+};
+use semver::{Version, VersionReq};
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::PathBuf,
+};
 
 fn read_test_file(path: &str) -> String {
     let path = PathBuf::from(file!())
@@ -13,32 +37,38 @@ fn read_test_file(path: &str) -> String {
 
 #[test]
 fn test_get_index_path_1() {
-    assert_eq!(get_index_path("a"), "1/a");
+    assert_eq!(get_index_path("a").expect("index path"), "1/a");
 }
 
 #[test]
 fn test_get_index_path_2() {
-    assert_eq!(get_index_path("ab"), "2/ab");
+    assert_eq!(get_index_path("ab").expect("index path"), "2/ab");
 }
 
 #[test]
 fn test_get_index_path_3() {
-    assert_eq!(get_index_path("abc"), "3/a/abc");
+    assert_eq!(get_index_path("abc").expect("index path"), "3/a/abc");
 }
 
 #[test]
 fn test_get_index_path_4() {
-    assert_eq!(get_index_path("abcd"), "ab/cd/abcd");
+    assert_eq!(get_index_path("abcd").expect("index path"), "ab/cd/abcd");
 }
 
 #[test]
 fn test_get_index_path_long() {
-    assert_eq!(get_index_path("abcdefgh"), "ab/cd/abcdefgh");
+    assert_eq!(
+        get_index_path("abcdefgh").expect("index path"),
+        "ab/cd/abcdefgh"
+    );
 }
 
 #[test]
 fn test_get_index_path_caps() {
-    assert_eq!(get_index_path("AbcDefGH"), "ab/cd/abcdefgh");
+    assert_eq!(
+        get_index_path("AbcDefGH").expect("index path"),
+        "ab/cd/abcdefgh"
+    );
 }
 
 #[test]
@@ -58,6 +88,62 @@ fn make_query_with_version() {
     );
 }
 
+#[test]
+fn make_query_cargo_add_specs() {
+    let query: Query = "serde@1.2".parse().expect("parse query");
+    assert_eq!(
+        query.version_req,
+        Some("^1.2".parse().expect("version req"))
+    );
+
+    let query: Query = "serde@=1.2.3".parse().expect("parse query");
+    assert_eq!(
+        query.version_req,
+        Some("=1.2.3".parse().expect("version req"))
+    );
+
+    let query: Query = "serde@".parse().expect("parse query");
+    assert_eq!(query.name(), "serde");
+    assert!(query.version_req.is_none());
+}
+
+#[test]
+fn make_query_name_candidates() {
+    let query: Query = "serde-json".parse().expect("parse query");
+    assert_eq!(query.name_candidates(), ["serde-json", "serde_json"]);
+
+    let query: Query = "foo_bar-baz".parse().expect("parse query");
+    assert_eq!(
+        query.name_candidates(),
+        ["foo_bar-baz", "foo_bar_baz", "foo-bar-baz"]
+    );
+
+    let query: Query = "serde".parse().expect("parse query");
+    assert_eq!(query.name_candidates(), ["serde"]);
+}
+
+#[test]
+fn make_query_with_features() {
+    let query: Query = "tokio@^1+rt,macros".parse().expect("parse query");
+    assert_eq!(query.name(), "tokio");
+    assert_eq!(query.version_req, Some("^1".parse().expect("version req")));
+    assert_eq!(query.features(), ["rt", "macros"]);
+
+    let query: Query = "tokio@^1?features=rt,macros".parse().expect("parse query");
+    assert_eq!(query.version_req, Some("^1".parse().expect("version req")));
+    assert_eq!(query.features(), ["rt", "macros"]);
+
+    let query: Query = "tokio+full".parse().expect("parse query");
+    assert_eq!(query.name(), "tokio");
+    assert!(query.version_req.is_none());
+    assert_eq!(query.with_features(["net"]).features(), ["full", "net"]);
+
+    assert!(matches!(
+        "tokio?default=false".parse::<Query>(),
+        Err(Error::InvalidQuery(_))
+    ));
+}
+
 #[test]
 fn test_get_specific_release() {
     let data = read_test_file("libc.index");
@@ -86,3 +172,2390 @@ fn test_get_latest_matching_release() {
         "0.1.12".parse().expect("version")
     );
 }
+
+#[test]
+fn test_selection_policy_permissive_allows_all() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    assert_eq!(
+        pkg.select(None, &SelectionPolicy::permissive())
+            .expect("release")
+            .vers,
+        "2.0.0-beta.1".parse().expect("version")
+    );
+    assert!(pkg
+        .select(
+            Some(&"=1.2.0".parse().expect("semver")),
+            &SelectionPolicy::permissive()
+        )
+        .is_some());
+}
+
+#[test]
+fn test_selection_policy_default_skips_yanked() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let policy = SelectionPolicy::default();
+
+    assert!(pkg
+        .select(Some(&"=1.2.0".parse().expect("semver")), &policy)
+        .is_none());
+    assert_eq!(
+        pkg.select(Some(&"^1".parse().expect("semver")), &policy)
+            .expect("release")
+            .vers,
+        "1.1.0".parse().expect("version")
+    );
+}
+
+#[test]
+fn test_selection_policy_prereleases() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let stable = SelectionPolicy::default();
+    let prereleases = SelectionPolicy::default().include_prereleases(true);
+    let major = "^2".parse().expect("semver");
+    let exact = "=2.0.0-beta.1".parse().expect("semver");
+
+    assert_eq!(
+        pkg.select(None, &stable).expect("release").vers,
+        "1.1.0".parse().expect("version")
+    );
+    assert!(pkg.select(Some(&major), &stable).is_none());
+    assert!(pkg.select(Some(&exact), &stable).is_some());
+
+    let beta = "2.0.0-beta.1".parse().expect("version");
+    assert_eq!(pkg.select(None, &prereleases).expect("release").vers, beta);
+    assert_eq!(
+        pkg.select(Some(&major), &prereleases)
+            .expect("release")
+            .vers,
+        beta
+    );
+}
+
+#[test]
+fn test_yank_policy() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let req = "^1".parse().expect("semver");
+    let select = |yank_policy| {
+        pkg.select(
+            Some(&req),
+            &SelectionPolicy::default().yank_policy(yank_policy),
+        )
+        .map(|release| release.vers.to_string())
+    };
+
+    assert_eq!(select(YankPolicy::Exclude).as_deref(), Some("1.1.0"));
+    assert_eq!(select(YankPolicy::Include).as_deref(), Some("1.2.0"));
+    assert_eq!(select(YankPolicy::Only).as_deref(), Some("1.2.0"));
+
+    let query: Query = "policy@^1.0, <1.2"
+        .parse::<Query>()
+        .expect("parse query")
+        .with_yank_policy(YankPolicy::Only);
+    assert!(query.select(pkg.clone()).is_none());
+}
+
+#[test]
+fn test_latest_for_msrv() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let latest_for = |rust_version: &str| {
+        pkg.latest_for_msrv(&rust_version.parse().expect("version"))
+            .map(|release| release.vers.to_string())
+    };
+
+    assert_eq!(latest_for("1.60.0").as_deref(), Some("1.0.0"));
+    assert_eq!(latest_for("1.80.0").as_deref(), Some("1.1.0"));
+    assert_eq!(latest_for("1.50.0"), None);
+}
+
+#[test]
+fn test_latest_unyanked() {
+    // Drop the pre-release so the newest release is the yanked `1.2.0`
+    let data = read_test_file("policy.index")
+        .lines()
+        .filter(|line| !line.contains("beta"))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    assert!(pkg.latest().expect("latest").yanked);
+    assert_eq!(
+        pkg.latest_unyanked().expect("latest unyanked").vers,
+        "1.1.0".parse().expect("version")
+    );
+    assert_eq!(
+        pkg.into_latest_unyanked().expect("latest unyanked").vers,
+        "1.1.0".parse().expect("version")
+    );
+}
+
+#[test]
+fn test_selection_policy_restricted() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let policy = SelectionPolicy::default()
+        .include_yanked(false)
+        .include_prereleases(false)
+        .rust_version(Some("1.60.0".parse().expect("version")));
+
+    assert_eq!(
+        pkg.select(None, &policy).expect("release").vers,
+        "1.0.0".parse().expect("version")
+    );
+    assert!(pkg
+        .select(Some(&"=1.2.0".parse().expect("semver")), &policy)
+        .is_none());
+}
+
+#[test]
+fn test_stable_releases() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    assert_eq!(pkg.stable_releases().count(), 3);
+    assert_eq!(
+        pkg.latest().expect("latest").vers,
+        "2.0.0-beta.1".parse().expect("version")
+    );
+    assert_eq!(
+        pkg.latest_stable().expect("latest stable").vers,
+        "1.2.0".parse().expect("version")
+    );
+    assert_eq!(
+        pkg.into_latest_stable().expect("latest stable").vers,
+        "1.2.0".parse().expect("version")
+    );
+}
+
+#[test]
+fn test_versions_matching() {
+    let data = read_test_file("policy.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    let versions = pkg
+        .versions_matching(&"^1".parse().expect("semver"))
+        .map(|release| release.vers.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(versions, ["1.2.0", "1.1.0", "1.0.0"]);
+
+    assert_eq!(
+        pkg.versions_matching(&"^3".parse().expect("semver"))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_releases_sorted_by_version() {
+    let data = read_test_file("libc.index");
+    let reversed = data.lines().rev().collect::<Vec<&str>>().join("\n");
+    let pkg = Package::from_index(reversed).expect("package from index");
+
+    assert_eq!(
+        pkg.latest().expect("latest").vers,
+        "0.2.153".parse().expect("version")
+    );
+    assert!(pkg
+        .releases()
+        .windows(2)
+        .all(|pair| pair[0].vers <= pair[1].vers));
+}
+
+#[test]
+fn test_from_index_lenient_skips_bad_lines() {
+    let data = read_test_file("libc.index");
+    let mut lines = data.lines().collect::<Vec<&str>>();
+    lines.insert(1, r#"{"name":"libc","vers":"not-a-version"}"#);
+    let data = lines.join("\n");
+
+    assert!(Package::from_index(&data).is_err());
+
+    let pkg = Package::from_index_lenient(&data).expect("package from index");
+    assert_eq!(pkg.releases().len(), lines.len() - 1);
+    assert_eq!(pkg.warnings().len(), 1);
+    assert_eq!(pkg.warnings()[0].line, 2);
+}
+
+#[test]
+fn test_package_serde_round_trip() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    let json = pkg.as_json_string().expect("serialize package");
+    let restored: Package = serde_json::from_str(&json).expect("deserialize package");
+
+    assert_eq!(restored.name(), pkg.name());
+    assert_eq!(restored.index_path(), pkg.index_path());
+    assert_eq!(restored.releases().len(), pkg.releases().len());
+    assert!(restored.warnings().is_empty());
+}
+
+#[test]
+fn test_extensions_lifecycle() {
+    let extensions = Extensions::from_json(
+        r#"{"crates":{"policy":{"deprecated":"use something else","versions":[
+            {"req":"<1.1.0","eol":"2024-06-30","tier":"unsupported"},
+            {"req":"^1","tier":"supported"}
+        ]}}}"#,
+    )
+    .expect("extensions");
+    let pkg = Package::from_index(read_test_file("policy.index")).expect("package from index");
+
+    let old = pkg
+        .version(&"=1.0.0".parse().expect("semver"))
+        .expect("release");
+    let lifecycle = extensions.lifecycle(old).expect("lifecycle");
+    assert_eq!(lifecycle.eol.as_deref(), Some("2024-06-30"));
+    assert_eq!(lifecycle.tier.as_deref(), Some("unsupported"));
+
+    let new = pkg
+        .version(&"=1.1.0".parse().expect("semver"))
+        .expect("release");
+    let lifecycle = extensions.lifecycle(new).expect("lifecycle");
+    assert_eq!(lifecycle.deprecated.as_deref(), Some("use something else"));
+    assert_eq!(lifecycle.eol, None);
+    assert_eq!(lifecycle.tier.as_deref(), Some("supported"));
+
+    let libc = Package::from_index(read_test_file("libc.index")).expect("package from index");
+    assert!(extensions
+        .lifecycle(libc.latest().expect("latest"))
+        .is_none());
+}
+
+#[test]
+fn test_get_index_path_invalid() {
+    assert!(matches!(
+        get_index_path("ñandú"),
+        Err(Error::InvalidName(NameError::InvalidStart('ñ')))
+    ));
+    assert!(matches!(
+        get_index_path("aé"),
+        Err(Error::InvalidName(NameError::InvalidChar('é')))
+    ));
+    assert!(matches!(
+        get_index_path(""),
+        Err(Error::InvalidName(NameError::Empty))
+    ));
+}
+
+#[test]
+fn test_validate_crate_name() {
+    assert_eq!(validate_crate_name("serde_json"), Ok(()));
+    assert_eq!(validate_crate_name("Inflector-2"), Ok(()));
+    assert_eq!(validate_crate_name(""), Err(NameError::Empty));
+    assert_eq!(
+        validate_crate_name("_foo"),
+        Err(NameError::InvalidStart('_'))
+    );
+    assert_eq!(
+        validate_crate_name("foo bar"),
+        Err(NameError::InvalidChar(' '))
+    );
+    assert_eq!(
+        validate_crate_name("foo.rs"),
+        Err(NameError::InvalidChar('.'))
+    );
+    assert_eq!(
+        validate_crate_name(&"a".repeat(65)),
+        Err(NameError::TooLong(65))
+    );
+    assert!(validate_crate_name(&"a".repeat(64)).is_ok());
+}
+
+#[test]
+fn test_normalize_name() {
+    assert_eq!(normalize_name("SERDE"), "serde");
+    assert_eq!(normalize_name("serde-json"), normalize_name("Serde_Json"));
+}
+
+#[test]
+fn make_query_trims_whitespace() {
+    let query: Query = " Serde@1 ".parse().expect("parse query");
+    assert_eq!(query.name(), "Serde");
+    assert_eq!(
+        get_index_path(query.name()).expect("index path"),
+        "se/rd/serde"
+    );
+}
+
+#[test]
+fn test_display_release_and_package() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    assert_eq!(
+        pkg.latest().expect("latest").to_string(),
+        "libc 0.2.153 (1 dep, 7 features)"
+    );
+    assert_eq!(
+        pkg.version(&"=0.1.11".parse().expect("semver"))
+            .expect("release")
+            .to_string(),
+        "libc 0.1.11 (0 deps, 2 features, yanked)"
+    );
+
+    let summary = pkg.to_string();
+    assert!(summary.starts_with("libc\n  index path: li/bc/libc\n"));
+    assert!(summary.ends_with("  latest: libc 0.2.153 (1 dep, 7 features)"));
+}
+
+#[test]
+fn test_release_eq_hash_ord() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+
+    let mut releases = pkg
+        .releases()
+        .iter()
+        .rev()
+        .cloned()
+        .collect::<Vec<Release>>();
+    releases.sort();
+    assert_eq!(&releases, pkg.releases());
+
+    let mut unique = HashSet::new();
+    assert!(unique.insert(pkg.latest().expect("latest").clone()));
+    assert!(!unique.insert(pkg.latest().expect("latest").clone()));
+
+    // Releases that only differ in data from the API aren't equal, in `Eq` or `Ord`
+    let latest = pkg.latest().expect("latest");
+    let published = Release {
+        published: Some("2024-05-01T12:00:00Z".to_owned()),
+        ..latest.clone()
+    };
+    assert_ne!(latest, &published);
+    assert_ne!(latest.cmp(&published), std::cmp::Ordering::Equal);
+    let licensed = Release {
+        license: Some("MIT".to_owned()),
+        ..latest.clone()
+    };
+    assert_ne!(latest, &licensed);
+    assert_ne!(latest.cmp(&licensed), std::cmp::Ordering::Equal);
+
+    let deps = pkg
+        .releases()
+        .iter()
+        .flat_map(|release| release.deps.iter().cloned())
+        .collect::<BTreeSet<Dependency>>();
+    assert!(deps
+        .iter()
+        .all(|dep| dep.name == "rustc-std-workspace-core"));
+}
+
+#[test]
+fn test_dependency_kind_serde() {
+    let parse = |kind: &str| -> Dependency {
+        serde_json::from_str(&format!(
+            r#"{{"name":"a","req":"^1","features":[],"optional":false,"default_features":true,"target":null{kind}}}"#
+        ))
+        .expect("dependency")
+    };
+
+    assert_eq!(parse("").kind, DependencyKind::Normal);
+    assert_eq!(parse(r#","kind":null"#).kind, DependencyKind::Normal);
+    assert_eq!(parse(r#","kind":"dev""#).kind, DependencyKind::Dev);
+    assert_eq!(parse(r#","kind":"build""#).kind, DependencyKind::Build);
+
+    let unknown = parse(r#","kind":"docs""#);
+    assert_eq!(unknown.kind, DependencyKind::Unknown("docs".to_owned()));
+    assert!(serde_json::to_string(&unknown)
+        .expect("serialize")
+        .contains(r#""kind":"docs""#));
+}
+
+#[test]
+fn test_cross_check() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(&data).expect("package from index");
+
+    let reordered = data.lines().rev().collect::<Vec<&str>>().join("\n");
+    let mirror = Package::from_index(reordered).expect("package from index");
+    assert!(pkg.cross_check(&mirror).is_ok());
+
+    let tampered = data.replace(
+        "9c198f91728a82281a64e1f4f9eeb25d82cb32a5de251c6bd1b5154d63a8e7bd",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+    let mirror = Package::from_index(tampered).expect("package from index");
+    assert!(matches!(
+        pkg.cross_check(&mirror),
+        Err(Error::CrossCheck(_))
+    ));
+}
+
+#[test]
+fn test_target_parse_triple() {
+    let target: Target = "x86_64-pc-windows-msvc".parse().expect("target");
+    assert_eq!(target, Target::Triple("x86_64-pc-windows-msvc".to_owned()));
+    assert!(target.applies_to("x86_64-pc-windows-msvc"));
+    assert!(!target.applies_to("x86_64-pc-windows-gnu"));
+}
+
+#[test]
+fn test_target_parse_cfg() {
+    let target: Target = r#"cfg(all(unix, not(target_os = "macos"), any(target_arch = "x86_64", target_arch = "aarch64")))"#
+        .parse()
+        .expect("target");
+
+    assert!(target.applies_to("x86_64-unknown-linux-gnu"));
+    assert!(target.applies_to("aarch64-unknown-linux-musl"));
+    assert!(!target.applies_to("i686-unknown-linux-gnu"));
+    assert!(!target.applies_to("aarch64-apple-darwin"));
+    assert!(!target.applies_to("x86_64-pc-windows-msvc"));
+    assert_eq!(
+        target.to_string(),
+        r#"cfg(all(unix, not(target_os = "macos"), any(target_arch = "x86_64", target_arch = "aarch64")))"#
+    );
+}
+
+#[test]
+fn test_target_cfg_values() {
+    let applies =
+        |cfg: &str, triple: &str| cfg.parse::<Target>().expect("target").applies_to(triple);
+
+    assert!(applies("cfg(windows)", "x86_64-pc-windows-gnu"));
+    assert!(applies(
+        r#"cfg(target_env = "msvc")"#,
+        "i686-pc-windows-msvc"
+    ));
+    assert!(applies(
+        r#"cfg(target_os = "android")"#,
+        "armv7-linux-androideabi"
+    ));
+    assert!(applies(
+        r#"cfg(target_family = "wasm")"#,
+        "wasm32-unknown-unknown"
+    ));
+    assert!(!applies("cfg(unix)", "wasm32-unknown-unknown"));
+    assert!(applies(
+        r#"cfg(all(target_os = "wasi", target_env = "p2"))"#,
+        "wasm32-wasip2"
+    ));
+    assert!(applies(
+        r#"cfg(target_pointer_width = "32")"#,
+        "i686-unknown-linux-gnu"
+    ));
+    assert!(applies(
+        r#"cfg(target_endian = "big")"#,
+        "s390x-unknown-linux-gnu"
+    ));
+    assert!(!applies(
+        r#"cfg(target_feature = "sse2")"#,
+        "x86_64-unknown-linux-gnu"
+    ));
+}
+
+#[test]
+fn test_target_parse_invalid() {
+    assert!("cfg(all(unix)".parse::<Target>().is_err());
+    assert!("cfg(target_os = )".parse::<Target>().is_err());
+    assert!("cfg(not(unix, windows))".parse::<Target>().is_err());
+    assert!("".parse::<Target>().is_err());
+}
+
+#[test]
+fn test_filtered_dependencies() {
+    fn names<'a>(deps: impl Iterator<Item = &'a Dependency>) -> Vec<&'a str> {
+        deps.map(|dep| dep.name.as_str()).collect()
+    }
+
+    let data = read_test_file("getrandom.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let release = pkg
+        .version(&"=0.2.15".parse().expect("semver"))
+        .expect("release");
+
+    assert_eq!(names(release.dev_deps()), ["wasm-bindgen-test"]);
+    assert_eq!(release.normal_deps().count(), release.deps.len() - 1);
+    assert_eq!(release.build_deps().count(), 0);
+    assert_eq!(
+        names(release.deps_for_target("x86_64-unknown-linux-gnu")),
+        ["cfg-if", "compiler_builtins", "core", "libc"]
+    );
+    assert_eq!(
+        names(release.deps_for_target("wasm32-wasi")),
+        ["cfg-if", "compiler_builtins", "core", "wasi"]
+    );
+    assert_eq!(
+        names(release.deps_for_target("wasm32-unknown-unknown")),
+        [
+            "cfg-if",
+            "compiler_builtins",
+            "core",
+            "js-sys",
+            "wasm-bindgen",
+            "wasm-bindgen-test"
+        ]
+    );
+}
+
+#[test]
+fn test_all_features_merges_features2() {
+    let data = read_test_file("getrandom.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let release = pkg
+        .version(&"=0.3.1".parse().expect("semver"))
+        .expect("release");
+
+    assert_eq!(release.features.keys().collect::<Vec<_>>(), ["std"]);
+
+    let features = release.all_features();
+    assert_eq!(
+        features.keys().collect::<Vec<_>>(),
+        ["rustc-dep-of-std", "std", "wasm_js"]
+    );
+    assert_eq!(features["wasm_js"], ["dep:wasm-bindgen", "dep:js-sys"]);
+
+    let old = pkg
+        .version(&"=0.2.15".parse().expect("semver"))
+        .expect("release");
+    assert_eq!(old.all_features(), old.features);
+}
+
+#[test]
+fn test_supports_rust_version() {
+    let release = feats_release();
+    let version = |version: &str| version.parse::<semver::Version>().expect("version");
+
+    assert!(release.supports_rust_version(&version("1.60.0")));
+    assert!(release.supports_rust_version(&version("1.81.0")));
+    assert!(release.supports_rust_version(&version("2.0.0")));
+    assert!(release.supports_rust_version(&version("1.60.0-nightly")));
+    assert!(!release.supports_rust_version(&version("1.59.0")));
+
+    let unconstrained = Release {
+        rust_version: None,
+        ..release
+    };
+    assert!(unconstrained.supports_rust_version(&version("1.0.0")));
+}
+
+fn feats_release() -> Release {
+    Package::from_index(read_test_file("feats.index"))
+        .expect("package from index")
+        .into_latest()
+        .expect("release")
+}
+
+fn set<const N: usize>(items: [&str; N]) -> BTreeSet<String> {
+    items.into_iter().map(ToOwned::to_owned).collect()
+}
+
+#[test]
+fn test_resolve_default_features() {
+    let resolved = feats_release()
+        .resolve_features(&[], true)
+        .expect("resolve features");
+
+    assert_eq!(resolved.features, set(["default", "std"]));
+    assert!(resolved.optional_deps.is_empty());
+    assert_eq!(resolved.dep_features["log"], set(["std"]));
+    assert!(!resolved.dep_features.contains_key("serde"));
+}
+
+#[test]
+fn test_default_features_implicit() {
+    let old = Package::from_index(read_test_file("feats.index"))
+        .expect("package from index")
+        .into_version(&"=0.9.0".parse().expect("semver"))
+        .expect("release");
+
+    let defaults = old.default_features();
+    assert_eq!(defaults.features, set(["default", "rand", "std"]));
+    assert_eq!(defaults.optional_deps, set(["rand"]));
+    assert_eq!(defaults.dep_features["rand"], set(["std"]));
+
+    assert_eq!(
+        feats_release().default_features(),
+        feats_release()
+            .resolve_features(&[], true)
+            .expect("resolve features")
+    );
+}
+
+#[test]
+fn test_resolve_features_dep_syntax_and_weak() {
+    let resolved = feats_release()
+        .resolve_features(&["json"], true)
+        .expect("resolve features");
+
+    assert_eq!(resolved.features, set(["default", "json", "serde", "std"]));
+    assert_eq!(resolved.optional_deps, set(["serde", "serde_json"]));
+    assert_eq!(resolved.dep_features["serde"], set(["std"]));
+    assert_eq!(resolved.dep_features["serde_json"], set(["std"]));
+}
+
+#[test]
+fn test_resolve_features_implicit() {
+    let release = feats_release();
+
+    let resolved = release
+        .resolve_features(&["small_rng"], false)
+        .expect("resolve features");
+    assert_eq!(resolved.features, set(["rand", "small_rng"]));
+    assert_eq!(resolved.optional_deps, set(["rand"]));
+    assert_eq!(resolved.dep_features["rand"], set(["small_rng"]));
+
+    assert!(matches!(
+        release.resolve_features(&["serde_json"], false),
+        Err(Error::UnknownFeature(_))
+    ));
+    assert!(matches!(
+        release.resolve_features(&["nope"], true),
+        Err(Error::UnknownFeature(_))
+    ));
+}
+
+#[test]
+fn test_activated_deps() {
+    let release = feats_release();
+    let activated = release
+        .activated_deps(&["serde"], false)
+        .expect("activated deps");
+
+    let names = activated
+        .iter()
+        .map(|activated| activated.dependency.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, ["log", "serde", "tempfile"]);
+
+    let serde = activated
+        .iter()
+        .find(|activated| activated.dependency.name == "serde")
+        .expect("serde");
+    assert_eq!(serde.features, set(["derive"]));
+
+    let activated = release
+        .activated_deps(&["serde"], true)
+        .expect("activated deps");
+    let serde = activated
+        .iter()
+        .find(|activated| activated.dependency.name == "serde")
+        .expect("serde");
+    assert_eq!(serde.features, set(["derive", "std"]));
+}
+
+#[test]
+fn test_compat() {
+    let bump = |from: &str, to: &str| {
+        compat(
+            &from.parse().expect("version"),
+            &to.parse().expect("version"),
+        )
+    };
+
+    assert_eq!(bump("1.2.3", "1.2.4"), Bump::Patch);
+    assert_eq!(bump("1.2.3", "1.3.0"), Bump::Minor);
+    assert_eq!(bump("1.2.3", "2.0.0"), Bump::Major);
+    assert_eq!(bump("0.1.2", "0.1.3"), Bump::Minor);
+    assert_eq!(bump("0.1.2", "0.2.0"), Bump::Major);
+    assert_eq!(bump("0.0.1", "0.0.2"), Bump::Major);
+    assert_eq!(bump("0.9.0", "1.0.0"), Bump::Major);
+    assert_eq!(bump("1.3.0", "1.2.3"), Bump::Minor);
+    assert_eq!(bump("1.0.0-beta.1", "1.0.0"), Bump::Patch);
+    assert!(bump("0.1.0", "0.2.0").is_breaking());
+}
+
+#[test]
+fn test_release_diff() {
+    let pkg = Package::from_index(read_test_file("feats.index")).expect("package from index");
+    let old = pkg
+        .version(&"=0.9.0".parse().expect("semver"))
+        .expect("release");
+    let new = pkg.latest().expect("latest");
+    let diff = old.diff(new);
+
+    assert_eq!(diff.bump, Bump::Major);
+    let added = diff
+        .added_deps
+        .iter()
+        .map(|dep| dep.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(added, ["serde", "serde_json", "tempfile"]);
+    assert!(diff.removed_deps.is_empty());
+    assert_eq!(diff.changed_deps.len(), 1);
+    assert_eq!(
+        diff.changed_deps[0].new.req,
+        "^0.8".parse().expect("semver")
+    );
+    assert_eq!(diff.added_features, set(["json", "serde", "small_rng"]));
+    assert!(diff.removed_features.is_empty());
+    assert_eq!(diff.changed_features, set(["default", "std"]));
+    assert_eq!(
+        diff.rust_version.map(|change| change.new),
+        Some(Some("1.60".parse().expect("semver")))
+    );
+    assert!(diff.yanked.is_none());
+
+    assert!(new.diff(new).is_empty());
+}
+
+#[test]
+fn test_release_diff_kind_change() {
+    let pkg = Package::from_index(read_test_file("feats.index")).expect("package from index");
+    let old = pkg.latest().expect("latest");
+    let mut new = old.clone();
+    new.deps
+        .iter_mut()
+        .filter(|dep| dep.name == "log")
+        .for_each(|dep| dep.kind = DependencyKind::Dev);
+
+    let diff = old.diff(&new);
+    assert!(diff.added_deps.is_empty() && diff.removed_deps.is_empty());
+    assert_eq!(diff.changed_deps.len(), 1);
+    assert_eq!(diff.changed_deps[0].old.kind, DependencyKind::Normal);
+    assert_eq!(diff.changed_deps[0].new.kind, DependencyKind::Dev);
+    assert_eq!(
+        diff.to_string(),
+        "feats 1.0.0 -> 1.0.0 (patch)\n  ~ dep log (normal -> dev)"
+    );
+}
+
+#[test]
+fn test_feature_history() {
+    let pkg = Package::from_index(read_test_file("getrandom.index")).expect("package from index");
+    let version_req = ">=0.2.10".parse().expect("semver");
+    let releases = pkg
+        .versions_matching(&version_req)
+        .filter(|release| release.vers.pre.is_empty());
+    let history = feature_history(releases);
+
+    let set = |features: &[&str]| features.iter().map(|f| f.to_string()).collect();
+    let changes = |version: &str| {
+        let version = version.parse::<Version>().expect("version");
+        history
+            .iter()
+            .find(|changes| changes.version == version)
+            .map(|changes| (changes.added.clone(), changes.removed.clone()))
+    };
+
+    // The oldest release lists every feature it has
+    assert_eq!(history[0].version, Version::new(0, 2, 10));
+    assert!(history[0].added.contains("js"));
+    assert_eq!(
+        changes("0.2.13"),
+        Some((set(&["linux_disable_fallback"]), set(&[])))
+    );
+    assert_eq!(
+        changes("0.3.0"),
+        Some((
+            set(&["wasm_js"]),
+            set(&[
+                "custom",
+                "js",
+                "linux_disable_fallback",
+                "rdrand",
+                "test-in-browser"
+            ])
+        ))
+    );
+    assert_eq!(
+        changes("0.3.4"),
+        Some((set(&[]), set(&["rustc-dep-of-std"])))
+    );
+    // Releases that change nothing are left out, even when listed out of order in the index
+    assert_eq!(changes("0.2.16"), None);
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0].version < pair[1].version));
+}
+
+#[test]
+fn test_verify_checksum() {
+    let mut release = feats_release();
+    // SHA256 of `hello world`
+    release.cksum = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_owned();
+
+    assert!(release.verify_checksum(b"hello world").is_ok());
+    assert!(release.verify_checksum_reader(&b"hello world"[..]).is_ok());
+    assert!(matches!(
+        release.verify_checksum(b"hello there"),
+        Err(Error::ChecksumMismatch { expected, .. }) if expected == release.cksum
+    ));
+}
+
+#[test]
+fn test_parse_crate_file_name() {
+    let parse = |file_name| {
+        parse_crate_file_name(file_name).map(|(name, version)| (name, version.to_string()))
+    };
+
+    assert_eq!(
+        parse("serde-1.0.219.crate"),
+        Some(("serde".to_owned(), "1.0.219".to_owned()))
+    );
+    assert_eq!(
+        parse("tracing-log-0.2.0.crate"),
+        Some(("tracing-log".to_owned(), "0.2.0".to_owned()))
+    );
+    // Pre-release and build metadata can contain `-` too
+    assert_eq!(
+        parse("foo-bar-1.0.0-alpha-1+build-2.crate"),
+        Some(("foo-bar".to_owned(), "1.0.0-alpha-1+build-2".to_owned()))
+    );
+    assert_eq!(parse("serde-1.0.219.tar.gz"), None);
+    assert_eq!(parse("serde.crate"), None);
+    assert_eq!(parse("-1.0.0.crate"), None);
+}
+
+#[test]
+fn test_normalized() {
+    let pkg = Package::from_index(read_test_file("feats.index")).expect("package from index");
+    let old = pkg
+        .version(&"=0.9.0".parse().expect("semver"))
+        .expect("release");
+    let normalized = old.normalized();
+
+    assert_eq!(old.v, 1);
+    assert_eq!(normalized.v, 2);
+    assert_eq!(normalized.features["rand"], ["dep:rand"]);
+    assert_eq!(
+        normalized
+            .resolve_features(&["rand"], true)
+            .expect("resolve"),
+        old.resolve_features(&["rand"], true).expect("resolve")
+    );
+
+    let new = pkg.latest().expect("latest");
+    let normalized = new.normalized();
+    assert!(normalized.features2.is_none());
+    assert_eq!(normalized.features.len(), new.all_features().len() + 1);
+    assert_eq!(normalized.features["json"], new.all_features()["json"]);
+    assert_eq!(normalized.features["rand"], ["dep:rand"]);
+    assert_eq!(normalized.normalized(), normalized);
+}
+
+#[test]
+fn test_feature_graph() {
+    let release = feats_release();
+    let graph = release.feature_graph();
+
+    assert_eq!(graph.enabled_by("json").collect::<Vec<&str>>(), ["serde"]);
+    assert_eq!(
+        graph.enabled_by("small_rng").collect::<Vec<&str>>(),
+        ["rand"]
+    );
+    assert_eq!(graph.enablers_of("std").collect::<Vec<&str>>(), ["default"]);
+    assert_eq!(
+        graph.enabling_dep("serde"),
+        BTreeSet::from(["json", "serde"])
+    );
+    assert_eq!(
+        graph.enabling_dep("rand"),
+        BTreeSet::from(["rand", "small_rng"])
+    );
+    assert_eq!(graph.enabling_dep("serde_json"), BTreeSet::from(["json"]));
+    assert_eq!(
+        graph.values("json").expect("json")[1],
+        FeatureValue::Dep("serde_json".to_owned())
+    );
+    assert!(graph.cycles().is_empty());
+
+    let mut cyclic = release;
+    cyclic.features2 = None;
+    cyclic.features = [("a", ["b"]), ("b", ["a"]), ("c", ["a"])]
+        .into_iter()
+        .map(|(name, enables)| (name.to_owned(), enables.map(ToOwned::to_owned).to_vec()))
+        .collect();
+    let graph = cyclic.feature_graph();
+    assert_eq!(graph.cycles(), [BTreeSet::from(["a", "b"])]);
+    assert_eq!(graph.enabled_by("c").collect::<Vec<&str>>(), ["a", "b"]);
+}
+
+#[cfg(feature = "cargo_metadata")]
+#[test]
+fn test_cargo_metadata_dependency_round_trip() {
+    let release = feats_release();
+    let mut deps = release.deps.clone();
+    deps[0].package = Some("log".to_owned());
+    deps[0].name = "logging".to_owned();
+    deps[0].target = Some("cfg(unix)".to_owned());
+
+    for dep in &deps {
+        let converted = cargo_metadata::Dependency::try_from(dep).expect("convert dependency");
+        assert_eq!(Dependency::from(&converted), *dep);
+    }
+
+    let renamed = cargo_metadata::Dependency::try_from(&deps[0]).expect("convert dependency");
+    assert_eq!(renamed.name, "log");
+    assert_eq!(renamed.rename.as_deref(), Some("logging"));
+    assert_eq!(renamed.kind, cargo_metadata::DependencyKind::Normal);
+}
+
+#[cfg(feature = "crates-index")]
+#[test]
+fn test_crates_index_round_trip() {
+    let pkg = Package::from_index(read_test_file("getrandom.index")).expect("package from index");
+
+    let krate = crates_index::Crate::try_from(&pkg).expect("convert package");
+    assert_eq!(krate.versions().len(), pkg.releases().len());
+
+    let converted = Package::try_from(&krate).expect("convert crate");
+    assert_eq!(converted.releases().len(), pkg.releases().len());
+    for (converted, original) in converted.releases().iter().zip(pkg.releases()) {
+        assert_eq!(converted.vers, original.vers);
+        assert_eq!(converted.deps, original.deps);
+        assert_eq!(converted.all_features(), original.all_features());
+        assert_eq!(converted.rust_version, original.rust_version);
+        assert_eq!(converted.cksum, original.cksum);
+    }
+
+    let version = crates_index::Version::try_from(&feats_release()).expect("convert");
+    assert_eq!(version.rust_version(), Some("1.60"));
+}
+
+#[test]
+fn test_availability_without_registry() {
+    assert!(is_reserved("std"));
+    assert!(is_reserved("Compiler_Builtins"));
+    assert!(is_reserved("NUL"));
+    assert!(!is_reserved("serde"));
+
+    // Invalid and reserved names are reported without fetching anything
+    let query: Query = "std".parse().expect("parse query");
+    assert_eq!(
+        query.availability().expect("availability"),
+        Availability::Reserved
+    );
+    let query: Query = "9lives".parse().expect("parse query");
+    assert_eq!(
+        query.availability().expect("availability"),
+        Availability::Invalid(NameError::InvalidStart('9'))
+    );
+}
+
+#[test]
+fn test_separator_spellings() {
+    assert_eq!(separator_spellings("serde").expect("spellings"), ["serde"]);
+
+    // Mixed separators are swapped one at a time, not only all at once
+    let spellings = separator_spellings("foo-bar_baz").expect("spellings");
+    assert_eq!(spellings[0], "foo-bar_baz");
+    assert_eq!(spellings.len(), 4);
+    for spelling in ["foo-bar-baz", "foo_bar-baz", "foo_bar_baz"] {
+        assert!(spellings.iter().any(|candidate| candidate == spelling));
+    }
+
+    assert_eq!(
+        separator_spellings("a-b-c-d-e").expect("spellings").len(),
+        16
+    );
+    assert!(separator_spellings("a-b-c-d-e-f").is_none());
+}
+
+#[test]
+fn test_suggestion_ranking() {
+    assert_eq!(edit_distance("tokio", "tokio"), 0);
+    assert_eq!(edit_distance("tokoi", "tokio"), 1);
+    assert_eq!(edit_distance("serde", "serd"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+    let candidates = ["tokio", "tokio-util", "toki", "tonic", "token"].map(ToOwned::to_owned);
+    assert_eq!(rank("tokoi", candidates.clone(), 3), ["tokio", "toki"]);
+    assert_eq!(rank("tokoi", candidates.clone(), 1), ["tokio"]);
+    assert_eq!(rank("tokio", candidates, 3), ["toki"]);
+    assert_eq!(
+        rank("serde-jsn", ["serde_json".to_owned()], 3),
+        ["serde_json"]
+    );
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_crate_info_from_json() {
+    let info = crate::api::CrateInfo::from_json(read_test_file("serde.api.json"))
+        .expect("parse crate info");
+
+    assert_eq!(info.name, "serde");
+    assert_eq!(
+        info.description.as_deref(),
+        Some("A generic serialization/deserialization framework")
+    );
+    assert_eq!(info.keywords, ["serde", "serialization", "no_std"]);
+    assert_eq!(info.categories, ["encoding", "no-std"]);
+    assert_eq!(info.homepage.as_deref(), Some("https://serde.rs"));
+    assert_eq!(
+        info.repository.as_deref(),
+        Some("https://github.com/serde-rs/serde")
+    );
+    assert_eq!(info.documentation.as_deref(), Some("https://docs.rs/serde"));
+
+    // Invalid names are rejected before making a request
+    assert!(matches!(
+        crate::api::Client::default().crate_info("no/such"),
+        Err(Error::InvalidName(NameError::InvalidChar('/')))
+    ));
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_downloads_from_json() {
+    let downloads = crate::api::Downloads::from_json(read_test_file("serde.api.json"))
+        .expect("parse downloads");
+
+    assert_eq!(downloads.total, 462016580);
+    assert_eq!(downloads.recent, Some(62185392));
+    assert_eq!(downloads.versions.len(), 3);
+    assert_eq!(
+        downloads.version(&semver::Version::new(1, 0, 210)),
+        Some(25339015)
+    );
+    assert_eq!(downloads.version(&semver::Version::new(1, 0, 208)), None);
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_crate_details_from_json() {
+    use crate::api::{CrateDetails, CrateInfo, Downloads, VersionInfo};
+
+    let content = read_test_file("serde.api.json");
+    let details = CrateDetails::from_json(&content).expect("parse crate details");
+
+    // The same response as each of the separate requests
+    assert_eq!(details.info, CrateInfo::from_json(&content).expect("info"));
+    assert_eq!(
+        details.downloads,
+        Downloads::from_json(&content).expect("downloads")
+    );
+    assert_eq!(
+        details.versions,
+        VersionInfo::from_json(&content).expect("versions")
+    );
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_owners_from_json() {
+    use crate::api::{Owner, OwnerKind};
+
+    let owners = Owner::from_json(read_test_file("serde.owners.json")).expect("parse owners");
+
+    assert_eq!(owners.len(), 2);
+    assert_eq!(owners[0].login, "dtolnay");
+    assert_eq!(owners[0].kind, OwnerKind::User);
+    assert_eq!(owners[0].name.as_deref(), Some("David Tolnay"));
+    assert_eq!(owners[1].login, "github:serde-rs:publish");
+    assert_eq!(owners[1].kind, OwnerKind::Team);
+    assert_eq!(owners[1].name, None);
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_version_info() {
+    let versions = crate::api::VersionInfo::from_json(read_test_file("serde.api.json"))
+        .expect("parse versions");
+    assert_eq!(versions.len(), 3);
+    let latest = &versions[&semver::Version::new(1, 0, 210)];
+    assert_eq!(latest.published, "2024-09-06T21:16:07.245524+00:00");
+    assert_eq!(latest.license.as_deref(), Some("MIT OR Apache-2.0"));
+
+    let package = Package::from_index(read_test_file("feats.index")).expect("package from index");
+    assert!(package
+        .releases()
+        .iter()
+        .all(|release| release.published.is_none()));
+
+    let version = |version: &str| version.parse::<semver::Version>().expect("version");
+    let info = crate::api::VersionInfo {
+        published: "2024-09-06T21:16:07.245524+00:00".to_owned(),
+        license: Some("MIT".to_owned()),
+    };
+    let package = package.with_version_info(&[(version("1.0.0"), info)].into());
+    let release = |vers: &str| {
+        package
+            .releases()
+            .iter()
+            .find(|release| release.vers == version(vers))
+            .expect("release")
+    };
+
+    assert_eq!(
+        release("1.0.0").published.as_deref(),
+        Some("2024-09-06T21:16:07.245524+00:00")
+    );
+    assert_eq!(release("1.0.0").license.as_deref(), Some("MIT"));
+    let unknown = release("0.9.0");
+    assert_eq!(unknown.published, None);
+    assert_eq!(unknown.license, None);
+
+    // Dates are left out of the index format when they haven't been fetched
+    assert!(!unknown
+        .as_json_string()
+        .expect("json")
+        .contains("published"));
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_reverse_dependencies_from_json() {
+    let rdeps = crate::api::ReverseDependencies::from_json(read_test_file("serde.rdeps.json"))
+        .expect("parse reverse dependencies");
+
+    assert_eq!(rdeps.total, 41235);
+    // The dev dependency's version isn't in the response, so it's skipped
+    assert_eq!(rdeps.dependents.len(), 2);
+
+    let json = &rdeps.dependents[0];
+    assert_eq!(json.name, "serde_json");
+    assert_eq!(json.vers, semver::Version::new(1, 0, 111));
+    assert_eq!(json.req, "^1.0.194");
+    assert_eq!(json.kind, DependencyKind::Normal);
+    assert!(!json.optional);
+
+    assert_eq!(rdeps.dependents[1].name, "indexmap");
+    assert!(rdeps.dependents[1].optional);
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_search_from_json() {
+    use crate::api::{Client, SearchResult};
+
+    let results = SearchResult::from_json(read_test_file("search.api.json")).expect("parse");
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].name, "serde_json");
+    assert_eq!(
+        results[0].description.as_deref(),
+        Some("A JSON serialization file format")
+    );
+    assert_eq!(results[0].latest, semver::Version::new(1, 0, 128));
+    // Crates without a stable release report their latest pre-release
+    assert_eq!(results[2].latest.to_string(), "0.19.0-rc.1");
+    assert_eq!(results[2].description, None);
+
+    // The API is found from the index's config
+    assert!(Client::from_config(&RegistryConfig::crates_io()).is_some());
+    let config = RegistryConfig::from_json(r#"{"dl": "https://example.com/crates"}"#);
+    assert!(Client::from_config(&config.expect("config")).is_none());
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_docs_status() {
+    use crate::docs_rs::{BuildStatus, DocsStatus};
+
+    let status = BuildStatus::from_json(read_test_file("serde.docs.json")).expect("parse status");
+    assert_eq!(
+        status,
+        BuildStatus {
+            version: semver::Version::new(1, 0, 210),
+            built: true,
+        }
+    );
+
+    let failed = DocsStatus {
+        latest: BuildStatus {
+            version: semver::Version::new(1, 0, 211),
+            built: false,
+        },
+        latest_documented: Some(status.version.clone()),
+    };
+    assert_eq!(
+        failed.to_string(),
+        "not built for 1.0.211, latest documented is 1.0.210"
+    );
+
+    let built = DocsStatus {
+        latest: status,
+        latest_documented: Some(semver::Version::new(1, 0, 210)),
+    };
+    assert_eq!(built.to_string(), "built for 1.0.210");
+}
+
+#[test]
+#[cfg(feature = "api")]
+fn test_advisories_from_json() {
+    use crate::advisories::{Advisory, AdvisoryKind};
+
+    let advisories = Advisory::from_json(read_test_file("osv.query.json")).expect("parse");
+
+    // Advisories from other databases are skipped
+    assert_eq!(advisories.len(), 2);
+    assert_eq!(advisories[0].id, "RUSTSEC-2020-0071");
+    assert_eq!(advisories[0].kind, AdvisoryKind::Vulnerability);
+    assert_eq!(advisories[0].aliases[0], "CVE-2020-26235");
+    assert_eq!(advisories[1].kind, AdvisoryKind::Unmaintained);
+    assert_eq!(
+        advisories[1].to_string(),
+        "RUSTSEC-2021-0139 (unmaintained): ansi_term is Unmaintained"
+    );
+
+    assert!(Advisory::from_json("{}").expect("parse").is_empty());
+}
+
+#[test]
+fn test_download_url() {
+    let release = Release {
+        name: "Serde".to_owned(),
+        ..feats_release()
+    };
+
+    assert_eq!(
+        RegistryConfig::crates_io().download_url(&release),
+        "https://static.crates.io/crates/Serde/1.0.0/download"
+    );
+
+    let config = RegistryConfig::from_json(
+        r#"{"dl": "https://example.com/{prefix}/{lowerprefix}/{crate}-{version}.crate?{sha256-checksum}"}"#,
+    )
+    .expect("parse config");
+    assert_eq!(config.api, None);
+    assert_eq!(
+        config.download_url(&release),
+        format!(
+            "https://example.com/Se/rd/se/rd/Serde-1.0.0.crate?{}",
+            release.cksum
+        )
+    );
+}
+
+fn resolve_fixture(name: &str) -> crate::Result<Package> {
+    Package::from_index(read_test_file(&format!("resolve/{name}")))
+}
+
+#[test]
+fn test_resolver_unifies_compatible_requirements() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+
+    let resolved = resolution
+        .releases()
+        .iter()
+        .map(|release| format!("{} {}", release.name, release.vers))
+        .collect::<Vec<String>>();
+
+    // `a 1.1.0` is the newest match for `^1`, but `b` needs `=1.0.0`. The yanked `c 2.1.0` is
+    // skipped, and dev and optional dependencies aren't followed
+    assert_eq!(
+        resolved,
+        [
+            "a 1.0.0",
+            "app 1.0.0",
+            "b 1.0.0",
+            "c 1.4.0",
+            "c 2.0.0",
+            "d 1.0.0",
+            "winapi 0.3.9"
+        ]
+    );
+    assert_eq!(
+        resolution
+            .roots()
+            .map(|root| root.name.as_str())
+            .collect::<Vec<_>>(),
+        ["app"]
+    );
+    assert_eq!(resolution.get("c").count(), 2);
+}
+
+#[test]
+fn test_unifier_unifies_compatible_requirements() {
+    let index = ["1.0.50", "1.0.100", "1.0.150", "2.0.0"]
+        .map(|version| {
+            format!(
+                r#"{{"name":"serde","vers":"{version}","deps":[],"cksum":"{}","features":{{}},"yanked":false}}"#,
+                "0".repeat(64)
+            )
+        })
+        .join("\n");
+    let package = Package::from_index(index).expect("package from index");
+    let policy = SelectionPolicy::default();
+    let req = |req: &str| req.parse::<VersionReq>().expect("version req");
+    let versions = |unifier: &Unifier| {
+        unifier
+            .releases()
+            .iter()
+            .map(|release| release.vers.to_string())
+            .collect::<Vec<String>>()
+    };
+
+    let mut unifier = Unifier::new();
+    let selected = unifier
+        .select(&package, Some(&req("^1.0.100")), &policy)
+        .expect("selection");
+    assert_eq!(selected.release.vers, Version::new(1, 0, 150));
+    assert_eq!(selected.replaced, None);
+
+    // `^1.0.50` is satisfied by the release `^1.0.100` already selected
+    let selected = unifier
+        .select(&package, Some(&req("^1.0.50")), &policy)
+        .expect("selection");
+    assert_eq!(selected.release.vers, Version::new(1, 0, 150));
+    assert_eq!(selected.replaced, None);
+    assert_eq!(versions(&unifier), ["1.0.150"]);
+
+    // A requirement ruling out `1.0.150` replaces it with a release fitting every requirement
+    let selected = unifier
+        .select(&package, Some(&req(">=1.0.50, <1.0.120")), &policy)
+        .expect("selection");
+    assert_eq!(selected.release.vers, Version::new(1, 0, 100));
+    assert_eq!(
+        selected.replaced.map(|replaced| replaced.vers),
+        Some(Version::new(1, 0, 150))
+    );
+    assert_eq!(versions(&unifier), ["1.0.100"]);
+
+    // Both are kept when no release fits every requirement, as are incompatible releases
+    let selected = unifier
+        .select(&package, Some(&req("=1.0.50")), &policy)
+        .expect("selection");
+    assert_eq!(selected.replaced, None);
+    unifier.select(&package, Some(&req("^2")), &policy);
+    assert_eq!(versions(&unifier), ["1.0.100", "1.0.50", "2.0.0"]);
+}
+
+#[test]
+fn test_resolver_reports_conflicts() {
+    let roots = ["b", "a@=1.1.0"].map(|root| root.parse::<Query>().expect("parse query"));
+    let result = Resolver::new(resolve_fixture).resolve(&roots);
+
+    assert!(matches!(result, Err(Error::Resolve(_))));
+
+    // A feature no release of a root has is a mistake in the query, not a conflict
+    let roots = ["app?features=missing"
+        .parse::<Query>()
+        .expect("parse query")];
+    let result = Resolver::new(resolve_fixture).resolve(&roots);
+
+    assert!(matches!(result, Err(Error::UnknownFeature(feature)) if feature == "missing"));
+}
+
+#[test]
+fn test_resolver_skips_other_targets() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolve_for = |target: &str| {
+        Resolver::new(resolve_fixture)
+            .with_target(target)
+            .resolve(&roots)
+            .expect("resolve")
+    };
+
+    assert!(resolve_for("x86_64-pc-windows-msvc")
+        .get("winapi")
+        .next()
+        .is_some());
+    assert!(resolve_for("x86_64-unknown-linux-gnu")
+        .get("winapi")
+        .next()
+        .is_none());
+}
+
+#[test]
+fn test_resolver_follows_enabled_features() {
+    let resolve = |query: &str| {
+        let roots = [query.parse::<Query>().expect("parse query")];
+        Resolver::new(resolve_fixture)
+            .resolve(&roots)
+            .expect("resolve")
+            .releases()
+            .iter()
+            .map(|release| release.name.clone())
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(resolve("tool"), ["tool"]);
+    assert_eq!(resolve("tool+fast"), ["simd", "tool"]);
+    // `simd?/logging` doesn't enable `simd` on it's own
+    assert_eq!(resolve("tool+logging"), ["log", "tool"]);
+    assert_eq!(
+        resolve("tool+fast,logging"),
+        ["log", "simd", "tool", "trace"]
+    );
+}
+
+#[test]
+fn test_resolver_follows_root_dev_dependencies() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+
+    // `d` has a dev dependency on `criterion` too, but it's not a root
+    assert!(resolution.get("tempfile").next().is_some());
+    assert!(resolution.get("criterion").next().is_none());
+}
+
+#[test]
+fn test_resolution_graph() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+    let describe = |id: NodeId| {
+        let release = graph.release(id);
+        format!("{} {}", release.name, release.vers)
+    };
+
+    let [app] = graph.roots() else {
+        panic!("expected one root");
+    };
+    let dependencies = graph
+        .dependencies(*app)
+        .map(|edge| describe(edge.to))
+        .collect::<Vec<String>>();
+    assert_eq!(
+        dependencies,
+        ["a 1.0.0", "b 1.0.0", "c 2.0.0", "d 1.0.0", "winapi 0.3.9"]
+    );
+
+    let a = graph
+        .find("a", &semver::Version::new(1, 0, 0))
+        .expect("find a");
+    let dependents = graph
+        .dependents(a)
+        .map(|edge| describe(edge.from))
+        .collect::<Vec<String>>();
+    assert_eq!(dependents, ["app 1.0.0", "b 1.0.0", "d 1.0.0"]);
+
+    // `d` needs `c ^1.2`, so it's edge goes to the other version of `c`
+    let d = graph
+        .find("d", &semver::Version::new(1, 0, 0))
+        .expect("find d");
+    let edge = graph
+        .dependencies(d)
+        .find(|edge| edge.dependency.name == "c")
+        .expect("edge to c");
+    assert_eq!(describe(edge.to), "c 1.4.0");
+    assert_eq!(edge.dependency.req.to_string(), "^1.2");
+
+    assert_eq!(graph.walk(*app).count(), graph.len());
+    assert_eq!(
+        graph.walk(d).map(describe).collect::<Vec<String>>(),
+        ["d 1.0.0", "c 1.4.0", "a 1.0.0"]
+    );
+}
+
+#[test]
+fn test_graph_to_dot() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+    let dot = resolution.graph().to_dot();
+    let lines = dot.lines().collect::<Vec<&str>>();
+
+    assert_eq!(lines.first(), Some(&"digraph dependencies {"));
+    assert_eq!(lines.last(), Some(&"}"));
+    assert!(lines.contains(&r#"    n1 [label="app 1.0.0", penwidth="2"];"#));
+    // `c` is in the graph twice, at semver-incompatible versions
+    assert!(
+        lines.contains(&r#"    n3 [fillcolor="lightyellow", label="c 1.4.0", style="filled"];"#)
+    );
+    assert!(lines.contains(&r#"    n1 -> n6 [label="^3", style="dashed"];"#));
+}
+
+#[test]
+fn test_graph_to_mermaid() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+    let mermaid = resolution.graph().to_mermaid();
+    let lines = mermaid.lines().collect::<Vec<&str>>();
+
+    assert_eq!(lines.first(), Some(&"graph TD"));
+    assert!(lines.contains(&r#"    n1["app 1.0.0"]"#));
+    assert!(lines.contains(&r#"    n1 -->|"^1"| n0"#));
+    assert!(lines.contains(&r#"    n1 -.->|"^3"| n6"#));
+    assert!(lines.contains(&"    class n1 root"));
+    assert!(lines.contains(&"    class n3,n4 duplicate"));
+    assert!(!mermaid.contains("yanked"));
+}
+
+#[test]
+fn test_graph_to_graphml() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+    let graphml = resolution.graph().to_graphml();
+    let lines = graphml.lines().collect::<Vec<&str>>();
+
+    assert_eq!(
+        lines.first(),
+        Some(&r#"<?xml version="1.0" encoding="UTF-8"?>"#)
+    );
+    assert_eq!(lines.last(), Some(&"</graphml>"));
+    assert!(graphml.contains(
+        r#"    <node id="n1">
+      <data key="name">app</data>
+      <data key="version">1.0.0</data>
+      <data key="yanked">false</data>
+      <data key="root">true</data>
+    </node>"#
+    ));
+    assert!(graphml.contains(
+        r#"    <edge source="n1" target="n6">
+      <data key="kind">dev</data>
+      <data key="req">^3</data>
+      <data key="optional">false</data>
+    </edge>"#
+    ));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| line.starts_with("    <edge "))
+            .count(),
+        resolution.graph().edges().len()
+    );
+}
+
+#[test]
+fn test_graph_build_order() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+
+    let order = graph
+        .build_order()
+        .map(|id| {
+            id.map(|id| {
+                let release = graph.release(id);
+                format!("{} {}", release.name, release.vers)
+            })
+        })
+        .collect::<crate::Result<Vec<String>>>()
+        .expect("build order");
+    let position = |release: &str| order.iter().position(|built| built == release);
+
+    assert_eq!(order.len(), graph.len());
+    assert_eq!(order.last().map(String::as_str), Some("app 1.0.0"));
+    assert!(position("a 1.0.0") < position("b 1.0.0"));
+    assert!(position("c 1.4.0") < position("d 1.0.0"));
+}
+
+#[test]
+fn test_graph_build_order_cycle() {
+    let release = |name: &str| Release {
+        name: name.to_owned(),
+        ..feats_release()
+    };
+    let dependency = |name: &str| Dependency {
+        name: name.to_owned(),
+        req: "^1".parse().expect("version req"),
+        ..feats_release().deps[0].clone()
+    };
+
+    let mut graph = DependencyGraph::new();
+    let a = graph.add_release(release("a"));
+    let b = graph.add_release(release("b"));
+    let c = graph.add_release(release("c"));
+    graph.add_edge(a, b, dependency("b"));
+    graph.add_edge(b, c, dependency("c"));
+    graph.add_edge(c, b, dependency("b"));
+
+    // `a` waits on the cycle, so nothing can be built
+    let order = graph.build_order().collect::<Vec<crate::Result<NodeId>>>();
+    assert!(matches!(
+        order.as_slice(),
+        [Err(Error::Cycle(cycle))] if cycle.len() == 3 && cycle[0] == cycle[2]
+    ));
+}
+
+#[test]
+fn test_graph_paths_to() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+    let paths_to = |name: &str| {
+        graph
+            .paths_to(name)
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|id| graph.release(id).name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" -> ")
+            })
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(
+        paths_to("a"),
+        ["app -> a", "app -> b -> a", "app -> d -> a"]
+    );
+    // `d` pulls in `c 1.4.0`, while `app` itself depends on `c 2.0.0`
+    assert_eq!(paths_to("c"), ["app -> d -> c", "app -> c"]);
+    assert_eq!(paths_to("app"), ["app"]);
+    assert!(paths_to("tempfile").is_empty());
+}
+
+#[test]
+fn test_graph_paths_to_repeated() {
+    let release = |name: &str| Release {
+        name: name.to_owned(),
+        ..feats_release()
+    };
+    let dependency = |name: &str| Dependency {
+        name: name.to_owned(),
+        req: "^1".parse().expect("version req"),
+        ..feats_release().deps[0].clone()
+    };
+
+    // A chain of diamonds, which has 2^3 distinct paths from `root` to `target`
+    let mut graph = DependencyGraph::new();
+    let root = graph.add_release(release("root"));
+    graph.add_root(root);
+    let mut top = root;
+    for level in 0..3 {
+        let left = graph.add_release(release(&format!("left{level}")));
+        let right = graph.add_release(release(&format!("right{level}")));
+        let bottom = graph.add_release(release(&format!("bottom{level}")));
+        graph.add_edge(top, left, dependency(&format!("left{level}")));
+        graph.add_edge(top, right, dependency(&format!("right{level}")));
+        graph.add_edge(left, bottom, dependency(&format!("bottom{level}")));
+        graph.add_edge(right, bottom, dependency(&format!("bottom{level}")));
+        top = bottom;
+    }
+
+    let paths = graph
+        .paths_to("bottom2")
+        .into_iter()
+        .map(|path| {
+            path.into_iter()
+                .map(|id| graph.release(id).name.as_str())
+                .collect::<Vec<&str>>()
+                .join(" -> ")
+        })
+        .collect::<Vec<String>>();
+
+    // Each release's dependents are followed once, and later paths stop at it
+    assert_eq!(
+        paths,
+        [
+            "root -> left0 -> bottom0 -> left1 -> bottom1 -> left2 -> bottom2",
+            "root -> right0 -> bottom0 -> left1 -> bottom1 -> left2 -> bottom2",
+            "bottom0 -> right1 -> bottom1 -> left2 -> bottom2",
+            "bottom1 -> right2 -> bottom2",
+        ]
+    );
+}
+
+#[test]
+fn test_resolution_to_lockfile() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let lockfile = resolution.to_lockfile_toml();
+    let checksum = "0".repeat(64);
+
+    assert!(lockfile.contains("\nversion = 3\n"));
+    // `c` is locked at two versions, so dependencies on it name the version too
+    assert!(lockfile.contains(&format!(
+        r#"
+[[package]]
+name = "d"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{checksum}"
+dependencies = [
+ "a",
+ "c 1.4.0",
+]
+"#
+    )));
+    assert!(lockfile.ends_with(&format!(
+        r#"
+[[package]]
+name = "winapi"
+version = "0.3.9"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{checksum}"
+"#
+    )));
+
+    let custom = ["app"
+        .parse::<Query>()
+        .expect("parse query")
+        .with_index("sparse+https://example.com/index/")];
+    let lockfile = Resolver::new(resolve_fixture)
+        .resolve(&custom)
+        .expect("resolve")
+        .to_lockfile_toml();
+    assert!(lockfile.contains(r#"source = "sparse+https://example.com/index/""#));
+}
+
+#[test]
+fn test_lockfile_roots() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let mut lockfile = Lockfile::from_graph(resolution.graph(), CRATES_IO_INDEX_URL);
+
+    lockfile.packages.push(LockedPackage {
+        name: "member".to_owned(),
+        version: Version::new(0, 1, 0),
+        source: None,
+        checksum: None,
+        dependencies: vec!["c 1.4.0".to_owned()],
+    });
+
+    let roots = lockfile
+        .roots()
+        .map(|root| format!("{} {}", root.name, root.version))
+        .collect::<Vec<String>>();
+
+    // `app` isn't depended on, and the workspace member depends on `c 1.4.0` directly
+    assert_eq!(roots, ["app 1.0.0", "c 1.4.0"]);
+}
+
+#[test]
+fn test_lockfile_compare() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+    let mut lockfile = Lockfile::from_graph(graph, CRATES_IO_INDEX_URL);
+
+    assert!(lockfile.compare(graph).is_empty());
+
+    let locked = |lockfile: &Lockfile, name: &str, version: &str| {
+        let version = Version::parse(version).expect("version");
+        lockfile
+            .packages
+            .iter()
+            .position(|package| package.name == name && package.version == version)
+            .expect("locked package")
+    };
+
+    let app = locked(&lockfile, "app", "1.0.0");
+    lockfile.packages[app].checksum = Some("f".repeat(64));
+    let c = locked(&lockfile, "c", "1.4.0");
+    lockfile.packages[c].version = Version::new(1, 3, 2);
+    let winapi = locked(&lockfile, "winapi", "0.3.9");
+    lockfile.packages.remove(winapi);
+    lockfile.packages.push(LockedPackage {
+        name: "unused".to_owned(),
+        version: Version::new(0, 1, 0),
+        source: Some("registry+https://github.com/rust-lang/crates.io-index".to_owned()),
+        checksum: None,
+        dependencies: Vec::new(),
+    });
+    let diff = lockfile.compare(graph);
+
+    // `c` is locked at two versions, and only the compatible one changed
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].name, "c");
+    assert_eq!(diff.changed[0].version.old, Version::new(1, 3, 2));
+    assert_eq!(diff.changed[0].version.new, Version::new(1, 4, 0));
+    assert!(!diff.changed[0].bump.is_breaking());
+    assert_eq!(
+        diff.added,
+        [PackageVersion {
+            name: "winapi".to_owned(),
+            version: Version::new(0, 3, 9),
+        }]
+    );
+    assert_eq!(
+        diff.removed,
+        [PackageVersion {
+            name: "unused".to_owned(),
+            version: Version::new(0, 1, 0),
+        }]
+    );
+    assert_eq!(diff.checksums.len(), 1);
+    assert_eq!(diff.checksums[0].name, "app");
+    assert_eq!(diff.checksums[0].checksum.old, "f".repeat(64));
+
+    // Checksums are hex, so one written in uppercase is still the same checksum
+    let libc = Package::from_index(read_test_file("libc.index")).expect("package from index");
+    let mut graph = DependencyGraph::new();
+    let root = graph.add_release(
+        libc.version(&"=0.1.12".parse().expect("semver"))
+            .expect("matching libc version")
+            .clone(),
+    );
+    graph.add_root(root);
+    let mut lockfile = Lockfile::from_graph(&graph, CRATES_IO_INDEX_URL);
+    for package in &mut lockfile.packages {
+        package.checksum = package.checksum.as_deref().map(str::to_uppercase);
+    }
+    assert!(lockfile.compare(&graph).is_empty());
+}
+
+#[test]
+fn test_locked_package_index_url() {
+    let locked = |source: Option<&str>| LockedPackage {
+        name: "libc".to_owned(),
+        version: Version::new(0, 2, 0),
+        source: source.map(ToOwned::to_owned),
+        checksum: None,
+        dependencies: Vec::new(),
+    };
+
+    assert_eq!(
+        locked(Some(
+            "registry+https://github.com/rust-lang/crates.io-index"
+        ))
+        .index_url(),
+        Some(CRATES_IO_INDEX_URL.to_owned())
+    );
+    assert_eq!(
+        locked(Some("sparse+https://index.crates.io/")).index_url(),
+        Some(CRATES_IO_INDEX_URL.to_owned())
+    );
+    assert_eq!(
+        locked(Some("sparse+https://registry.example.com/index/")).index_url(),
+        Some("https://registry.example.com/index".to_owned())
+    );
+    assert_eq!(
+        locked(Some("registry+https://git.example.com/index")).index_url(),
+        None
+    );
+    assert_eq!(locked(None).index_url(), None);
+}
+
+#[test]
+fn test_locked_package_audit() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let locked = |version: &str| LockedPackage {
+        name: "libc".to_owned(),
+        version: Version::parse(version).expect("version"),
+        source: Some("registry+https://github.com/rust-lang/crates.io-index".to_owned()),
+        checksum: None,
+        dependencies: Vec::new(),
+    };
+
+    assert_eq!(locked("0.1.11").audit(&pkg), [LockIssue::Yanked]);
+    assert_eq!(locked("0.1.12").audit(&pkg), []);
+    assert_eq!(locked("0.99.0").audit(&pkg), [LockIssue::Missing]);
+
+    let cksum = pkg
+        .version(&"=0.1.12".parse().expect("semver"))
+        .expect("matching libc version")
+        .cksum
+        .clone();
+    let mut matching = locked("0.1.12");
+    matching.checksum = Some(cksum.to_uppercase());
+    assert_eq!(matching.audit(&pkg), []);
+
+    let mut tampered = locked("0.1.11");
+    tampered.checksum = Some("0".repeat(64));
+    assert_eq!(
+        tampered.audit(&pkg),
+        [
+            LockIssue::ChecksumMismatch {
+                locked: "0".repeat(64),
+                index: pkg
+                    .version(&"=0.1.11".parse().expect("semver"))
+                    .expect("matching libc version")
+                    .cksum
+                    .clone(),
+            },
+            LockIssue::Yanked,
+        ]
+    );
+}
+
+#[test]
+fn test_manifest_dependencies() {
+    // The JSON equivalent of a `Cargo.toml`
+    let manifest: Manifest = serde_json::from_str(
+        r#"{
+            "package": { "name": "app", "version": "0.1.0" },
+            "dependencies": {
+                "serde": { "version": "1.0.219", "features": ["derive"] },
+                "rand": { "version": "0.6", "package": "rand_core", "default-features": false },
+                "helper": { "path": "../helper" },
+                "shared": { "workspace": true },
+                "anything": { "git": "https://example.com/anything" },
+                "internal": { "version": "2", "registry": "company" }
+            },
+            "build-dependencies": { "cc": "1" },
+            "dev-dependencies": { "criterion": { "version": "0.5", "optional": false } },
+            "target": {
+                "cfg(windows)": { "dependencies": { "winapi": "0.3.9" } }
+            }
+        }"#,
+    )
+    .expect("manifest");
+
+    let dependencies = manifest.dependencies().expect("dependencies");
+    let queries = dependencies
+        .iter()
+        .map(|dependency| {
+            let target = dependency.target.as_deref().unwrap_or("all");
+            format!(
+                "{} {} {target} {}",
+                dependency.kind,
+                dependency.query_spec(),
+                dependency.default_features
+            )
+        })
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        queries,
+        [
+            "normal internal@^2 all true",
+            "normal rand_core@^0.6 all false",
+            "normal serde@^1.0.219?features=derive all true",
+            "build cc@^1 all true",
+            "dev criterion@^0.5 all true",
+            "normal winapi@^0.3.9 cfg(windows) true",
+        ]
+    );
+
+    assert_eq!(dependencies[0].registry.as_deref(), Some("company"));
+    assert_eq!(dependencies[1].registry, None);
+
+    let query = dependencies[2]
+        .query_spec()
+        .parse::<Query>()
+        .expect("parse query");
+    assert_eq!(query.name(), "serde");
+    assert_eq!(query.features(), ["derive"]);
+}
+
+#[test]
+fn test_manifest_dependencies_in_workspace() {
+    let root: Manifest = serde_json::from_str(
+        r#"{
+            "workspace": {
+                "members": ["crates/*"],
+                "dependencies": {
+                    "serde": { "version": "1.0.219", "features": ["derive"], "default-features": false },
+                    "helper": { "path": "crates/helper" }
+                }
+            }
+        }"#,
+    )
+    .expect("workspace manifest");
+    let member: Manifest = serde_json::from_str(
+        r#"{
+            "dependencies": {
+                "serde": { "workspace": true, "features": ["rc"], "optional": true },
+                "helper": { "workspace": true },
+                "undeclared": { "workspace": true },
+                "log": "0.4"
+            }
+        }"#,
+    )
+    .expect("member manifest");
+    let workspace: &Workspace = root.workspace.as_ref().expect("workspace");
+
+    assert_eq!(workspace.members, ["crates/*"]);
+
+    // Without the workspace, inherited dependencies are left out
+    let names = |dependencies: Vec<Dependency>| {
+        dependencies
+            .into_iter()
+            .map(|dependency| dependency.name)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(names(member.dependencies().expect("dependencies")), ["log"]);
+    assert_eq!(member.inherited(), ["helper", "serde", "undeclared"]);
+
+    let dependencies = member.dependencies_in(workspace).expect("dependencies");
+    assert_eq!(names(dependencies.clone()), ["log", "serde"]);
+
+    let serde = &dependencies[1];
+    assert_eq!(serde.req, "^1.0.219".parse().expect("semver"));
+    assert_eq!(serde.features, ["derive", "rc"]);
+    assert!(!serde.default_features);
+    assert!(serde.optional);
+}
+
+#[test]
+fn test_package_outdated() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let policy = SelectionPolicy::default();
+    let version = |version: &str| Version::parse(version).expect("version");
+
+    let locked = pkg.outdated(
+        &"^0.2.100".parse().expect("semver"),
+        Some(&version("0.2.150")),
+        &policy,
+    );
+    assert_eq!(locked.current, Some(version("0.2.150")));
+    assert_eq!(locked.compatible, Some(version("0.2.153")));
+    assert_eq!(locked.latest, Some(version("0.2.153")));
+    assert!(locked.is_outdated());
+
+    // Without a lockfile, the newest release matching the requirement is in use
+    let held_back = pkg.outdated(&"~0.1.5".parse().expect("semver"), None, &policy);
+    assert_eq!(held_back.current, held_back.compatible);
+    assert!(held_back.is_outdated());
+
+    let up_to_date = pkg.outdated(&"^0.2".parse().expect("semver"), None, &policy);
+    assert_eq!(up_to_date.current, Some(version("0.2.153")));
+    assert!(!up_to_date.is_outdated());
+}
+
+#[test]
+fn test_dependency_to_manifest_line() {
+    let dependency = |req: &str| Dependency {
+        name: "serde".to_owned(),
+        req: req.parse().expect("semver"),
+        features: Vec::new(),
+        optional: false,
+        default_features: true,
+        target: None,
+        kind: DependencyKind::Normal,
+        registry: None,
+        package: None,
+    };
+
+    assert_eq!(
+        dependency("^1.0.219").to_manifest_line(),
+        r#"serde = "1.0.219""#
+    );
+    assert_eq!(dependency("~1.0").to_manifest_line(), r#"serde = "~1.0""#);
+    assert_eq!(
+        dependency(">=1, <2").to_manifest_line(),
+        r#"serde = ">=1, <2""#
+    );
+
+    let detailed = Dependency {
+        features: vec!["derive".to_owned(), "rc".to_owned()],
+        default_features: false,
+        optional: true,
+        package: Some("serde_core".to_owned()),
+        ..dependency("1.0.219")
+    };
+    assert_eq!(
+        detailed.to_manifest_line(),
+        r#"serde = { version = "1.0.219", package = "serde_core", default-features = false, features = ["derive", "rc"], optional = true }"#
+    );
+}
+
+#[test]
+fn test_update_requirements() {
+    let manifest = r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] } # serialization
+log = "0.4"
+"rand" = '0.8'
+tokio = { features = ["version", "a,b"], version = "1.0" }
+semver-version = { package = "semver-version", "version" = "0.1" }
+
+[dev-dependencies]
+log = "=0.4.0"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2.100"
+
+[build-dependencies.cc]
+version = "1.0.50"
+features = ["parallel"]
+"#;
+    let update = |name: &str, from: &str, to: &str| RequirementUpdate {
+        name: name.to_owned(),
+        from: from.parse().expect("semver"),
+        to: to.to_owned(),
+    };
+    let updates = [
+        update("serde", "^1.0", "1.0.219"),
+        update("log", "^0.4", "0.4.27"),
+        update("rand", "^0.8", "0.9.1"),
+        update("tokio", "^1.0", "1.45.0"),
+        update("semver-version", "^0.1", "0.1.2"),
+        update("libc", "^0.2.100", "0.2.172"),
+        update("cc", "^1.0.50", "1.2.0"),
+        // The package's own version isn't a dependency
+        update("version", "^0.1.0", "9.9.9"),
+    ];
+
+    assert_eq!(
+        update_requirements(manifest, &updates),
+        r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1.0.219", features = ["derive"] } # serialization
+log = "0.4.27"
+"rand" = '0.9.1'
+tokio = { features = ["version", "a,b"], version = "1.45.0" }
+semver-version = { package = "semver-version", "version" = "0.1.2" }
+
+[dev-dependencies]
+log = "=0.4.0"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2.172"
+
+[build-dependencies.cc]
+version = "1.2.0"
+features = ["parallel"]
+"#
+    );
+}
+
+#[test]
+fn test_outdated_suggested_req() {
+    let data = read_test_file("libc.index");
+    let pkg = Package::from_index(data).expect("package from index");
+    let policy = SelectionPolicy::default();
+
+    let outdated = pkg.outdated(&"^0.1".parse().expect("semver"), None, &policy);
+    assert_eq!(
+        outdated.suggested_req(),
+        Some("^0.2.153".parse().expect("semver"))
+    );
+
+    let up_to_date = pkg.outdated(&"^0.2".parse().expect("semver"), None, &policy);
+    assert_eq!(up_to_date.suggested_req(), None);
+}
+
+#[test]
+fn test_graph_msrv() {
+    // `c` has two releases in the tree that both declare the highest rust version
+    let fetcher = |name: &str| {
+        let rust_version = match name {
+            "b" => "1.60",
+            "c" => "1.70",
+            "d" => "1.56.1",
+            _ => return resolve_fixture(name),
+        };
+        let index = read_test_file(&format!("resolve/{name}")).replace(
+            r#""deps":"#,
+            &format!(r#""rust_version": "{rust_version}", "deps":"#),
+        );
+        Package::from_index(index)
+    };
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(fetcher).resolve(&roots).expect("resolve");
+    let graph = resolution.graph();
+    let msrv = graph.msrv();
+
+    let releases = |ids: &[NodeId]| {
+        ids.iter()
+            .map(|id| format!("{} {}", graph.release(*id).name, graph.release(*id).vers))
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(msrv.rust_version, Some(Version::new(1, 70, 0)));
+    assert_eq!(releases(&msrv.imposed_by), ["c 1.4.0", "c 2.0.0"]);
+    assert_eq!(
+        releases(&msrv.unspecified),
+        ["a 1.0.0", "app 1.0.0", "winapi 0.3.9"]
+    );
+
+    assert!(msrv.supports(&Version::new(1, 70, 0)));
+    assert!(msrv.supports(&"1.70.0-nightly".parse().expect("semver")));
+    assert!(!msrv.supports(&Version::new(1, 69, 0)));
+
+    let empty = Resolver::new(resolve_fixture)
+        .resolve(&roots)
+        .expect("resolve")
+        .graph()
+        .msrv();
+    assert_eq!(empty.rust_version, None);
+    assert!(empty.supports(&Version::new(1, 0, 0)));
+}
+
+#[test]
+fn test_release_to_dependency() {
+    let libc = Package::from_index(read_test_file("libc.index")).expect("package from index");
+    let release = libc.latest().expect("latest release");
+    assert_eq!(
+        release.to_dependency().req,
+        "^0.2.153".parse().expect("semver")
+    );
+    assert_eq!(
+        release.to_dependency().to_manifest_line(),
+        r#"libc = "0.2.153""#
+    );
+
+    let with_features = Dependency {
+        features: vec!["extra_traits".to_owned()],
+        ..release.to_dependency()
+    };
+    assert_eq!(
+        with_features.to_manifest_line(),
+        r#"libc = { version = "0.2.153", features = ["extra_traits"] }"#
+    );
+
+    let policy = Package::from_index(read_test_file("policy.index")).expect("package from index");
+    let prerelease = policy.latest().expect("latest release");
+    assert_eq!(
+        prerelease.to_dependency().to_manifest_line(),
+        r#"policy = "2.0.0-beta.1""#
+    );
+}
+
+#[test]
+fn test_graph_to_tree() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([
+            DependencyKind::Normal,
+            DependencyKind::Build,
+            DependencyKind::Dev,
+        ])
+        .resolve(&roots)
+        .expect("resolve");
+
+    // `b` is a build dependency of `d` too, so it's listed twice but only expanded once
+    let mut graph = resolution.into_graph();
+    let find = |graph: &DependencyGraph, name: &str| graph.named(name).next().expect("node");
+    let (b, d) = (find(&graph, "b"), find(&graph, "d"));
+    let build = Dependency {
+        name: "b".to_owned(),
+        req: "^1".parse().expect("semver"),
+        features: Vec::new(),
+        optional: false,
+        default_features: true,
+        target: None,
+        kind: DependencyKind::Build,
+        registry: None,
+        package: None,
+    };
+    graph.add_edge(d, b, build);
+
+    assert_eq!(
+        graph.to_tree(None, Charset::Utf8),
+        "\
+app v1.0.0
+├── a v1.0.0
+├── b v1.0.0
+│   └── a v1.0.0
+├── c v2.0.0
+├── d v1.0.0
+│   ├── a v1.0.0
+│   └── c v1.4.0
+│   [build-dependencies]
+│   └── b v1.0.0 (*)
+└── winapi v0.3.9
+[dev-dependencies]
+└── tempfile v3.10.1
+"
+    );
+    assert_eq!(
+        graph.to_tree(Some(1), Charset::Ascii),
+        "\
+app v1.0.0
+|-- a v1.0.0
+|-- b v1.0.0
+|-- c v2.0.0
+|-- d v1.0.0
+`-- winapi v0.3.9
+[dev-dependencies]
+`-- tempfile v3.10.1
+"
+    );
+}
+
+#[test]
+fn test_sbom_cyclonedx() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+    let version = Version::new(1, 0, 0);
+
+    let sbom = Sbom::new(graph, CRATES_IO_INDEX_URL)
+        .with_created("2024-05-01T12:00:00Z")
+        .with_license("a", &version, "MIT/Apache-2.0")
+        .with_supplier("a", Supplier::Person("Ferris".to_owned()));
+    let bom = sbom.to_cyclonedx();
+
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["metadata"]["timestamp"], "2024-05-01T12:00:00Z");
+    assert_eq!(
+        bom["components"].as_array().map(Vec::len),
+        Some(graph.len())
+    );
+
+    let component = bom["components"]
+        .as_array()
+        .and_then(|components| components.iter().find(|c| c["name"] == "a"))
+        .expect("component");
+    assert_eq!(component["purl"], "pkg:cargo/a@1.0.0");
+    assert_eq!(component["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(component["licenses"][0]["expression"], "MIT OR Apache-2.0");
+    assert_eq!(component["supplier"]["name"], "Ferris");
+
+    let app = bom["dependencies"]
+        .as_array()
+        .and_then(|deps| deps.iter().find(|dep| dep["ref"] == "pkg:cargo/app@1.0.0"))
+        .expect("dependencies of app");
+    let depends_on = app["dependsOn"].as_array().expect("depends on");
+    assert!(depends_on.contains(&"pkg:cargo/tempfile@3.10.1".into()));
+
+    // Releases from other registries name the index they're from
+    let sbom = Sbom::new(graph, "https://example.com/index");
+    let release = graph.release(graph.named("a").next().expect("a"));
+    assert_eq!(
+        sbom.purl(release),
+        "pkg:cargo/a@1.0.0?repository_url=https://example.com/index"
+    );
+}
+
+#[test]
+fn test_sbom_spdx() {
+    let roots = ["app".parse::<Query>().expect("parse query")];
+    let resolution = Resolver::new(resolve_fixture)
+        .with_dep_kinds([DependencyKind::Normal, DependencyKind::Dev])
+        .resolve(&roots)
+        .expect("resolve");
+    let graph = resolution.graph();
+
+    let spdx = Sbom::new(graph, CRATES_IO_INDEX_URL)
+        .with_created("2024-05-01T12:00:00Z")
+        .with_supplier("a", Supplier::Organization("rust-lang".to_owned()))
+        .to_spdx();
+
+    assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+    assert_eq!(spdx["name"], "app-1.0.0");
+    assert_eq!(spdx["creationInfo"]["created"], "2024-05-01T12:00:00Z");
+    assert!(spdx["documentNamespace"]
+        .as_str()
+        .is_some_and(|namespace| namespace.starts_with("https://spdx.org/spdxdocs/app-1.0.0-")));
+
+    let package = spdx["packages"]
+        .as_array()
+        .and_then(|packages| packages.iter().find(|p| p["name"] == "a"))
+        .expect("package");
+    assert_eq!(package["SPDXID"], "SPDXRef-a-1.0.0");
+    assert_eq!(package["supplier"], "Organization: rust-lang");
+    assert_eq!(package["licenseDeclared"], "NOASSERTION");
+    assert_eq!(package["checksums"][0]["algorithm"], "SHA256");
+
+    let relationships = spdx["relationships"].as_array().expect("relationships");
+    let relationship = |element: &str, kind: &str, related: &str| {
+        serde_json::json!({
+            "spdxElementId": element,
+            "relationshipType": kind,
+            "relatedSpdxElement": related,
+        })
+    };
+    for expected in [
+        relationship("SPDXRef-DOCUMENT", "DESCRIBES", "SPDXRef-app-1.0.0"),
+        relationship("SPDXRef-app-1.0.0", "DEPENDS_ON", "SPDXRef-a-1.0.0"),
+        relationship(
+            "SPDXRef-tempfile-3.10.1",
+            "DEV_DEPENDENCY_OF",
+            "SPDXRef-app-1.0.0",
+        ),
+    ] {
+        assert!(relationships.contains(&expected), "missing {expected}");
+    }
+}
@@ -0,0 +1,121 @@
+//! Writing output as YAML, for `--type=yaml`
+//!
+//! Values are converted to JSON values first, so the YAML has the same structure as the JSON
+//! output. Mappings and sequences are written in block style, and strings are only quoted when
+//! they'd otherwise be read as something else, like `1.0` as a number or `no` as a boolean
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Write a value as a YAML document
+pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value)?;
+
+    let mut yaml = String::new();
+    if is_block(&value) {
+        write_block(&mut yaml, &value, 0);
+    } else {
+        yaml.push_str(&scalar(&value));
+        yaml.push('\n');
+    }
+
+    Ok(yaml)
+}
+
+/// Whether a value is written as a block, which is any mapping or sequence that isn't empty
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Write a non-empty mapping or sequence, with each line indented by `indent` spaces
+fn write_block(yaml: &mut String, value: &Value, indent: usize) {
+    let padding = " ".repeat(indent);
+
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                yaml.push_str(&format!("{padding}{}:", string(key)));
+                write_nested(yaml, value, indent);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                if !is_block(item) {
+                    yaml.push_str(&format!("{padding}- {}\n", scalar(item)));
+                    continue;
+                }
+
+                // The item starts on the dash's line, like `- name: serde`
+                let mut nested = String::new();
+                write_block(&mut nested, item, indent + 2);
+                nested.replace_range(indent..indent + 2, "- ");
+                yaml.push_str(&nested);
+            }
+        }
+        _ => unreachable!("only mappings and sequences are written as blocks"),
+    }
+}
+
+/// Write the value of a mapping entry, after it's key
+fn write_nested(yaml: &mut String, value: &Value, indent: usize) {
+    if is_block(value) {
+        yaml.push('\n');
+        write_block(yaml, value, indent + 2);
+    } else {
+        yaml.push(' ');
+        yaml.push_str(&scalar(value));
+        yaml.push('\n');
+    }
+}
+
+/// Write a scalar, or an empty mapping or sequence, in flow style
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_owned(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => string(value),
+        Value::Array(_) => "[]".to_owned(),
+        Value::Object(_) => "{}".to_owned(),
+    }
+}
+
+/// Write a string, double quoted unless it's safe to leave plain
+fn string(value: &str) -> String {
+    if is_plain(value) {
+        return value.to_owned();
+    }
+
+    // JSON's escapes are valid in YAML's double quoted strings
+    Value::String(value.to_owned()).to_string()
+}
+
+/// Whether a string reads back as the same string without quotes
+fn is_plain(value: &str) -> bool {
+    const RESERVED: [&str; 11] = [
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~", "",
+    ];
+
+    if RESERVED.contains(&value.to_lowercase().as_str())
+        || value.starts_with(' ')
+        || value.ends_with(' ')
+    {
+        return false;
+    }
+
+    // Anything that may be a number is quoted, but versions like `1.0.219` are left plain
+    if value.starts_with(['+', '-', '.'])
+        || (value.starts_with(|char: char| char.is_ascii_digit()) && value.matches('.').count() < 2)
+    {
+        return false;
+    }
+
+    value
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || " _-./+()".contains(char))
+}